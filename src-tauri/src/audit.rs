@@ -0,0 +1,258 @@
+use crate::device::{
+    DeviceCapabilities, DriverStatus, GamepadState, HideCandidate, PhysicalDevice,
+};
+use crate::error::Result;
+use crate::mapping::AxisOverride;
+use crate::platform::{
+    DeviceEnumerator, DeviceHider, DeviceSelection, PlatformServices, VirtualControllerManager,
+};
+use std::cell::Cell;
+use std::collections::BTreeMap;
+use std::io::{BufRead, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Past this size the audit file is rotated to `audit.log.1` before the next write.
+const MAX_AUDIT_BYTES: u64 = 1024 * 1024;
+
+/// `Settings::audit_log_enabled`, mirrored here so every call site can check it
+/// without reaching `AppState`.
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Serializes appends (and rotation) across threads.
+static WRITE_LOCK: Mutex<()> = Mutex::new(());
+
+thread_local! {
+    /// Caller tag set by `caller` for the current thread.
+    static CALLER: Cell<Option<&'static str>> = const { Cell::new(None) };
+}
+
+pub fn set_enabled(enabled: bool) {
+    if ENABLED.swap(enabled, Ordering::SeqCst) != enabled {
+        log::info!(
+            "Device audit log {}",
+            if enabled { "enabled" } else { "disabled" }
+        );
+    }
+}
+
+pub fn path() -> Option<PathBuf> {
+    crate::config::data_dir().map(|d| d.join("audit.log"))
+}
+
+/// Restores the previous caller tag when dropped.
+pub struct CallerGuard(Option<&'static str>);
+
+impl Drop for CallerGuard {
+    fn drop(&mut self) {
+        CALLER.with(|c| c.set(self.0));
+    }
+}
+
+/// Tag device changes made on this thread with `name` (e.g. "reset") until the
+/// guard is dropped. Untagged changes are attributed by thread name.
+pub fn caller(name: &'static str) -> CallerGuard {
+    CallerGuard(CALLER.with(|c| c.replace(Some(name))))
+}
+
+fn current_caller() -> String {
+    if let Some(name) = CALLER.with(Cell::get) {
+        return name.to_string();
+    }
+    match std::thread::current().name() {
+        Some(name) if name.starts_with("padswitch-") => name["padswitch-".len()..].to_string(),
+        _ => "command".to_string(),
+    }
+}
+
+/// Append one device state change to the audit file, if auditing is on. Write
+/// failures only go to the regular log.
+pub fn record<T>(action: &str, target: &str, result: &Result<T>) {
+    if !ENABLED.load(Ordering::SeqCst) {
+        return;
+    }
+    let outcome = match result {
+        Ok(_) => "ok".to_string(),
+        Err(e) => format!("failed: {}", e),
+    };
+    let line = format!(
+        "{}\t{}\t{}\t{}\t{}\n",
+        chrono::Utc::now().to_rfc3339(),
+        current_caller(),
+        action,
+        target,
+        outcome
+    );
+    if let Err(e) = append(&line) {
+        log::warn!("Failed to write the audit log: {}", e);
+    }
+}
+
+fn append(line: &str) -> std::io::Result<()> {
+    let Some(path) = path() else {
+        return Ok(());
+    };
+    let _lock = WRITE_LOCK.lock().unwrap();
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    if std::fs::metadata(&path).is_ok_and(|m| m.len() > MAX_AUDIT_BYTES) {
+        std::fs::rename(&path, path.with_extension("log.1"))?;
+    }
+    std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)?
+        .write_all(line.as_bytes())
+}
+
+/// The last `lines` entries, oldest first. Empty if nothing was audited yet.
+pub fn tail(lines: usize) -> Result<Vec<String>> {
+    let Some(path) = path() else {
+        return Ok(vec![]);
+    };
+    let file = match std::fs::File::open(&path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+        Err(e) => return Err(e.into()),
+    };
+    let all: Vec<String> = std::io::BufReader::new(file)
+        .lines()
+        .collect::<std::io::Result<_>>()?;
+    Ok(all[all.len().saturating_sub(lines)..].to_vec())
+}
+
+/// Wraps the platform backend and audits every hide, unhide, enable, disable and
+/// hiding switch that goes through it. Everything else is passed straight on.
+pub struct AuditedPlatform {
+    inner: Arc<dyn PlatformServices>,
+}
+
+impl AuditedPlatform {
+    pub fn new(inner: Arc<dyn PlatformServices>) -> Self {
+        Self { inner }
+    }
+}
+
+impl DeviceEnumerator for AuditedPlatform {
+    fn enumerate_devices(&self) -> Result<Vec<PhysicalDevice>> {
+        self.inner.enumerate_devices()
+    }
+
+    fn check_drivers(&self) -> Result<DriverStatus> {
+        self.inner.check_drivers()
+    }
+
+    fn backend_description(&self) -> String {
+        self.inner.backend_description()
+    }
+
+    fn read_raw_report(&self, instance_path: &str, count: u32) -> Result<Vec<String>> {
+        self.inner.read_raw_report(instance_path, count)
+    }
+
+    fn is_device_present(&self, instance_path: &str) -> Result<bool> {
+        self.inner.is_device_present(instance_path)
+    }
+
+    fn device_capabilities(&self, device: &PhysicalDevice) -> Result<DeviceCapabilities> {
+        self.inner.device_capabilities(device)
+    }
+
+    fn enumerate_input_devices(&self) -> Result<Vec<PhysicalDevice>> {
+        self.inner.enumerate_input_devices()
+    }
+
+    fn set_device_selection(&self, selection: DeviceSelection) {
+        self.inner.set_device_selection(selection)
+    }
+}
+
+impl DeviceHider for AuditedPlatform {
+    fn hide_device(&self, instance_path: &str) -> Result<()> {
+        let result = self.inner.hide_device(instance_path);
+        record("hide", instance_path, &result);
+        result
+    }
+
+    fn unhide_device(&self, instance_path: &str) -> Result<()> {
+        let result = self.inner.unhide_device(instance_path);
+        record("unhide", instance_path, &result);
+        result
+    }
+
+    fn whitelist_self(&self) -> Result<()> {
+        self.inner.whitelist_self()
+    }
+
+    fn whitelist_app(&self, app_path: &str) -> Result<bool> {
+        self.inner.whitelist_app(app_path)
+    }
+
+    fn unwhitelist_app(&self, app_path: &str) -> Result<()> {
+        self.inner.unwhitelist_app(app_path)
+    }
+
+    fn disable_device(&self, instance_path: &str) -> Result<()> {
+        let result = self.inner.disable_device(instance_path);
+        record("disable", instance_path, &result);
+        result
+    }
+
+    fn enable_device(&self, instance_path: &str) -> Result<()> {
+        let result = self.inner.enable_device(instance_path);
+        record("enable", instance_path, &result);
+        result
+    }
+
+    fn activate_hiding(&self) -> Result<()> {
+        let result = self.inner.activate_hiding();
+        record("activate-hiding", "-", &result);
+        result
+    }
+
+    fn deactivate_hiding(&self) -> Result<()> {
+        let result = self.inner.deactivate_hiding();
+        record("deactivate-hiding", "-", &result);
+        result
+    }
+
+    fn hiding_active(&self) -> Result<bool> {
+        self.inner.hiding_active()
+    }
+
+    fn hide_candidates(&self, instance_path: &str) -> Result<Vec<HideCandidate>> {
+        self.inner.hide_candidates(instance_path)
+    }
+}
+
+impl VirtualControllerManager for AuditedPlatform {
+    fn create_virtual_controller(&self) -> Result<u32> {
+        self.inner.create_virtual_controller()
+    }
+
+    fn destroy_virtual_controller(&self, index: u32) -> Result<()> {
+        self.inner.destroy_virtual_controller(index)
+    }
+
+    fn read_gamepad_state(&self, instance_path: &str) -> Result<GamepadState> {
+        self.inner.read_gamepad_state(instance_path)
+    }
+
+    fn write_virtual_state(&self, index: u32, state: &GamepadState) -> Result<()> {
+        self.inner.write_virtual_state(index, state)
+    }
+
+    fn set_rumble(&self, instance_path: &str, left: u16, right: u16) -> Result<()> {
+        self.inner.set_rumble(instance_path, left, right)
+    }
+
+    fn supports_rumble(&self, instance_path: &str) -> Result<bool> {
+        self.inner.supports_rumble(instance_path)
+    }
+
+    fn set_axis_overrides(&self, overrides: BTreeMap<String, Vec<AxisOverride>>) {
+        self.inner.set_axis_overrides(overrides)
+    }
+}
@@ -1,7 +1,23 @@
-use crate::config::{GameRule, Profile, RoutingMode, Settings};
-use crate::device::{DriverStatus, PhysicalDevice, SlotAssignment};
+use crate::config::{
+    AppConfig, GameRule, MoveDirection, Profile, ProfileInput, RoutingMode, Settings,
+    VirtualLayout, VirtualSubtype, MAX_LAYOUT_TARGETS,
+};
+use crate::conflicts::ConflictWarning;
+use crate::device::{
+    DeviceCapabilities, DriverStatus, HideCandidate, PhysicalDevice, SlotAssignment,
+};
+use crate::diagnostics::{DeviceSlotTable, Diagnostics};
 use crate::error::Result;
-use crate::state::AppState;
+use crate::input_loop::{
+    AssignmentResolution, ForwardingOptions, LatencyReport, VirtualControllerInfo,
+};
+use crate::mapping::{AxisOverride, RemapPreset};
+use crate::platform::{same_instance_path, DeviceSelection, RestoreOptions};
+use crate::state::{
+    AppState, BackgroundError, EffectiveRoutingMode, HiddenDevicePreview, Inner, ProfileWithStatus,
+    RestartCheck, SavedProfile,
+};
+use std::sync::MutexGuard;
 use tauri::{AppHandle, Emitter, State};
 use uuid::Uuid;
 
@@ -9,11 +25,21 @@ use uuid::Uuid;
 pub fn get_connected_devices(state: State<AppState>) -> Result<Vec<PhysicalDevice>> {
     let manager = state.manager().clone();
     let devices = manager.enumerate_devices()?;
+    crate::device_refresh::cancel_pending(&state);
     let mut inner = state.lock_inner();
     inner.devices = devices.clone();
     Ok(devices)
 }
 
+/// Re-enumerate and merge into the cached device list instead of replacing it:
+/// hidden state and confirmed XInput slots survive for devices still present,
+/// and vanished ones are kept as disconnected. Emits `devices-updated` with the diff.
+#[tauri::command]
+pub fn refresh_devices(app: AppHandle, state: State<AppState>) -> Result<Vec<PhysicalDevice>> {
+    crate::device_refresh::cancel_pending(&state);
+    crate::device_refresh::refresh(&app)
+}
+
 #[tauri::command]
 pub fn check_driver_status(state: State<AppState>) -> Result<DriverStatus> {
     let manager = state.manager().clone();
@@ -23,8 +49,80 @@ pub fn check_driver_status(state: State<AppState>) -> Result<DriverStatus> {
     Ok(status)
 }
 
+/// Read raw input frames from a device as hex, for diagnosing controllers that map wrong.
+/// `count` is clamped to 1-64; the read gives up after a short timeout.
+#[tauri::command]
+pub fn read_raw_report(
+    state: State<AppState>,
+    instance_path: String,
+    count: u32,
+) -> Result<Vec<String>> {
+    let manager = state.manager().clone();
+    manager.read_raw_report(&instance_path, count.clamp(1, 64))
+}
+
+/// List the buttons and axes a connected device supports, for the mapping editor.
+#[tauri::command]
+pub fn get_device_capabilities(
+    state: State<AppState>,
+    device_id: String,
+) -> Result<DeviceCapabilities> {
+    let manager = state.manager().clone();
+    let device = state
+        .lock_inner()
+        .devices
+        .iter()
+        .find(|d| d.id == device_id)
+        .cloned()
+        .ok_or_else(|| crate::error::PadSwitchError::DeviceNotFound(device_id.clone()))?;
+    manager.device_capabilities(&device)
+}
+
+/// Whether a connected device has rumble motors PadSwitch can drive, so the UI can
+/// gray out rumble forwarding for it. Pads reached through an XInput slot are
+/// probed there; others by their instance path.
+#[tauri::command]
+pub fn device_supports_rumble(state: State<AppState>, device_id: String) -> Result<bool> {
+    let manager = state.manager().clone();
+    let path = state
+        .lock_inner()
+        .devices
+        .iter()
+        .find(|d| d.id == device_id)
+        .map(|d| {
+            d.xinput_slot
+                .map_or_else(|| d.instance_path.clone(), |s| s.to_string())
+        })
+        .ok_or_else(|| crate::error::PadSwitchError::DeviceNotFound(device_id.clone()))?;
+    manager.supports_rumble(&path)
+}
+
+/// Every path HidHide could blacklist for a device (instance, children, HID
+/// interfaces, parent containers) and whether each is blacklisted now. Read-only,
+/// for picking the right path on stubborn composite controllers. Windows-only.
+#[tauri::command]
+pub fn get_hide_candidates(
+    state: State<AppState>,
+    device_id: String,
+) -> Result<Vec<HideCandidate>> {
+    let manager = state.manager().clone();
+    let instance_path = state
+        .lock_inner()
+        .devices
+        .iter()
+        .find(|d| d.id == device_id)
+        .map(|d| d.instance_path.clone())
+        .ok_or_else(|| crate::error::PadSwitchError::DeviceNotFound(device_id.clone()))?;
+    manager.hide_candidates(&instance_path)
+}
+
 #[tauri::command]
-pub fn toggle_device(state: State<AppState>, device_id: String, hidden: bool) -> Result<()> {
+pub fn toggle_device(
+    app: AppHandle,
+    state: State<AppState>,
+    device_id: String,
+    hidden: bool,
+) -> Result<()> {
     let manager = state.manager().clone();
 
     // Read the instance_path and active routing mode while holding the lock briefly
@@ -63,41 +161,227 @@ pub fn toggle_device(state: State<AppState>, device_id: String, hidden: bool) ->
     if let Some(device) = inner.devices.iter_mut().find(|d| d.id == device_id) {
         device.hidden = hidden;
     }
+    drop(inner);
+
+    crate::device_refresh::schedule(&app);
     Ok(())
 }
 
+/// Replace the live assignments. Returns warnings about assignments forwarding
+/// would skip (see `Inner::assignment_warnings`); they're applied regardless.
 #[tauri::command]
-pub fn apply_assignments(state: State<AppState>, assignments: Vec<SlotAssignment>) -> Result<()> {
+pub fn apply_assignments(
+    app: AppHandle,
+    state: State<AppState>,
+    assignments: Vec<SlotAssignment>,
+) -> Result<Vec<String>> {
     let mut inner = state.lock_inner();
+    let warnings = inner.assignment_warnings(&assignments, &inner.active_routing_mode());
     inner.assignments = assignments;
-    Ok(())
+    drop(inner);
+
+    for warning in &warnings {
+        log::warn!("{}", warning);
+    }
+    crate::device_refresh::schedule(&app);
+    Ok(warnings)
+}
+
+/// Point the enabled assignment for `target_slot` at a different device (e.g. a spare
+/// controller mid-match) and restart forwarding if it's running. Only the live
+/// assignments change; the saved profile is left as-is. Returns the new assignments.
+#[tauri::command]
+pub fn rebind_slot(
+    app: AppHandle,
+    state: State<AppState>,
+    target_slot: u8,
+    new_device_id: String,
+) -> Result<Vec<SlotAssignment>> {
+    let manager = state.manager().clone();
+    let mut inner = state.lock_inner();
+
+    if !inner
+        .devices
+        .iter()
+        .any(|d| d.id == new_device_id && d.connected)
+    {
+        return Err(crate::error::PadSwitchError::DeviceNotFound(new_device_id));
+    }
+    if let Some(other) = inner
+        .assignments
+        .iter()
+        .find(|a| a.enabled && a.device_id == new_device_id && a.slot != target_slot)
+    {
+        return Err(crate::error::PadSwitchError::Config(format!(
+            "Device {} is already bound to slot {}",
+            new_device_id, other.slot
+        )));
+    }
+
+    let assignment = inner
+        .assignments
+        .iter_mut()
+        .find(|a| a.enabled && a.slot == target_slot)
+        .ok_or_else(|| {
+            crate::error::PadSwitchError::Config(format!(
+                "No enabled assignment for slot {}",
+                target_slot
+            ))
+        })?;
+    log::info!(
+        "Rebinding slot {}: {} -> {}",
+        target_slot,
+        assignment.device_id,
+        new_device_id
+    );
+    assignment.device_id = new_device_id;
+    let assignments = inner.assignments.clone();
+
+    if inner.forwarding_active() {
+        if let Err(e) = inner.restart_forwarding(manager, &app) {
+            drop(inner);
+            let _ = app.emit(
+                "forwarding-status",
+                serde_json::json!({ "active": false, "error": e.to_string() }),
+            );
+            return Err(e);
+        }
+    }
+    drop(inner);
+
+    crate::device_refresh::schedule(&app);
+    Ok(assignments)
+}
+
+/// Virtual controllers of the active Force-mode session and what feeds them.
+/// Empty when not forwarding (or in Minimal mode, which creates none).
+#[tauri::command]
+pub fn get_virtual_controllers(state: State<AppState>) -> Vec<VirtualControllerInfo> {
+    state.lock_inner().input_loop.virtual_controllers()
+}
+
+/// Estimate the software latency of one virtual controller (Windows Force mode):
+/// from reading the physical pads to XInput reporting the frame from the virtual
+/// controller. Not total input lag — USB polling and the game aren't included.
+/// Runs off the main thread; takes well under a second while forwarding.
+#[tauri::command(async)]
+pub fn measure_latency(state: State<AppState>, target_slot: u8) -> Result<LatencyReport> {
+    if !cfg!(target_os = "windows") {
+        return Err(crate::error::PadSwitchError::PlatformNotSupported(
+            "Latency measurement needs Force mode on Windows".into(),
+        ));
+    }
+    let inner = state.lock_inner();
+    if !inner.is_forwarding() {
+        return Err(crate::error::PadSwitchError::InvalidState(
+            "Forwarding is not running".into(),
+        ));
+    }
+    let virtual_slot = inner
+        .input_loop
+        .virtual_controllers()
+        .iter()
+        .find(|v| v.target_slot == target_slot)
+        .and_then(|v| v.xinput_slot);
+    drop(inner);
+    let Some(virtual_slot) = virtual_slot else {
+        return Err(crate::error::PadSwitchError::InvalidState(format!(
+            "No virtual controller with a known XInput slot on target slot {}",
+            target_slot
+        )));
+    };
+    state.latency_probe().measure(
+        &**state.manager(),
+        target_slot,
+        virtual_slot,
+        std::time::Duration::from_secs(3),
+    )
 }
 
+/// Show which assignments the input loop would forward and why the rest are skipped.
 #[tauri::command]
+pub fn get_resolved_assignments(state: State<AppState>) -> AssignmentResolution {
+    state.lock_inner().explain_assignments()
+}
+
+/// Physical devices Force mode would hide from games if this profile were started,
+/// so the UI can ask for confirmation first. Read-only.
+#[tauri::command]
+pub fn preview_hidden_devices(
+    profile_id: String,
+    state: State<AppState>,
+) -> Result<Vec<HiddenDevicePreview>> {
+    state
+        .lock_inner()
+        .preview_hidden_devices(&profile_id)
+        .ok_or_else(|| crate::error::PadSwitchError::Config("Profile not found".into()))
+}
+
+/// Runs off the main thread, so `cancel_start_forwarding` can run while it blocks.
+#[tauri::command(async)]
 pub fn start_forwarding(app: AppHandle, state: State<AppState>) -> Result<()> {
     let manager = state.manager().clone();
     let mut inner = state.lock_inner();
 
-    inner.start_forwarding(manager)?;
+    if inner.is_forwarding() {
+        return Err(crate::error::PadSwitchError::InvalidState(
+            "Forwarding is already active".into(),
+        ));
+    }
+    inner.start_forwarding(manager, &app)?;
     drop(inner);
 
     let _ = app.emit("forwarding-status", serde_json::json!({ "active": true }));
+    crate::tray::rebuild_tray_menu(&app);
+    crate::device_refresh::schedule(&app);
+    Ok(())
+}
+
+/// Abort a `start_forwarding` that is still hiding devices or plugging in virtual
+/// controllers. The start undoes what it did so far and returns an error.
+#[tauri::command]
+pub fn cancel_start_forwarding(state: State<AppState>) -> Result<()> {
+    if !state.cancel_start() {
+        return Err(crate::error::PadSwitchError::InvalidState(
+            "No forwarding start is in progress".into(),
+        ));
+    }
+    log::info!("Forwarding start cancel requested");
     Ok(())
 }
 
 #[tauri::command]
 pub fn stop_forwarding(app: AppHandle, state: State<AppState>) -> Result<()> {
-    let mut inner = state.lock_inner();
-    inner.stop_forwarding();
-    drop(inner);
+    // Entries added mid-session were unhidden by the loop's cleanup
+    let stopped = state.stop_forwarding_with(
+        |inner| {
+            inner
+                .forwarding_active()
+                .then(|| inner.config.persistent_hidden.clone())
+        },
+        |persistent_hidden| {
+            let Some(persistent_hidden) = persistent_hidden else {
+                return false;
+            };
+            crate::platform::apply_persistent_hides(&**state.manager(), &persistent_hidden);
+            true
+        },
+    );
+    if !stopped {
+        return Err(crate::error::PadSwitchError::InvalidState(
+            "Forwarding is not active".into(),
+        ));
+    }
 
     let _ = app.emit("forwarding-status", serde_json::json!({ "active": false }));
+    crate::tray::rebuild_tray_menu(&app);
+    crate::device_refresh::schedule(&app);
     Ok(())
 }
 
 #[tauri::command]
 pub fn is_forwarding(state: State<AppState>) -> bool {
-    state.lock_inner().forwarding_active
+    state.lock_inner().is_forwarding()
 }
 
 // --- Profile commands ---
@@ -108,26 +392,50 @@ pub fn get_profiles(state: State<AppState>) -> Result<Vec<Profile>> {
     Ok(inner.config.profiles.clone())
 }
 
+/// Every saved profile with whether it could start right now (elevation, drivers,
+/// connected devices). Runs off the main thread: driver checks can be slow.
+#[tauri::command(async)]
+pub fn get_profiles_with_status(state: State<AppState>) -> Result<Vec<ProfileWithStatus>> {
+    let manager = state.manager().clone();
+    let inner = state.lock_inner();
+    Ok(inner
+        .config
+        .profiles
+        .iter()
+        .map(|profile| ProfileWithStatus {
+            profile: profile.clone(),
+            status: inner.profile_status(profile, &*manager),
+        })
+        .collect())
+}
+
+/// Profiles with an assignment for `device_id`, so the UI can show what unplugging
+/// or removing a controller affects.
+#[tauri::command]
+pub fn profiles_using_device(state: State<AppState>, device_id: String) -> Vec<Profile> {
+    let inner = state.lock_inner();
+    inner
+        .config
+        .profiles_using_device(&device_id)
+        .into_iter()
+        .cloned()
+        .collect()
+}
+
 #[tauri::command]
 pub fn save_profile(
     app: AppHandle,
     state: State<AppState>,
-    name: String,
-    assignments: Vec<SlotAssignment>,
-    routing_mode: Option<RoutingMode>,
-) -> Result<Profile> {
+    profile: ProfileInput,
+) -> Result<SavedProfile> {
     let mut inner = state.lock_inner();
-    let profile = Profile {
-        id: Uuid::new_v4().to_string(),
-        name,
-        assignments,
-        routing_mode: routing_mode.unwrap_or_default(),
-    };
+    let profile = profile.into_profile(Uuid::new_v4().to_string());
+    let warnings = inner.assignment_warnings(&profile.assignments, &profile.routing_mode);
     inner.config.profiles.push(profile.clone());
     inner.config.save()?;
     drop(inner);
     crate::tray::rebuild_tray_menu(&app);
-    Ok(profile)
+    Ok(SavedProfile { profile, warnings })
 }
 
 #[tauri::command]
@@ -169,46 +477,524 @@ pub fn activate_profile(
     Ok(profile.assignments)
 }
 
-// --- Reset command ---
-
-/// Nuclear reset: stop everything, re-enable all devices, unhide all devices,
-/// deactivate HidHide, clear active profile. Use when controllers stop working.
+/// Change a saved profile's routing mode. Runs the target mode's preflight
+/// (elevation / drivers) before persisting, and restarts forwarding if the
+/// profile is active and forwarding is running.
 #[tauri::command]
-pub fn reset_all(app: AppHandle, state: State<AppState>) -> Result<()> {
-    log::info!("Reset all: starting full reset");
+pub fn set_profile_routing_mode(
+    app: AppHandle,
+    state: State<AppState>,
+    profile_id: String,
+    routing_mode: RoutingMode,
+) -> Result<Profile> {
+    let manager = state.manager().clone();
+    let mut inner = state.lock_inner();
+
+    let mut updated = inner
+        .config
+        .profiles
+        .iter()
+        .find(|p| p.id == profile_id)
+        .ok_or_else(|| crate::error::PadSwitchError::Config("Profile not found".into()))?
+        .clone();
+    updated.routing_mode = routing_mode;
 
-    // 1. Stop process watcher
+    inner.preflight_check(
+        &updated.routing_mode,
+        &ForwardingOptions::from_profile(&updated),
+        &*manager,
+    )?;
+
+    if let Some(profile) = inner
+        .config
+        .profiles
+        .iter_mut()
+        .find(|p| p.id == profile_id)
     {
-        let mut watcher = state.lock_watcher();
-        watcher.stop();
+        *profile = updated.clone();
+    }
+    inner.config.save()?;
+
+    let is_active = inner.config.settings.active_profile_id.as_deref() == Some(&profile_id);
+    let restart_result = if is_active && inner.forwarding_active() {
+        log::info!(
+            "Routing mode changed on active profile — restarting forwarding ({:?})",
+            updated.routing_mode
+        );
+        inner.restart_forwarding(manager, &app)
+    } else {
+        Ok(())
+    };
+    drop(inner);
+
+    crate::tray::rebuild_tray_menu(&app);
+    if is_active {
+        let _ = app.emit(
+            "profile-activated",
+            serde_json::json!({
+                "profile_id": updated.id,
+                "assignments": updated.assignments,
+                "routing_mode": updated.routing_mode,
+            }),
+        );
+    }
+    if let Err(e) = restart_result {
+        let _ = app.emit(
+            "forwarding-status",
+            serde_json::json!({ "active": false, "error": e.to_string() }),
+        );
+        return Err(e);
     }
 
-    // 2. Stop forwarding (input loop handles its own cleanup for the current mode)
+    Ok(updated)
+}
+
+/// Replace the button remap table of one assignment in a saved profile with a
+/// preset layout. Restarts forwarding if the profile is active and forwarding is
+/// running, so the new layout applies immediately.
+#[tauri::command]
+pub fn apply_remap_preset(
+    app: AppHandle,
+    state: State<AppState>,
+    profile_id: String,
+    assignment_index: usize,
+    preset: RemapPreset,
+) -> Result<Profile> {
     let manager = state.manager().clone();
+    let mut inner = state.lock_inner();
+
+    let profile = inner
+        .config
+        .profiles
+        .iter_mut()
+        .find(|p| p.id == profile_id)
+        .ok_or_else(|| crate::error::PadSwitchError::Config("Profile not found".into()))?;
+    let assignment = profile
+        .assignments
+        .get_mut(assignment_index)
+        .ok_or_else(|| {
+            crate::error::PadSwitchError::Config(format!(
+                "Profile '{}' has no assignment {}",
+                profile_id, assignment_index
+            ))
+        })?;
+    assignment.mapping.button_remap = preset.button_remap();
+    let updated = profile.clone();
+    inner.config.save()?;
+
+    let is_active = inner.config.settings.active_profile_id.as_deref() == Some(&profile_id);
+    let restart_result = if is_active {
+        inner.assignments = updated.assignments.clone();
+        if inner.forwarding_active() {
+            log::info!(
+                "Remap preset {:?} applied to active profile — restarting forwarding",
+                preset
+            );
+            inner.restart_forwarding(manager, &app)
+        } else {
+            Ok(())
+        }
+    } else {
+        Ok(())
+    };
+    drop(inner);
+
+    if let Err(e) = restart_result {
+        let _ = app.emit(
+            "forwarding-status",
+            serde_json::json!({ "active": false, "error": e.to_string() }),
+        );
+        return Err(e);
+    }
+
+    Ok(updated)
+}
+
+/// Replace a device's axis overrides (Linux: raw `ABS_*` axes read as gamepad
+/// axes). An empty list restores the standard layout. Restarts forwarding if it's
+/// running so the virtual device is rebuilt with the new layout.
+#[tauri::command]
+pub fn set_axis_overrides(
+    app: AppHandle,
+    state: State<AppState>,
+    device_id: String,
+    overrides: Vec<AxisOverride>,
+) -> Result<()> {
+    let manager = state.manager().clone();
+    let mut inner = state.lock_inner();
+    if overrides.is_empty() {
+        inner.config.axis_overrides.remove(&device_id);
+    } else {
+        inner
+            .config
+            .axis_overrides
+            .insert(device_id.clone(), overrides);
+    }
+    inner.config.save()?;
+    manager.set_axis_overrides(inner.config.axis_overrides.clone());
+
+    let restart_result = if inner.forwarding_active() {
+        log::info!(
+            "Axis overrides for {} changed — restarting forwarding",
+            device_id
+        );
+        inner.restart_forwarding(manager, &app)
+    } else {
+        Ok(())
+    };
+    drop(inner);
+
+    if let Err(e) = restart_result {
+        let _ = app.emit(
+            "forwarding-status",
+            serde_json::json!({ "active": false, "error": e.to_string() }),
+        );
+        return Err(e);
+    }
+    Ok(())
+}
+
+// --- Virtual layout commands ---
+
+#[tauri::command]
+pub fn get_virtual_layouts(state: State<AppState>) -> Vec<VirtualLayout> {
+    state.lock_inner().config.virtual_layouts.clone()
+}
+
+/// Create a layout (`layout_id` = None) or replace an existing one. Every profile
+/// referencing it picks up the change; forwarding restarts if the active one does.
+#[tauri::command]
+pub fn save_virtual_layout(
+    app: AppHandle,
+    state: State<AppState>,
+    layout_id: Option<String>,
+    name: String,
+    targets: Vec<VirtualSubtype>,
+) -> Result<VirtualLayout> {
+    if targets.len() > MAX_LAYOUT_TARGETS {
+        return Err(crate::error::PadSwitchError::Config(format!(
+            "A virtual layout can have at most {} targets",
+            MAX_LAYOUT_TARGETS
+        )));
+    }
+    let mut inner = state.lock_inner();
+    let layout = VirtualLayout {
+        id: layout_id.unwrap_or_else(|| Uuid::new_v4().to_string()),
+        name,
+        targets,
+    };
+    match inner
+        .config
+        .virtual_layouts
+        .iter_mut()
+        .find(|l| l.id == layout.id)
     {
-        let mut inner = state.lock_inner();
-        inner.stop_forwarding();
+        Some(existing) => *existing = layout.clone(),
+        None => inner.config.virtual_layouts.push(layout.clone()),
+    }
+    inner.config.save()?;
+    restart_for_layout(&app, &state, inner, &layout.id)?;
+    Ok(layout)
+}
+
+/// Delete a layout. Profiles that used it go back to their own virtual subtype.
+#[tauri::command]
+pub fn delete_virtual_layout(
+    app: AppHandle,
+    state: State<AppState>,
+    layout_id: String,
+) -> Result<()> {
+    let mut inner = state.lock_inner();
+    let uses_layout = inner
+        .active_profile()
+        .is_some_and(|p| p.virtual_layout_id.as_deref() == Some(&layout_id));
+    inner.config.virtual_layouts.retain(|l| l.id != layout_id);
+    for profile in &mut inner.config.profiles {
+        if profile.virtual_layout_id.as_deref() == Some(&layout_id) {
+            profile.virtual_layout_id = None;
+        }
+    }
+    inner.config.save()?;
+    if uses_layout {
+        restart_active(&app, &state, inner, "Virtual layout deleted")?;
     }
+    Ok(())
+}
 
-    // 3. Re-enable and unhide all known devices (both are idempotent, errors swallowed)
-    let device_paths: Vec<String> = {
+/// Point a profile at a layout, or back at its own virtual subtype (`None`).
+#[tauri::command]
+pub fn set_profile_virtual_layout(
+    app: AppHandle,
+    state: State<AppState>,
+    profile_id: String,
+    layout_id: Option<String>,
+) -> Result<Profile> {
+    let mut inner = state.lock_inner();
+    if let Some(id) = &layout_id {
+        if !inner.config.virtual_layouts.iter().any(|l| &l.id == id) {
+            return Err(crate::error::PadSwitchError::Config(format!(
+                "Virtual layout {} not found",
+                id
+            )));
+        }
+    }
+    let profile = inner
+        .config
+        .profiles
+        .iter_mut()
+        .find(|p| p.id == profile_id)
+        .ok_or_else(|| crate::error::PadSwitchError::Config("Profile not found".into()))?;
+    profile.virtual_layout_id = layout_id;
+    let updated = profile.clone();
+    inner.config.save()?;
+
+    let is_active = inner.config.settings.active_profile_id.as_deref() == Some(&profile_id);
+    if is_active {
+        restart_active(
+            &app,
+            &state,
+            inner,
+            "Virtual layout of the active profile changed",
+        )?;
+    }
+    Ok(updated)
+}
+
+/// Restart forwarding if the active profile uses `layout_id`.
+fn restart_for_layout(
+    app: &AppHandle,
+    state: &AppState,
+    inner: MutexGuard<'_, Inner>,
+    layout_id: &str,
+) -> Result<()> {
+    let uses_layout = inner
+        .active_profile()
+        .is_some_and(|p| p.virtual_layout_id.as_deref() == Some(layout_id));
+    if !uses_layout {
+        return Ok(());
+    }
+    restart_active(
+        app,
+        state,
+        inner,
+        "Virtual layout of the active profile changed",
+    )
+}
+
+/// Restart forwarding, if it's running, so a changed active profile applies.
+fn restart_active(
+    app: &AppHandle,
+    state: &AppState,
+    mut inner: MutexGuard<'_, Inner>,
+    reason: &str,
+) -> Result<()> {
+    let restart_result = if inner.forwarding_active() {
+        log::info!("{} — restarting forwarding", reason);
+        inner.restart_forwarding(state.manager().clone(), app)
+    } else {
+        Ok(())
+    };
+    drop(inner);
+
+    if let Err(e) = restart_result {
+        let _ = app.emit(
+            "forwarding-status",
+            serde_json::json!({ "active": false, "error": e.to_string() }),
+        );
+        return Err(e);
+    }
+    Ok(())
+}
+
+// --- Reset command ---
+
+/// Re-enable and unhide a single device (including removing it from the HidHide
+/// blacklist) without touching forwarding, the watcher or other devices.
+/// Both steps are always attempted; the first failure is returned.
+#[tauri::command]
+pub fn reset_device(app: AppHandle, state: State<AppState>, device_id: String) -> Result<()> {
+    let manager = state.manager().clone();
+    let instance_path = {
         let inner = state.lock_inner();
-        inner.devices.iter().map(|d| d.instance_path.clone()).collect()
+        inner
+            .devices
+            .iter()
+            .find(|d| d.id == device_id)
+            .map(|d| d.instance_path.clone())
+            .ok_or_else(|| crate::error::PadSwitchError::DeviceNotFound(device_id.clone()))?
     };
 
-    for path in &device_paths {
-        if let Err(e) = manager.enable_device(path) {
-            log::warn!("Reset: enable_device failed for {}: {}", path, e);
-        }
-        if let Err(e) = manager.unhide_device(path) {
-            log::warn!("Reset: unhide_device failed for {}: {}", path, e);
+    log::info!("Reset device: {}", instance_path);
+    let enabled = manager.enable_device(&instance_path);
+    if let Err(e) = &enabled {
+        log::warn!(
+            "Reset device: enable_device failed for {}: {}",
+            instance_path,
+            e
+        );
+    }
+    let unhidden = manager.unhide_device(&instance_path);
+    if let Err(e) = &unhidden {
+        log::warn!(
+            "Reset device: unhide_device failed for {}: {}",
+            instance_path,
+            e
+        );
+    }
+
+    {
+        let mut inner = state.lock_inner();
+        if let Some(device) = inner.devices.iter_mut().find(|d| d.id == device_id) {
+            device.hidden = false;
         }
     }
+    crate::device_refresh::schedule(&app);
+
+    enabled.and(unhidden)
+}
+
+/// Warn about running software known to fight PadSwitch over controllers
+/// (reWASD, DS4Windows, Steam Input, ...), with remediation hints.
+#[tauri::command]
+pub fn detect_conflicts(state: State<AppState>) -> Vec<ConflictWarning> {
+    let conflict_processes = state
+        .lock_inner()
+        .config
+        .settings
+        .conflict_processes
+        .clone();
+    crate::conflicts::detect(&conflict_processes)
+}
 
-    // 4. Deactivate HidHide globally
-    if let Err(e) = manager.deactivate_hiding() {
-        log::warn!("Reset: deactivate_hiding failed: {}", e);
+// --- Manual device mode commands ---
+
+/// Every input device, controller or not, to pick from in manual device mode.
+/// Runs off the main thread: enumerating everything can be slow.
+#[tauri::command(async)]
+pub fn get_input_devices(state: State<AppState>) -> Result<Vec<PhysicalDevice>> {
+    state.manager().enumerate_input_devices()
+}
+
+/// Device ids marked as controllers for manual device mode.
+#[tauri::command]
+pub fn get_marked_controllers(state: State<AppState>) -> Vec<String> {
+    state.lock_inner().config.marked_controllers.clone()
+}
+
+/// Mark (or unmark) a device as a controller by its stable id. In manual device
+/// mode only marked devices are enumerated. Returns the updated list.
+#[tauri::command]
+pub fn mark_as_controller(
+    app: AppHandle,
+    state: State<AppState>,
+    device_id: String,
+    marked: bool,
+) -> Result<Vec<String>> {
+    let mut inner = state.lock_inner();
+    let list = &mut inner.config.marked_controllers;
+    let present = list.contains(&device_id);
+    if marked == present {
+        return Ok(list.clone());
+    }
+    if marked {
+        list.push(device_id);
+    } else {
+        list.retain(|id| *id != device_id);
     }
+    inner.config.save()?;
+    state
+        .manager()
+        .set_device_selection(DeviceSelection::from_config(&inner.config));
+    let list = inner.config.marked_controllers.clone();
+    drop(inner);
+
+    crate::device_refresh::schedule(&app);
+    Ok(list)
+}
+
+// --- Persistent hide commands ---
+
+/// Instance paths kept hidden across sessions.
+#[tauri::command]
+pub fn get_persistent_hidden(state: State<AppState>) -> Vec<String> {
+    state.lock_inner().config.persistent_hidden.clone()
+}
+
+/// Keep a device hidden (HidHide, Windows) across sessions and forwarding stops.
+/// Hides it immediately and returns the updated list.
+#[tauri::command]
+pub fn add_persistent_hide(state: State<AppState>, device_id: String) -> Result<Vec<String>> {
+    let manager = state.manager().clone();
+    let mut inner = state.lock_inner();
+    let instance_path = inner
+        .devices
+        .iter()
+        .find(|d| d.id == device_id)
+        .map(|d| d.instance_path.clone())
+        .ok_or_else(|| crate::error::PadSwitchError::DeviceNotFound(device_id.clone()))?;
+
+    manager.hide_device(&instance_path)?;
+    manager.activate_hiding()?;
+
+    let list = &mut inner.config.persistent_hidden;
+    if !list.iter().any(|p| same_instance_path(p, &instance_path)) {
+        list.push(instance_path);
+        inner.config.save()?;
+    }
+    Ok(inner.config.persistent_hidden.clone())
+}
+
+/// Stop keeping a device hidden and unhide it now. Takes the instance path so
+/// entries for disconnected devices can be removed too. Returns the updated list.
+#[tauri::command]
+pub fn remove_persistent_hide(
+    state: State<AppState>,
+    instance_path: String,
+) -> Result<Vec<String>> {
+    let manager = state.manager().clone();
+    let mut inner = state.lock_inner();
+    inner
+        .config
+        .persistent_hidden
+        .retain(|p| !same_instance_path(p, &instance_path));
+    inner.config.save()?;
+
+    if let Err(e) = manager.unhide_device(&instance_path) {
+        log::warn!(
+            "Unhide after removing persistent hide failed for {}: {}",
+            instance_path,
+            e
+        );
+    }
+    Ok(inner.config.persistent_hidden.clone())
+}
+
+/// Nuclear reset: stop everything, re-enable all devices, unhide all devices,
+/// deactivate HidHide, clear active profile. Use when controllers stop working.
+#[tauri::command]
+pub fn reset_all(app: AppHandle, state: State<AppState>) -> Result<()> {
+    log::info!("Reset all: starting full reset");
+    let _caller = crate::audit::caller("reset");
+
+    // 1. Stop process watcher (or suppress the running game's rule, if configured)
+    crate::process_watcher::pause_for_reset(&state);
+
+    // 2. Stop forwarding (input loop handles its own cleanup for the current mode),
+    //    then re-enable and unhide all known devices before anything can start again
+    let manager = state.manager().clone();
+    state.stop_forwarding_with(
+        |inner| {
+            let paths: Vec<String> = inner
+                .devices
+                .iter()
+                .map(|d| d.instance_path.clone())
+                .collect();
+            (paths, RestoreOptions::from_settings(&inner.config.settings))
+        },
+        // 3. Re-enable (or power-cycle) and unhide each device in priority order,
+        // 4. deactivate HidHide globally
+        |(paths, options)| crate::platform::restore_devices(&*manager, &paths, &options),
+    );
 
     // 5. Clear active profile
     {
@@ -230,11 +1016,80 @@ pub fn reset_all(app: AppHandle, state: State<AppState>) -> Result<()> {
     );
 
     crate::tray::rebuild_tray_menu(&app);
+    crate::device_refresh::schedule(&app);
 
     log::info!("Reset all: complete");
     Ok(())
 }
 
+/// Run dirty-shutdown recovery on demand: stop forwarding, enumerate devices afresh,
+/// re-enable and unhide all of them (persistent hides are re-applied), switch
+/// HidHide off and clear the active profile. More thorough than `reset_all`, which
+/// only restores the cached device list.
+#[tauri::command(async)]
+pub fn recover_now(app: AppHandle, state: State<AppState>) -> Result<()> {
+    log::info!("Recover now: starting");
+    let _caller = crate::audit::caller("recover");
+    let safe_mode = state.safe_mode();
+    state.stop_forwarding_with(
+        |inner| {
+            if safe_mode {
+                vec![]
+            } else {
+                inner.config.persistent_hidden.clone()
+            }
+        },
+        |keep_hidden| {
+            state.recover_devices(&keep_hidden);
+            crate::platform::apply_persistent_hides(&**state.manager(), &keep_hidden);
+        },
+    );
+
+    if !safe_mode {
+        let mut inner = state.lock_inner();
+        inner.config.settings.active_profile_id = None;
+        inner.assignments.clear();
+        inner.config.save()?;
+    }
+
+    let _ = app.emit("forwarding-status", serde_json::json!({ "active": false }));
+    if !safe_mode {
+        let _ = app.emit(
+            "profile-activated",
+            serde_json::json!({
+                "profile_id": null,
+                "assignments": [],
+                "routing_mode": "Minimal",
+            }),
+        );
+    }
+    crate::tray::rebuild_tray_menu(&app);
+    crate::device_refresh::schedule(&app);
+
+    log::info!("Recover now: complete");
+    Ok(())
+}
+
+/// Whether HidHide is globally active right now. Windows only.
+#[tauri::command]
+pub fn get_hidhide_active(state: State<AppState>) -> Result<bool> {
+    state.manager().hiding_active()
+}
+
+/// Switch HidHide on or off without running a session, for troubleshooting.
+/// Left on, it's switched off again when PadSwitch exits. Windows only.
+#[tauri::command]
+pub fn set_hidhide_active(state: State<AppState>, active: bool) -> Result<()> {
+    // Also the platform check: only Windows has a global switch
+    let was_active = state.manager().hiding_active()?;
+    log::info!(
+        "HidHide manually set active={} (was {})",
+        active,
+        was_active
+    );
+    state.set_hiding_active(active)
+}
+
 // --- Environment commands ---
 
 #[tauri::command]
@@ -242,37 +1097,241 @@ pub fn is_elevated() -> bool {
     crate::platform::is_elevated()
 }
 
+/// App version, OS name/version, architecture and elevation in one place.
+#[tauri::command]
+pub fn get_app_info(app: AppHandle) -> crate::platform::AppInfo {
+    crate::platform::app_info(app.package_info().version.to_string())
+}
+
+/// Every enumerated device with its type and XInput slot, as rows and as a text
+/// table for support threads. Read-only: the cached device list is left alone.
+#[tauri::command]
+pub fn dump_device_slot_table(state: State<AppState>) -> Result<DeviceSlotTable> {
+    let devices = state.manager().enumerate_devices()?;
+    Ok(crate::diagnostics::device_slot_table(&devices))
+}
+
+/// Human-readable name of the platform backend (hiding + virtual controller tech).
+#[tauri::command]
+pub fn get_backend_description(state: State<AppState>) -> String {
+    state.manager().backend_description()
+}
+
+/// App, driver, routing and device details in one bundle for bug reports.
+#[tauri::command]
+pub fn collect_diagnostics(app: AppHandle, state: State<AppState>) -> Result<Diagnostics> {
+    crate::diagnostics::collect(&state, app.package_info().version.to_string())
+}
+
+/// Put a trimmed diagnostics summary on the clipboard for pasting into a support
+/// chat. Returns its length in characters.
+#[tauri::command]
+pub fn copy_diagnostics_to_clipboard(app: AppHandle, state: State<AppState>) -> Result<usize> {
+    use tauri_plugin_clipboard_manager::ClipboardExt;
+
+    let diagnostics = crate::diagnostics::collect(&state, app.package_info().version.to_string())?;
+    let text = diagnostics.to_text(crate::diagnostics::CLIPBOARD_MAX_CHARS);
+    app.clipboard()
+        .write_text(text.clone())
+        .map_err(|e| crate::error::PadSwitchError::Platform(format!("Clipboard: {}", e)))?;
+    Ok(text.chars().count())
+}
+
+/// The last `lines` (default 200) audit entries, oldest first. Each is
+/// tab-separated: timestamp, caller, action, device, outcome.
+#[tauri::command]
+pub fn read_audit_log(lines: Option<usize>) -> Result<Vec<String>> {
+    crate::audit::tail(lines.unwrap_or(200))
+}
+
+/// Where the audit log is written, for opening it in an external viewer.
+#[tauri::command]
+pub fn get_audit_log_path() -> Option<String> {
+    crate::audit::path().map(|p| p.to_string_lossy().into_owned())
+}
+
+/// The last failure on the input loop or watcher thread, for UIs that mount after
+/// its error event was emitted. Cleared when forwarding starts successfully.
+#[tauri::command]
+pub fn get_last_background_error(state: State<AppState>) -> Option<BackgroundError> {
+    state.last_background_error()
+}
+
+/// Whether this instance was launched with `--safe-mode` (auto-start behaviors skipped).
+#[tauri::command]
+pub fn is_safe_mode(state: State<AppState>) -> bool {
+    state.safe_mode()
+}
+
+/// The routing mode forwarding would start in and why (active profile or default).
+#[tauri::command]
+pub fn get_effective_routing_mode(state: State<AppState>) -> EffectiveRoutingMode {
+    state.lock_inner().effective_routing_mode()
+}
+
+/// Whether applying `settings` and/or `profile` would need forwarding restarted,
+/// and why, so the UI only prompts when a change won't reach the running session.
+#[tauri::command]
+pub fn changes_require_restart(
+    state: State<AppState>,
+    settings: Option<Settings>,
+    profile: Option<Profile>,
+) -> RestartCheck {
+    state
+        .lock_inner()
+        .restart_check(settings.as_ref(), profile.as_ref())
+}
+
+/// Whether starting `routing_mode` with the active profile's options needs admin
+/// on this platform, so the UI can offer a relaunch before a failed start.
+#[tauri::command]
+pub fn plan_requires_elevation(state: State<AppState>, routing_mode: RoutingMode) -> bool {
+    let options = state.lock_inner().active_forwarding_options();
+    crate::platform::requires_elevation(&routing_mode, &options)
+}
+
+/// Relaunch PadSwitch elevated and exit this instance cleanly.
+///
+/// The lockfile is removed before launching so the new instance doesn't treat
+/// us as a dirty shutdown. If the UAC prompt is declined (or launching fails),
+/// the lockfile is restored and this instance keeps running untouched.
+#[tauri::command]
+pub fn relaunch_elevated(app: AppHandle, state: State<AppState>) -> Result<()> {
+    crate::remove_lockfile();
+    if let Err(e) = crate::platform::relaunch_elevated() {
+        crate::create_lockfile();
+        log::warn!("Relaunch as administrator failed: {}", e);
+        return Err(e);
+    }
+
+    log::info!("Elevated instance launched — shutting down this instance");
+    state.shutdown();
+
+    app.exit(0);
+    Ok(())
+}
+
 /// Poll all XInput slots for a button press. Returns the slot number (0-3) that
 /// first receives input, or null if no input within ~5 seconds.
 /// Used by the "Identify" feature to reliably map physical device → XInput slot.
 #[tauri::command]
 pub fn detect_xinput_slot(state: State<AppState>) -> Result<Option<u32>> {
     let manager = state.manager().clone();
+    Ok(crate::identify::detect_slot(
+        &*manager,
+        std::time::Duration::from_secs(5),
+    ))
+}
 
-    // Snapshot current button state for all 4 slots
-    let mut baseline = [0u16; 4];
-    for slot in 0..4u32 {
-        if let Ok(gs) = manager.read_gamepad_state(&slot.to_string()) {
-            baseline[slot as usize] = gs.buttons;
-        }
-    }
+/// Rumble a single XInput slot briefly so the user can see which pad it is.
+#[tauri::command]
+pub fn pulse_slot(state: State<AppState>, slot: u32) -> Result<()> {
+    let manager = state.manager().clone();
+    crate::identify::pulse(&*manager, slot, crate::identify::PULSE)
+}
 
-    // Poll at ~60Hz for up to 5 seconds
-    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
-    while std::time::Instant::now() < deadline {
-        for slot in 0..4u32 {
-            if let Ok(gs) = manager.read_gamepad_state(&slot.to_string()) {
-                // Detect any new button press (bits that weren't set before)
-                if gs.buttons & !baseline[slot as usize] != 0 {
-                    log::info!("Detected button press on XInput slot {}", slot);
-                    return Ok(Some(slot));
-                }
-            }
+/// Vibrate the virtual controller for `target_slot` the way a game would, so the
+/// rumble-forward path should pass it on to its physical pads. `strength` is 0.0-1.0;
+/// `duration_ms` is capped at 5s. Errors unless a Force session has that target
+/// and Windows gave it an XInput slot.
+#[tauri::command(async)]
+pub fn test_virtual_rumble(
+    state: State<'_, AppState>,
+    target_slot: u8,
+    strength: f32,
+    duration_ms: u64,
+) -> Result<()> {
+    let manager = state.manager().clone();
+    let target = {
+        let inner = state.lock_inner();
+        if !inner.is_forwarding() {
+            return Err(crate::error::PadSwitchError::InvalidState(
+                "Forwarding is not active".into(),
+            ));
         }
-        std::thread::sleep(std::time::Duration::from_millis(16));
-    }
+        inner
+            .input_loop
+            .virtual_controllers()
+            .into_iter()
+            .find(|vc| vc.target_slot == target_slot)
+    };
+    let target = target.ok_or_else(|| {
+        crate::error::PadSwitchError::DeviceNotFound(format!(
+            "No virtual controller for target slot {}",
+            target_slot
+        ))
+    })?;
+    let xinput_slot = target.xinput_slot.ok_or_else(|| {
+        crate::error::PadSwitchError::PlatformNotSupported(
+            "The virtual controller has no XInput slot to send rumble to".into(),
+        )
+    })?;
 
-    Ok(None) // Timeout — no input detected
+    let speed = (strength.clamp(0.0, 1.0) * f32::from(u16::MAX)) as u16;
+    let path = xinput_slot.to_string();
+    manager.set_rumble(&path, speed, speed)?;
+    std::thread::sleep(std::time::Duration::from_millis(duration_ms.min(5000)));
+    manager.set_rumble(&path, 0, 0)
+}
+
+/// Rumble every connected XInput slot in sequence (background; see `identify-step`).
+#[tauri::command]
+pub fn identify_all(app: AppHandle) {
+    crate::identify::start_all(&app);
+}
+
+/// Cancel a running identify-all sequence.
+#[tauri::command]
+pub fn cancel_identify(state: State<AppState>) {
+    crate::identify::cancel(&state);
+}
+
+/// Stream all four XInput slots as `slots-preview` events (~30Hz) for a controller
+/// test screen. Refused while Force mode is forwarding.
+#[tauri::command]
+pub fn start_all_slots_preview(app: AppHandle) -> Result<()> {
+    crate::slot_preview::start(&app)
+}
+
+/// Stop the stream started by `start_all_slots_preview`.
+#[tauri::command]
+pub fn stop_all_slots_preview(state: State<AppState>) {
+    crate::slot_preview::stop(&state);
+}
+
+/// Record raw input from an XInput slot for `duration_ms` and save it to `path`.
+/// Runs off the main thread since it blocks for the whole duration.
+/// Returns the number of frames captured.
+#[tauri::command(async)]
+pub fn record_input(
+    state: State<'_, AppState>,
+    path: String,
+    slot: u32,
+    duration_ms: u64,
+) -> Result<usize> {
+    let manager = state.manager().clone();
+    let recording = crate::recording::record(
+        &*manager,
+        slot,
+        std::time::Duration::from_millis(duration_ms),
+    )?;
+    recording.save(std::path::Path::new(&path))?;
+    Ok(recording.frames.len())
+}
+
+/// Replay a recording into a temporary virtual controller, using the mapping of the
+/// current assignment for `target_slot` (identity if none). Blocks until playback ends.
+#[tauri::command(async)]
+pub fn replay_input(state: State<'_, AppState>, path: String, target_slot: u8) -> Result<()> {
+    let recording = crate::recording::Recording::load(std::path::Path::new(&path))?;
+    let mapping = state
+        .lock_inner()
+        .assignments
+        .iter()
+        .find(|a| a.enabled && a.slot == target_slot)
+        .map(|a| a.mapping.clone())
+        .unwrap_or_default();
+    crate::recording::replay(&recording, &mapping)
 }
 
 /// Update a device's XInput slot assignment after identification.
@@ -306,9 +1365,19 @@ pub fn get_game_rules(state: State<AppState>) -> Result<Vec<GameRule>> {
 #[tauri::command]
 pub fn add_game_rule(
     state: State<AppState>,
-    exe_name: String,
+    exe_names: Vec<String>,
     profile_id: String,
 ) -> Result<GameRule> {
+    let exe_names: Vec<String> = exe_names
+        .into_iter()
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .collect();
+    if exe_names.is_empty() {
+        return Err(crate::error::PadSwitchError::Config(
+            "A game rule needs at least one executable name".into(),
+        ));
+    }
     let mut inner = state.lock_inner();
     // Validate that the referenced profile exists
     if !inner.config.profiles.iter().any(|p| p.id == profile_id) {
@@ -318,9 +1387,10 @@ pub fn add_game_rule(
     }
     let rule = GameRule {
         id: Uuid::new_v4().to_string(),
-        exe_name,
+        exe_names,
         profile_id,
         enabled: true,
+        activation_delay_ms: 0,
     };
     inner.config.game_rules.push(rule.clone());
     inner.config.save()?;
@@ -342,22 +1412,112 @@ pub fn toggle_game_rule(state: State<AppState>, rule_id: String, enabled: bool)
         rule.enabled = enabled;
     }
     inner.config.save()?;
+    drop(inner);
+    if enabled {
+        state.lock_watcher().unsuppress(&rule_id);
+    }
+    Ok(())
+}
+
+/// Set how long the watcher waits after a rule matches before switching profiles.
+#[tauri::command]
+pub fn set_game_rule_delay(
+    state: State<AppState>,
+    rule_id: String,
+    activation_delay_ms: u32,
+) -> Result<()> {
+    let mut inner = state.lock_inner();
+    let rule = inner
+        .config
+        .game_rules
+        .iter_mut()
+        .find(|r| r.id == rule_id)
+        .ok_or_else(|| {
+            crate::error::PadSwitchError::Config(format!("Game rule '{}' does not exist", rule_id))
+        })?;
+    rule.activation_delay_ms = activation_delay_ms;
+    inner.config.save()?;
     Ok(())
 }
 
+/// Copy an existing rule under a new id, inserted directly after the original.
+#[tauri::command]
+pub fn duplicate_game_rule(state: State<AppState>, rule_id: String) -> Result<GameRule> {
+    let mut inner = state.lock_inner();
+    let index = inner
+        .config
+        .game_rules
+        .iter()
+        .position(|r| r.id == rule_id)
+        .ok_or_else(|| {
+            crate::error::PadSwitchError::Config(format!("Game rule '{}' does not exist", rule_id))
+        })?;
+    let rule = GameRule {
+        id: Uuid::new_v4().to_string(),
+        ..inner.config.game_rules[index].clone()
+    };
+    inner.config.game_rules.insert(index + 1, rule.clone());
+    inner.config.save()?;
+    Ok(rule)
+}
+
+/// Swap a rule with its neighbour and return the reordered list.
+/// Moving the first rule up or the last rule down is a no-op.
+#[tauri::command]
+pub fn move_game_rule(
+    state: State<AppState>,
+    rule_id: String,
+    direction: MoveDirection,
+) -> Result<Vec<GameRule>> {
+    let mut inner = state.lock_inner();
+    let rules = &mut inner.config.game_rules;
+    let index = rules.iter().position(|r| r.id == rule_id).ok_or_else(|| {
+        crate::error::PadSwitchError::Config(format!("Game rule '{}' does not exist", rule_id))
+    })?;
+    let target = match direction {
+        MoveDirection::Up => index.checked_sub(1),
+        MoveDirection::Down => Some(index + 1).filter(|&i| i < rules.len()),
+    };
+    if let Some(target) = target {
+        rules.swap(index, target);
+        inner.config.save()?;
+    }
+    Ok(inner.config.game_rules.clone())
+}
+
 // --- Process watcher commands ---
 
 #[tauri::command]
 pub fn start_process_watcher(app: AppHandle, state: State<AppState>) -> Result<()> {
-    let mut watcher = state.lock_watcher();
-    watcher.start(app);
-    Ok(())
+    apply_auto_switch(app, &state, true)
+}
+
+#[tauri::command]
+pub fn stop_process_watcher(app: AppHandle, state: State<AppState>) -> Result<()> {
+    apply_auto_switch(app, &state, false)
 }
 
+/// Turn auto-switch on or off: starts/stops the process watcher and persists
+/// `settings.auto_switch` so the next launch matches.
 #[tauri::command]
-pub fn stop_process_watcher(state: State<AppState>) -> Result<()> {
+pub fn set_auto_switch(app: AppHandle, state: State<AppState>, enabled: bool) -> Result<()> {
+    apply_auto_switch(app, &state, enabled)
+}
+
+/// Start or stop the watcher and save `auto_switch`, holding the watcher lock
+/// throughout so concurrent toggles can't leave the two out of sync.
+fn apply_auto_switch(app: AppHandle, state: &AppState, enabled: bool) -> Result<()> {
     let mut watcher = state.lock_watcher();
-    watcher.stop();
+    if enabled {
+        watcher.start(app);
+    } else {
+        watcher.stop();
+    }
+    let mut inner = state.lock_inner();
+    if inner.config.settings.auto_switch != enabled {
+        inner.config.settings.auto_switch = enabled;
+        inner.config.save()?;
+    }
     Ok(())
 }
 
@@ -368,6 +1528,85 @@ pub fn is_watcher_running(state: State<AppState>) -> bool {
 
 // --- Settings commands ---
 
+/// JSON schema describing the config file, for editing it outside the app.
+#[tauri::command]
+pub fn get_config_schema() -> serde_json::Value {
+    AppConfig::schema()
+}
+
+/// Check an externally edited config file before it replaces the live config.
+/// Returns the problems found; an empty list means the file is safe to use.
+#[tauri::command]
+pub fn validate_config_file(path: String) -> Result<Vec<String>> {
+    AppConfig::validate_file(std::path::Path::new(&path))
+}
+
+/// Write the whole config (profiles, rules, settings) to `path` as a backup.
+#[tauri::command]
+pub fn backup_config(state: State<AppState>, path: String) -> Result<()> {
+    state
+        .lock_inner()
+        .config
+        .backup(std::path::Path::new(&path))
+}
+
+/// Replace the live config with a backup from `backup_config`. The backup is
+/// validated first; forwarding is stopped and the backup's active profile loaded.
+/// Emits `config-restored` with the new config so the UI reloads.
+#[tauri::command]
+pub fn restore_config(app: AppHandle, state: State<AppState>, path: String) -> Result<AppConfig> {
+    let config = AppConfig::read_backup(std::path::Path::new(&path))?;
+
+    // Persistent hides follow the restored config
+    let was_forwarding = state.stop_forwarding_with(
+        |inner| {
+            (
+                inner.forwarding_active(),
+                inner.config.persistent_hidden.clone(),
+            )
+        },
+        |(was_forwarding, previous_hidden)| {
+            let manager = state.manager();
+            manager.set_axis_overrides(config.axis_overrides.clone());
+            manager.set_device_selection(DeviceSelection::from_config(&config));
+            crate::audit::set_enabled(config.settings.audit_log_enabled);
+            for path in previous_hidden.iter().filter(|p| {
+                !config
+                    .persistent_hidden
+                    .iter()
+                    .any(|h| same_instance_path(h, p))
+            }) {
+                if let Err(e) = manager.unhide_device(path) {
+                    log::warn!("Restore: unhide failed for {}: {}", path, e);
+                }
+            }
+            crate::platform::apply_persistent_hides(&**manager, &config.persistent_hidden);
+            was_forwarding
+        },
+    );
+
+    let mut inner = state.lock_inner();
+    inner.config = config.clone();
+    inner.assignments = inner
+        .active_profile()
+        .map(|p| p.assignments.clone())
+        .unwrap_or_default();
+    inner.config.save()?;
+    drop(inner);
+
+    log::info!("Config restored from {}", path);
+
+    if was_forwarding {
+        let _ = app.emit("forwarding-status", serde_json::json!({ "active": false }));
+    }
+    crate::tray::rebuild_tray_menu(&app);
+    crate::control_server::sync_with_settings(&app);
+    apply_auto_switch(app.clone(), &state, config.settings.auto_switch)?;
+    crate::device_refresh::schedule(&app); // The device selection may have changed
+    let _ = app.emit("config-restored", serde_json::json!({ "config": config }));
+    Ok(config)
+}
+
 #[tauri::command]
 pub fn get_settings(state: State<AppState>) -> Result<Settings> {
     let inner = state.lock_inner();
@@ -375,9 +1614,33 @@ pub fn get_settings(state: State<AppState>) -> Result<Settings> {
 }
 
 #[tauri::command]
-pub fn update_settings(state: State<AppState>, settings: Settings) -> Result<()> {
+pub fn update_settings(app: AppHandle, state: State<AppState>, settings: Settings) -> Result<()> {
     let mut inner = state.lock_inner();
+    let server_changed = inner.config.settings.control_server_enabled
+        != settings.control_server_enabled
+        || inner.config.settings.control_server_port != settings.control_server_port
+        || inner.config.settings.control_server_token != settings.control_server_token;
+    let auto_switch =
+        (inner.config.settings.auto_switch != settings.auto_switch).then_some(settings.auto_switch);
+    let selection_changed = inner.config.settings.manual_device_mode != settings.manual_device_mode;
+    crate::audit::set_enabled(settings.audit_log_enabled);
     inner.config.settings = settings;
     inner.config.save()?;
+    if selection_changed {
+        let selection = DeviceSelection::from_config(&inner.config);
+        state.manager().set_device_selection(selection);
+    }
+    drop(inner);
+
+    if selection_changed {
+        crate::device_refresh::schedule(&app);
+    }
+
+    if server_changed {
+        crate::control_server::sync_with_settings(&app);
+    }
+    if let Some(enabled) = auto_switch {
+        apply_auto_switch(app, &state, enabled)?;
+    }
     Ok(())
 }
@@ -1,50 +1,361 @@
 use crate::device::SlotAssignment;
 use crate::error::{PadSwitchError, Result};
+use crate::mapping::AxisOverride;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Default, PartialEq, Eq)]
 pub enum RoutingMode {
     #[default]
     Minimal,
     Force,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Which controller model the virtual ViGEm targets identify as (Force mode, Windows).
+///
+/// Only the USB vendor/product IDs change — the report format is always Xbox 360,
+/// so impulse triggers and the Share button are not forwarded. `XboxSeries` helps
+/// games that pick button glyphs or enable features by controller ID; everything
+/// else should stay on `Xbox360`, which every XInput game recognises.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, Default, PartialEq, Eq)]
+pub enum VirtualSubtype {
+    #[default]
+    Xbox360,
+    XboxSeries,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Profile {
     pub id: String,
     pub name: String,
+    #[serde(default)]
     pub assignments: Vec<SlotAssignment>,
     #[serde(default)]
     pub routing_mode: RoutingMode,
+    /// Force mode (Windows): also disable physical devices via SetupDi, not just hide
+    /// them with HidHide. For games that read raw HID and bypass HidHide. Requires admin.
+    #[serde(default)]
+    pub hard_hide: bool,
+    /// Controller model presented by the virtual targets in Force mode.
+    #[serde(default)]
+    pub virtual_subtype: VirtualSubtype,
+    /// Shared `VirtualLayout` to build the Force mode targets from, instead of one
+    /// `virtual_subtype` target per assigned slot.
+    #[serde(default)]
+    pub virtual_layout_id: Option<String>,
+}
+
+/// A new profile as sent to `save_profile`. The id is assigned on save; omitted
+/// options take the `Profile` defaults.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ProfileInput {
+    pub name: String,
+    #[serde(default)]
+    pub assignments: Vec<SlotAssignment>,
+    #[serde(default)]
+    pub routing_mode: RoutingMode,
+    #[serde(default)]
+    pub hard_hide: bool,
+    #[serde(default)]
+    pub virtual_subtype: VirtualSubtype,
+    #[serde(default)]
+    pub virtual_layout_id: Option<String>,
 }
 
+impl ProfileInput {
+    pub fn into_profile(self, id: String) -> Profile {
+        Profile {
+            id,
+            name: self.name,
+            assignments: self.assignments,
+            routing_mode: self.routing_mode,
+            hard_hide: self.hard_hide,
+            virtual_subtype: self.virtual_subtype,
+            virtual_layout_id: self.virtual_layout_id,
+        }
+    }
+}
+
+/// A reusable set of Force mode virtual targets (e.g. "4x Xbox 360"), shared by
+/// every profile that references it. Targets are created in list order; targets
+/// past the last assigned slot are plugged in but idle.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct VirtualLayout {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub targets: Vec<VirtualSubtype>,
+}
+
+/// XInput games see at most four controllers, so a layout can't have more.
+pub const MAX_LAYOUT_TARGETS: usize = 4;
+
 /// A rule that maps a game executable to a preset profile.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct GameRule {
     pub id: String,
-    /// Executable filename to match (e.g. "RocketLeague.exe"). Case-insensitive.
-    pub exe_name: String,
+    /// Executable filenames to match (e.g. "RocketLeague.exe"). Case-insensitive;
+    /// the rule matches if any of them is running, so a launcher and the game it
+    /// spawns can share one rule. Older configs stored a single `exe_name` string.
+    #[serde(alias = "exe_name", deserialize_with = "string_or_vec")]
+    #[schemars(with = "Vec<String>")]
+    pub exe_names: Vec<String>,
     /// Which profile to activate when this game is running.
     pub profile_id: String,
     /// Whether this rule is active.
     #[serde(default = "default_true")]
     pub enabled: bool,
+    /// Wait this long after a match, then re-check the process is still running
+    /// before switching profiles. Rides out launchers that respawn the game.
+    #[serde(default)]
+    pub activation_delay_ms: u32,
+}
+
+/// Direction for moving a game rule one position in the list.
+///
+/// Rules are matched in list order (first match wins), so moving a rule up
+/// gives it precedence over the rule above it.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum MoveDirection {
+    Up,
+    Down,
+}
+
+/// Which XInput DLL to load (Windows). Versions differ in battery and Guide button
+/// support; `Auto` tries the newest available. Takes effect on restart.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, Default, PartialEq, Eq)]
+pub enum XInputVersion {
+    #[default]
+    Auto,
+    XInput1_4,
+    XInput1_3,
+    XInput9_1_0,
+}
+
+/// What the process watcher does when the game that triggered a rule exits.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, Default, PartialEq, Eq)]
+pub enum GameExitBehavior {
+    /// Re-activate the profile that was active before the game launched.
+    #[default]
+    RevertToPrevious,
+    /// Leave the game's profile active but stop forwarding.
+    StopForwarding,
+    /// Leave the game's profile active and forwarding untouched.
+    KeepProfile,
+}
+
+/// How much the tray's Profiles submenu shows next to each profile name.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, Default, PartialEq, Eq)]
+pub enum TrayProfileDetail {
+    /// Only a `*` before the active profile.
+    #[default]
+    Marker,
+    /// Also the routing mode after every profile.
+    RoutingMode,
+    /// Routing mode, and `▶` instead of `*` while the active profile is forwarding.
+    Full,
+}
+
+impl GameRule {
+    /// The first of this rule's executables found in `processes`, if any.
+    pub fn matching_exe<'a>(&'a self, processes: &[String]) -> Option<&'a str> {
+        self.exe_names
+            .iter()
+            .find(|name| processes.iter().any(|p| p.eq_ignore_ascii_case(name)))
+            .map(String::as_str)
+    }
+}
+
+/// Accept either a single string (pre-multi-exe configs) or a list of strings.
+fn string_or_vec<'de, D>(deserializer: D) -> std::result::Result<Vec<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum StringOrVec {
+        One(String),
+        Many(Vec<String>),
+    }
+
+    Ok(match StringOrVec::deserialize(deserializer)? {
+        StringOrVec::One(name) => vec![name],
+        StringOrVec::Many(names) => names,
+    })
 }
 
 fn default_true() -> bool {
     true
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Settings {
+    #[serde(default)]
     pub auto_start: bool,
+    #[serde(default)]
     pub start_minimized: bool,
+    /// Start forwarding the active profile when the app launches (not in safe mode).
+    #[serde(default)]
     pub auto_forward_on_launch: bool,
     /// Whether the process watcher is enabled (auto-switch presets on game launch).
     #[serde(default)]
     pub auto_switch: bool,
+    #[serde(default)]
     pub active_profile_id: Option<String>,
+    /// Serve the local control endpoint on 127.0.0.1 for external tools (Stream Deck, macros).
+    #[serde(default)]
+    pub control_server_enabled: bool,
+    /// Port for the local control endpoint.
+    #[serde(default = "default_control_server_port")]
+    pub control_server_port: u16,
+    /// Token external tools must send in the `X-PadSwitch-Token` header.
+    #[serde(default)]
+    pub control_server_token: Option<String>,
+    /// Whitelist `overlay_whitelist_apps` in HidHide while Force mode is active,
+    /// so input overlays and streaming tools still see hidden controllers.
+    #[serde(default)]
+    pub overlay_whitelist_enabled: bool,
+    /// Executable names (matched against running processes) or full paths to whitelist.
+    #[serde(default = "default_overlay_whitelist_apps")]
+    pub overlay_whitelist_apps: Vec<String>,
+    /// What auto-switch does when the matched game exits.
+    #[serde(default)]
+    pub on_game_exit: GameExitBehavior,
+    /// Minimum time between two auto-switch profile changes; transitions inside it
+    /// wait until it's over. Stops flapping launchers from restarting forwarding
+    /// over and over. 0 disables it.
+    #[serde(default)]
+    pub watcher_switch_cooldown_ms: u32,
+    /// XInput DLL to load; falls back to `Auto` if the requested one is missing.
+    #[serde(default)]
+    pub xinput_version: XInputVersion,
+    /// Read the Guide button via the undocumented `XInputGetStateEx` (ordinal 100)
+    /// and forward it. Ignored with xinput9_1_0, which lacks it. Takes effect on restart.
+    #[serde(default)]
+    pub forward_guide_button: bool,
+    /// Minimal mode: seconds without a heartbeat before a separate watchdog process
+    /// re-enables the reordered devices (covers the app being killed). 0 disables it.
+    #[serde(default)]
+    pub minimal_watchdog_secs: u32,
+    /// Force mode: also blacklist the HID interface paths of each hidden device and its
+    /// children. Needed for composite/wireless controllers games still see otherwise.
+    #[serde(default = "default_true")]
+    pub hide_interface_paths: bool,
+    /// Minimal mode: skip devices that were already disabled before the session and
+    /// only re-enable the ones PadSwitch disabled. Off restores the old behavior of
+    /// re-enabling every assigned device on cleanup.
+    #[serde(default = "default_true")]
+    pub minimal_cleanup_only_own: bool,
+    /// Force mode: send neutral input while none of the focus executables owns the
+    /// foreground window, and resume when one does.
+    #[serde(default)]
+    pub forward_only_when_focused: bool,
+    /// Executables whose window must be focused. Empty means the executables of
+    /// all enabled game rules.
+    #[serde(default)]
+    pub focus_exe_names: Vec<String>,
+    /// Process names that conflict with PadSwitch (checked by `detect_conflicts`).
+    #[serde(default = "default_conflict_processes")]
+    pub conflict_processes: Vec<String>,
+    /// Force mode: forward rumble games send the virtual controllers to the physical
+    /// pads feeding them (Windows).
+    #[serde(default = "default_true")]
+    pub forward_rumble: bool,
+    /// Reset keeps the process watcher running and only ignores the rules whose game
+    /// is running, until that game exits. Off stops the watcher on reset.
+    #[serde(default)]
+    pub reset_suppresses_running_games: bool,
+    /// Reset: disable then re-enable each device instead of only enabling it, for
+    /// controllers that don't come back otherwise. Needs elevation; falls back to a
+    /// plain enable without it.
+    #[serde(default)]
+    pub reset_power_cycle: bool,
+    /// Reset: instance paths to restore first, in order.
+    #[serde(default)]
+    pub reset_priority: Vec<String>,
+    /// Force mode: send every virtual controller one neutral frame (buttons up,
+    /// sticks centred, triggers released) before unplugging it, so games don't latch
+    /// the last held input.
+    #[serde(default = "default_true")]
+    pub neutralize_on_stop: bool,
+    /// How long to hold the neutral frame before unplugging, in milliseconds.
+    #[serde(default = "default_stop_settle_ms")]
+    pub stop_settle_ms: u32,
+    /// Force mode failsafe: holding `reset_chord_buttons` on any forwarded controller
+    /// for `reset_chord_hold_ms` runs Reset all.
+    #[serde(default)]
+    pub reset_chord_enabled: bool,
+    /// XInput button bits that make up the chord (all must be held).
+    #[serde(default = "default_reset_chord_buttons")]
+    pub reset_chord_buttons: u16,
+    #[serde(default = "default_reset_chord_hold_ms")]
+    pub reset_chord_hold_ms: u32,
+    /// After a crash, re-enable and unhide every device and clear the active profile
+    /// at launch. Off: only report it (`dirty-shutdown-detected`) and leave recovery
+    /// to the user. Safe mode always recovers.
+    #[serde(default = "default_true")]
+    pub auto_recover_on_dirty_shutdown: bool,
+    /// Detail shown next to profile names in the tray menu.
+    #[serde(default)]
+    pub tray_profile_detail: TrayProfileDetail,
+    /// Only treat `AppConfig::marked_controllers` as controllers, picked from every
+    /// input device, instead of guessing. For pads the heuristic misclassifies.
+    #[serde(default)]
+    pub manual_device_mode: bool,
+    /// Append every hide/unhide/enable/disable and HidHide switch to `audit.log`
+    /// in the data dir, with the caller and outcome.
+    #[serde(default)]
+    pub audit_log_enabled: bool,
+}
+
+fn default_conflict_processes() -> Vec<String> {
+    [
+        "reWASDEngine.exe",
+        "reWASDTray.exe",
+        "DS4Windows.exe",
+        "DSX.exe",
+        "x360ce.exe",
+        "InputMapper.exe",
+        "BetterJoy.exe",
+        "antimicrox",
+        "sc-controller",
+    ]
+    .iter()
+    .map(|s| s.to_string())
+    .collect()
+}
+
+fn default_overlay_whitelist_apps() -> Vec<String> {
+    [
+        "obs64.exe",
+        "obs32.exe",
+        "Streamlabs OBS.exe",
+        "Streamlabs Desktop.exe",
+        "XSplit.Core.exe",
+        "reWASD.exe",
+    ]
+    .iter()
+    .map(|s| s.to_string())
+    .collect()
+}
+
+fn default_reset_chord_buttons() -> u16 {
+    use crate::mapping::buttons;
+    buttons::START | buttons::BACK | buttons::LEFT_SHOULDER | buttons::RIGHT_SHOULDER
+}
+
+fn default_reset_chord_hold_ms() -> u32 {
+    2000
+}
+
+fn default_stop_settle_ms() -> u32 {
+    50
+}
+
+fn default_control_server_port() -> u16 {
+    37821
 }
 
 impl Default for Settings {
@@ -55,16 +366,59 @@ impl Default for Settings {
             auto_forward_on_launch: false,
             auto_switch: false,
             active_profile_id: None,
+            control_server_enabled: false,
+            control_server_port: default_control_server_port(),
+            control_server_token: None,
+            overlay_whitelist_enabled: false,
+            overlay_whitelist_apps: default_overlay_whitelist_apps(),
+            on_game_exit: GameExitBehavior::default(),
+            watcher_switch_cooldown_ms: 0,
+            xinput_version: XInputVersion::default(),
+            forward_guide_button: false,
+            minimal_watchdog_secs: 0,
+            hide_interface_paths: true,
+            minimal_cleanup_only_own: true,
+            forward_only_when_focused: false,
+            focus_exe_names: vec![],
+            conflict_processes: default_conflict_processes(),
+            forward_rumble: true,
+            reset_suppresses_running_games: false,
+            reset_power_cycle: false,
+            reset_priority: vec![],
+            neutralize_on_stop: true,
+            stop_settle_ms: default_stop_settle_ms(),
+            reset_chord_enabled: false,
+            reset_chord_buttons: default_reset_chord_buttons(),
+            reset_chord_hold_ms: default_reset_chord_hold_ms(),
+            auto_recover_on_dirty_shutdown: true,
+            tray_profile_detail: TrayProfileDetail::default(),
+            manual_device_mode: false,
+            audit_log_enabled: false,
         }
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct AppConfig {
+    #[serde(default)]
     pub settings: Settings,
+    #[serde(default)]
     pub profiles: Vec<Profile>,
     #[serde(default)]
     pub game_rules: Vec<GameRule>,
+    /// Instance paths kept hidden via HidHide across sessions (Windows). Re-applied at
+    /// startup and never unhidden by forwarding cleanup or dirty-shutdown recovery.
+    #[serde(default)]
+    pub persistent_hidden: Vec<String>,
+    /// Axis overrides per device id, for controllers with a non-standard axis
+    /// layout (Linux).
+    #[serde(default)]
+    pub axis_overrides: BTreeMap<String, Vec<AxisOverride>>,
+    /// Device ids marked as controllers, for `Settings::manual_device_mode`.
+    #[serde(default)]
+    pub marked_controllers: Vec<String>,
+    #[serde(default)]
+    pub virtual_layouts: Vec<VirtualLayout>,
 }
 
 impl Default for AppConfig {
@@ -73,15 +427,42 @@ impl Default for AppConfig {
             settings: Settings::default(),
             profiles: vec![],
             game_rules: vec![],
+            persistent_hidden: vec![],
+            axis_overrides: BTreeMap::new(),
+            marked_controllers: vec![],
+            virtual_layouts: vec![],
         }
     }
 }
 
+/// Set to a directory to keep the config, lockfile and heartbeat there instead of
+/// the per-user config directory (portable installs, isolated test runs).
+pub const CONFIG_DIR_ENV: &str = "PADSWITCH_CONFIG_DIR";
+
+/// Directory everything PadSwitch persists lives in: `PADSWITCH_CONFIG_DIR` if set,
+/// otherwise `padswitch` under the OS config directory. Not created here.
+pub fn data_dir() -> Option<PathBuf> {
+    match std::env::var_os(CONFIG_DIR_ENV) {
+        Some(dir) if !dir.is_empty() => Some(PathBuf::from(dir)),
+        _ => dirs::config_dir().map(|d| d.join("padswitch")),
+    }
+}
+
+/// Format version written into config backups. Bump when a backup written by this
+/// build could not be read by an older one.
+const BACKUP_VERSION: u32 = 1;
+
+/// Whole-config backup file: the config plus a version header.
+#[derive(Serialize, Deserialize)]
+struct ConfigBackup {
+    version: u32,
+    config: AppConfig,
+}
+
 impl AppConfig {
     fn config_path() -> Result<PathBuf> {
-        let dir = dirs::config_dir()
-            .ok_or_else(|| PadSwitchError::Config("Cannot find config directory".into()))?
-            .join("padswitch");
+        let dir = data_dir()
+            .ok_or_else(|| PadSwitchError::Config("Cannot find config directory".into()))?;
         std::fs::create_dir_all(&dir)?;
         Ok(dir.join("config.json"))
     }
@@ -97,10 +478,246 @@ impl AppConfig {
         Ok(serde_json::from_str(&data)?)
     }
 
+    /// Write the config via a temp file and rename, so a crash mid-write never
+    /// leaves a truncated config behind.
     pub fn save(&self) -> Result<()> {
         let path = Self::config_path()?;
         let data = serde_json::to_string_pretty(self)?;
-        std::fs::write(&path, data)?;
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, data)?;
+        std::fs::rename(&tmp, &path)?;
+        Ok(())
+    }
+
+    /// Write the whole config (profiles, rules, settings) to `path` as a backup.
+    pub fn backup(&self, path: &Path) -> Result<()> {
+        let data = serde_json::to_string_pretty(&ConfigBackup {
+            version: BACKUP_VERSION,
+            config: self.clone(),
+        })?;
+        std::fs::write(path, data)?;
         Ok(())
     }
+
+    /// Read a backup written by `backup`. Rejects backups from a newer format
+    /// and configs that fail `validate`, so a bad file never replaces the live one.
+    pub fn read_backup(path: &Path) -> Result<Self> {
+        let data = std::fs::read_to_string(path)?;
+        let backup: ConfigBackup = serde_json::from_str(&data)
+            .map_err(|e| PadSwitchError::Config(format!("Not a PadSwitch backup: {}", e)))?;
+        if backup.version > BACKUP_VERSION {
+            return Err(PadSwitchError::Config(format!(
+                "Backup format {} is newer than this version of PadSwitch supports ({})",
+                backup.version, BACKUP_VERSION
+            )));
+        }
+        let problems = backup.config.validate();
+        if !problems.is_empty() {
+            return Err(PadSwitchError::Config(format!(
+                "Backup is invalid: {}",
+                problems.join("; ")
+            )));
+        }
+        Ok(backup.config)
+    }
+
+    /// JSON schema of the config file, for editing it outside the app.
+    pub fn schema() -> serde_json::Value {
+        serde_json::to_value(schemars::schema_for!(AppConfig)).unwrap_or_default()
+    }
+
+    /// Check an edited config file before it replaces the live one.
+    ///
+    /// Returns every problem found (empty if the file is safe to use): parse and
+    /// type errors first, then invariants the types can't express. Only I/O
+    /// failures are returned as `Err`.
+    pub fn validate_file(path: &Path) -> Result<Vec<String>> {
+        let data = std::fs::read_to_string(path)?;
+        match serde_json::from_str::<Self>(&data) {
+            Ok(config) => Ok(config.validate()),
+            Err(e) => Ok(vec![format!("Invalid config: {}", e)]),
+        }
+    }
+
+    /// The layout `profile` references, if it exists.
+    pub fn virtual_layout_of(&self, profile: &Profile) -> Option<&VirtualLayout> {
+        let id = profile.virtual_layout_id.as_deref()?;
+        self.virtual_layouts.iter().find(|l| l.id == id)
+    }
+
+    /// Profiles with at least one assignment (enabled or not) for `device_id`.
+    pub fn profiles_using_device(&self, device_id: &str) -> Vec<&Profile> {
+        self.profiles
+            .iter()
+            .filter(|p| p.assignments.iter().any(|a| a.device_id == device_id))
+            .collect()
+    }
+
+    /// Invariants not enforced by deserialization: slot ranges, unique ids and
+    /// references to existing profiles.
+    pub fn validate(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        let mut profile_ids: Vec<&str> = Vec::new();
+
+        for profile in &self.profiles {
+            if profile.id.trim().is_empty() {
+                problems.push(format!("Profile \"{}\" has an empty id", profile.name));
+            } else if profile_ids.contains(&profile.id.as_str()) {
+                problems.push(format!("Duplicate profile id \"{}\"", profile.id));
+            } else {
+                profile_ids.push(&profile.id);
+            }
+            for assignment in &profile.assignments {
+                if assignment.slot > 3 {
+                    problems.push(format!(
+                        "Profile \"{}\" assigns device {} to slot {} (must be 0-3)",
+                        profile.name, assignment.device_id, assignment.slot
+                    ));
+                }
+            }
+            if let Some(layout_id) = &profile.virtual_layout_id {
+                if self.virtual_layout_of(profile).is_none() {
+                    problems.push(format!(
+                        "Profile \"{}\" references missing virtual layout \"{}\"",
+                        profile.name, layout_id
+                    ));
+                }
+            }
+        }
+
+        let mut layout_ids: Vec<&str> = Vec::new();
+        for layout in &self.virtual_layouts {
+            if layout_ids.contains(&layout.id.as_str()) {
+                problems.push(format!("Duplicate virtual layout id \"{}\"", layout.id));
+            } else {
+                layout_ids.push(&layout.id);
+            }
+            if layout.targets.len() > MAX_LAYOUT_TARGETS {
+                problems.push(format!(
+                    "Virtual layout \"{}\" has {} targets (at most {})",
+                    layout.name,
+                    layout.targets.len(),
+                    MAX_LAYOUT_TARGETS
+                ));
+            }
+        }
+
+        let mut rule_ids: Vec<&str> = Vec::new();
+        for rule in &self.game_rules {
+            if rule_ids.contains(&rule.id.as_str()) {
+                problems.push(format!("Duplicate game rule id \"{}\"", rule.id));
+            } else {
+                rule_ids.push(&rule.id);
+            }
+            if rule.exe_names.iter().all(|n| n.trim().is_empty()) {
+                problems.push(format!("Game rule \"{}\" has no executable names", rule.id));
+            }
+            if !profile_ids.contains(&rule.profile_id.as_str()) {
+                problems.push(format!(
+                    "Game rule \"{}\" references missing profile \"{}\"",
+                    rule.id, rule.profile_id
+                ));
+            }
+        }
+
+        if let Some(active) = &self.settings.active_profile_id {
+            if !profile_ids.contains(&active.as_str()) {
+                problems.push(format!("Active profile \"{}\" does not exist", active));
+            }
+        }
+
+        problems
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_config_loads_with_defaults() {
+        let config: AppConfig = serde_json::from_str("{}").unwrap();
+        assert!(config.profiles.is_empty());
+        assert!(config.game_rules.is_empty());
+        assert_eq!(
+            config.settings.control_server_port,
+            default_control_server_port()
+        );
+    }
+
+    #[test]
+    fn partial_config_keeps_present_fields() {
+        let json = r#"{
+            "settings": { "auto_switch": true },
+            "profiles": [{ "id": "p1", "name": "Co-op" }]
+        }"#;
+        let config: AppConfig = serde_json::from_str(json).unwrap();
+        assert!(config.settings.auto_switch);
+        assert!(!config.settings.auto_start);
+        assert_eq!(config.profiles[0].name, "Co-op");
+        assert!(config.profiles[0].assignments.is_empty());
+        assert_eq!(config.profiles[0].routing_mode, RoutingMode::Minimal);
+    }
+
+    #[test]
+    fn validate_reports_broken_references() {
+        let json = r#"{
+            "settings": { "active_profile_id": "gone" },
+            "profiles": [
+                { "id": "p1", "name": "A", "assignments": [
+                    { "device_id": "d1", "slot": 7, "enabled": true }
+                ] },
+                { "id": "p1", "name": "B" }
+            ],
+            "game_rules": [{ "id": "r1", "exe_names": ["game.exe"], "profile_id": "p2" }]
+        }"#;
+        let config: AppConfig = serde_json::from_str(json).unwrap();
+        let problems = config.validate();
+        assert_eq!(problems.len(), 4, "{:?}", problems);
+        assert!(AppConfig::default().validate().is_empty());
+    }
+
+    #[test]
+    fn profiles_using_device_matches_any_assignment() {
+        let json = r#"{
+            "profiles": [
+                { "id": "p1", "name": "A", "assignments": [
+                    { "device_id": "d1", "slot": 0, "enabled": true }
+                ] },
+                { "id": "p2", "name": "B", "assignments": [
+                    { "device_id": "d2", "slot": 0, "enabled": true },
+                    { "device_id": "d1", "slot": 1, "enabled": false }
+                ] },
+                { "id": "p3", "name": "C" }
+            ]
+        }"#;
+        let config: AppConfig = serde_json::from_str(json).unwrap();
+        let ids: Vec<&str> = config
+            .profiles_using_device("d1")
+            .iter()
+            .map(|p| p.id.as_str())
+            .collect();
+        assert_eq!(ids, ["p1", "p2"]);
+        assert!(config.profiles_using_device("d3").is_empty());
+    }
+
+    #[test]
+    fn profiles_resolve_shared_virtual_layouts() {
+        let json = r#"{
+            "profiles": [
+                { "id": "p1", "name": "A", "virtual_layout_id": "l1" },
+                { "id": "p2", "name": "B", "virtual_layout_id": "gone" },
+                { "id": "p3", "name": "C" }
+            ],
+            "virtual_layouts": [
+                { "id": "l1", "name": "2x Series", "targets": ["XboxSeries", "XboxSeries"] }
+            ]
+        }"#;
+        let config: AppConfig = serde_json::from_str(json).unwrap();
+        let layout = config.virtual_layout_of(&config.profiles[0]).unwrap();
+        assert_eq!(layout.targets, [VirtualSubtype::XboxSeries; 2]);
+        assert!(config.virtual_layout_of(&config.profiles[1]).is_none());
+        assert!(config.virtual_layout_of(&config.profiles[2]).is_none());
+        assert_eq!(config.validate().len(), 1);
+    }
 }
@@ -0,0 +1,115 @@
+use crate::process_watcher::list_running_processes;
+use serde::Serialize;
+use std::path::PathBuf;
+
+/// Exe names of the Steam client.
+const STEAM_PROCESSES: &[&str] = &["steam.exe", "steam"];
+
+const STEAM_INPUT_ENABLED: &str =
+    "Steam Input is enabled for Xbox controllers, so Steam grabs and re-emits them.";
+const STEAM_INPUT_UNKNOWN: &str =
+    "Steam is running; if Steam Input is enabled for Xbox controllers it will grab and re-emit them.";
+
+/// A third-party program or setting that fights PadSwitch over the same devices.
+#[derive(Debug, Clone, Serialize)]
+pub struct ConflictWarning {
+    /// What was detected (process name, or "Steam Input").
+    pub source: String,
+    pub message: String,
+    /// What the user can do about it.
+    pub remediation: String,
+}
+
+/// Check running processes against `conflict_processes` (case-insensitive exe names)
+/// and whether Steam Input is enabled for Xbox controllers.
+pub fn detect(conflict_processes: &[String]) -> Vec<ConflictWarning> {
+    let processes = list_running_processes();
+    let is_running = |name: &str| processes.iter().any(|p| p.eq_ignore_ascii_case(name));
+
+    let mut warnings: Vec<ConflictWarning> = conflict_processes
+        .iter()
+        .filter(|name| is_running(name))
+        .map(|name| ConflictWarning {
+            source: name.clone(),
+            message: format!("{} is running and also remaps or hides controllers.", name),
+            remediation: format!(
+                "Close {} (including its tray icon) before forwarding, \
+                 or turn off its controller handling.",
+                name
+            ),
+        })
+        .collect();
+
+    warnings.extend(steam_input_warning(&processes));
+    warnings
+}
+
+/// Just the Steam Input check from `detect`, for the Force-mode preflight: Steam
+/// re-grabs the virtual controller and fights HidHide over the physical ones.
+pub fn detect_steam_input() -> Option<ConflictWarning> {
+    steam_input_warning(&list_running_processes())
+}
+
+fn steam_input_warning(processes: &[String]) -> Option<ConflictWarning> {
+    let steam_running = STEAM_PROCESSES
+        .iter()
+        .any(|name| processes.iter().any(|p| p.eq_ignore_ascii_case(name)));
+    if !steam_running {
+        return None;
+    }
+    let message = match steam_xbox_support_enabled() {
+        Some(false) => return None,
+        Some(true) => STEAM_INPUT_ENABLED,
+        None => STEAM_INPUT_UNKNOWN,
+    };
+    Some(ConflictWarning {
+        source: "Steam Input".into(),
+        message: message.into(),
+        remediation: "In Steam, open Settings → Controller and turn off Steam Input \
+                      for Xbox controllers, or disable it per game."
+            .into(),
+    })
+}
+
+/// Read `SteamController_XBoxSupport` from Steam's global config.
+/// `None` if Steam's config can't be found or doesn't mention the setting.
+fn steam_xbox_support_enabled() -> Option<bool> {
+    steam_config_candidates()
+        .into_iter()
+        .find_map(|path| std::fs::read_to_string(path).ok())
+        .and_then(|vdf| {
+            vdf.lines().find_map(|line| {
+                let mut parts = line.split('"').filter(|p| !p.trim().is_empty());
+                match (parts.next(), parts.next()) {
+                    (Some("SteamController_XBoxSupport"), Some(value)) => Some(value == "1"),
+                    _ => None,
+                }
+            })
+        })
+}
+
+/// Default locations of Steam's `config/config.vdf`.
+fn steam_config_candidates() -> Vec<PathBuf> {
+    let mut roots: Vec<PathBuf> = Vec::new();
+    #[cfg(target_os = "windows")]
+    {
+        for var in ["ProgramFiles(x86)", "ProgramFiles"] {
+            if let Some(dir) = std::env::var_os(var) {
+                roots.push(PathBuf::from(dir).join("Steam"));
+            }
+        }
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        if let Some(home) = dirs::home_dir() {
+            roots.push(home.join(".steam").join("steam"));
+        }
+        if let Some(data) = dirs::data_dir() {
+            roots.push(data.join("Steam"));
+        }
+    }
+    roots
+        .into_iter()
+        .map(|root| root.join("config").join("config.vdf"))
+        .collect()
+}
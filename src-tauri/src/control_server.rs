@@ -0,0 +1,267 @@
+use crate::device::SlotAssignment;
+use crate::error::PadSwitchError;
+use crate::state::AppState;
+use std::io::{Read, Write};
+use std::net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+/// Header external tools must send with the token from `Settings::control_server_token`.
+const TOKEN_HEADER: &str = "x-padswitch-token";
+
+/// Upper bound on request head size — we never need a body.
+const MAX_REQUEST_BYTES: usize = 8 * 1024;
+
+/// Minimal local HTTP control endpoint for external integrations (Stream Deck, macros).
+///
+/// Bound to 127.0.0.1 only and every request must carry the configured token.
+/// Routes map 1:1 onto the existing Tauri commands:
+/// - `POST /profiles/{id}/activate`
+/// - `POST /forwarding/start`
+/// - `POST /forwarding/stop`
+/// - `POST /reset`
+pub struct ControlServer {
+    running: Arc<AtomicBool>,
+    thread_handle: Option<std::thread::JoinHandle<()>>,
+}
+
+impl ControlServer {
+    pub fn new() -> Self {
+        Self {
+            running: Arc::new(AtomicBool::new(false)),
+            thread_handle: None,
+        }
+    }
+
+    pub fn start(&mut self, app: AppHandle, port: u16, token: String) -> crate::error::Result<()> {
+        if self.running.load(Ordering::SeqCst) {
+            return Ok(());
+        }
+        if token.is_empty() {
+            return Err(crate::error::PadSwitchError::Config(
+                "Control server requires a non-empty token".into(),
+            ));
+        }
+
+        let listener = TcpListener::bind(SocketAddr::from((Ipv4Addr::LOCALHOST, port)))?;
+        // Non-blocking accept so the loop can observe the running flag
+        listener.set_nonblocking(true)?;
+
+        let running = self.running.clone();
+        running.store(true, Ordering::SeqCst);
+
+        let handle = std::thread::Builder::new()
+            .name("padswitch-control-server".into())
+            .spawn(move || server_loop(running, listener, app, token))
+            .map_err(|e| {
+                self.running.store(false, Ordering::SeqCst);
+                crate::error::PadSwitchError::Platform(format!(
+                    "Failed to spawn control server: {}",
+                    e
+                ))
+            })?;
+
+        self.thread_handle = Some(handle);
+        log::info!("Control server listening on 127.0.0.1:{}", port);
+        Ok(())
+    }
+
+    pub fn stop(&mut self) {
+        if !self.running.load(Ordering::SeqCst) {
+            return;
+        }
+        self.running.store(false, Ordering::SeqCst);
+        if let Some(handle) = self.thread_handle.take() {
+            let _ = handle.join();
+        }
+        log::info!("Control server stopped");
+    }
+}
+
+impl Drop for ControlServer {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// Start or stop the control server so it matches the current settings.
+pub fn sync_with_settings(app: &AppHandle) {
+    let state = app.state::<AppState>();
+    let (enabled, port, token) = {
+        let inner = state.lock_inner();
+        let s = &inner.config.settings;
+        (
+            s.control_server_enabled,
+            s.control_server_port,
+            s.control_server_token.clone().unwrap_or_default(),
+        )
+    };
+
+    let mut server = state.lock_control_server();
+    // Always restart so port/token changes take effect
+    server.stop();
+    if enabled {
+        if let Err(e) = server.start(app.clone(), port, token) {
+            log::error!("Failed to start control server: {}", e);
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Server loop
+// ---------------------------------------------------------------------------
+
+fn server_loop(running: Arc<AtomicBool>, listener: TcpListener, app: AppHandle, token: String) {
+    while running.load(Ordering::SeqCst) {
+        match listener.accept() {
+            Ok((stream, _)) => {
+                if let Err(e) = handle_connection(stream, &app, &token) {
+                    log::warn!("Control server: connection error: {}", e);
+                }
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                std::thread::sleep(Duration::from_millis(50));
+            }
+            Err(e) => {
+                log::warn!("Control server: accept failed: {}", e);
+                std::thread::sleep(Duration::from_millis(50));
+            }
+        }
+    }
+}
+
+fn handle_connection(mut stream: TcpStream, app: &AppHandle, token: &str) -> std::io::Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(Duration::from_secs(2)))?;
+    stream.set_write_timeout(Some(Duration::from_secs(2)))?;
+
+    let Some(head) = read_request_head(&mut stream)? else {
+        return write_response(&mut stream, 400, &error_body("Malformed request"));
+    };
+
+    let mut lines = head.lines();
+    let request_line = lines.next().unwrap_or_default();
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default();
+    let path = parts.next().unwrap_or_default();
+
+    let provided = lines
+        .filter_map(|l| l.split_once(':'))
+        .find(|(name, _)| name.trim().eq_ignore_ascii_case(TOKEN_HEADER))
+        .map(|(_, value)| value.trim());
+    if !provided.is_some_and(|p| token_matches(p, token)) {
+        return write_response(&mut stream, 401, &error_body("Missing or invalid token"));
+    }
+
+    if method != "POST" {
+        return write_response(&mut stream, 405, &error_body("Only POST is supported"));
+    }
+
+    let (status, body) = match dispatch(app, path) {
+        Some(Ok(())) => (200, serde_json::json!({ "ok": true }).to_string()),
+        Some(Err(e @ PadSwitchError::InvalidState(_))) => (409, error_body(&e.to_string())),
+        Some(Err(e)) => (400, error_body(&e.to_string())),
+        None => (404, error_body("Unknown route")),
+    };
+    write_response(&mut stream, status, &body)
+}
+
+/// Compare the shared secret in constant time (for equal lengths), so response
+/// timing doesn't reveal how many leading bytes of a guess were right.
+fn token_matches(provided: &str, token: &str) -> bool {
+    if provided.len() != token.len() {
+        return false;
+    }
+    let diff = provided
+        .bytes()
+        .zip(token.bytes())
+        .fold(0u8, |acc, (a, b)| acc | (a ^ b));
+    std::hint::black_box(diff) == 0
+}
+
+/// Route a request path to the matching command. Returns `None` for unknown routes.
+fn dispatch(app: &AppHandle, path: &str) -> Option<crate::error::Result<()>> {
+    let path = path.trim_end_matches('/');
+    let result = match path {
+        "/forwarding/start" => {
+            crate::commands::start_forwarding(app.clone(), app.state::<AppState>())
+        }
+        "/forwarding/stop" => {
+            crate::commands::stop_forwarding(app.clone(), app.state::<AppState>())
+        }
+        "/reset" => crate::commands::reset_all(app.clone(), app.state::<AppState>()),
+        _ => {
+            let profile_id = path
+                .strip_prefix("/profiles/")
+                .and_then(|rest| rest.strip_suffix("/activate"))
+                .filter(|id| !id.is_empty() && !id.contains('/'))?;
+            crate::commands::activate_profile(
+                app.clone(),
+                app.state::<AppState>(),
+                profile_id.to_string(),
+            )
+            .map(|assignments| notify_profile_activated(app, profile_id, assignments))
+        }
+    };
+    Some(result)
+}
+
+/// The frontend only learns about activations it didn't trigger itself via this event.
+fn notify_profile_activated(app: &AppHandle, profile_id: &str, assignments: Vec<SlotAssignment>) {
+    let routing_mode = app.state::<AppState>().lock_inner().active_routing_mode();
+    let _ = app.emit(
+        "profile-activated",
+        serde_json::json!({
+            "profile_id": profile_id,
+            "assignments": assignments,
+            "routing_mode": routing_mode,
+        }),
+    );
+}
+
+/// Read up to the end of the HTTP headers. Returns `None` if the head is oversized or not UTF-8.
+fn read_request_head(stream: &mut TcpStream) -> std::io::Result<Option<String>> {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 1024];
+    loop {
+        let n = stream.read(&mut chunk)?;
+        if n == 0 {
+            break;
+        }
+        buf.extend_from_slice(&chunk[..n]);
+        if buf.windows(4).any(|w| w == b"\r\n\r\n") {
+            break;
+        }
+        if buf.len() > MAX_REQUEST_BYTES {
+            return Ok(None);
+        }
+    }
+    Ok(String::from_utf8(buf).ok())
+}
+
+fn write_response(stream: &mut TcpStream, status: u16, body: &str) -> std::io::Result<()> {
+    let reason = match status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        409 => "Conflict",
+        _ => "Error",
+    };
+    let response = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        reason,
+        body.len(),
+        body
+    );
+    stream.write_all(response.as_bytes())?;
+    stream.flush()
+}
+
+fn error_body(message: &str) -> String {
+    serde_json::json!({ "ok": false, "error": message }).to_string()
+}
@@ -1,3 +1,6 @@
+use crate::config::RoutingMode;
+use crate::mapping::InputMapping;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
@@ -8,7 +11,7 @@ pub enum DeviceType {
     Unknown,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct PhysicalDevice {
     /// Unique ID for this device within PadSwitch (stable across sessions)
     pub id: String,
@@ -28,6 +31,10 @@ pub struct PhysicalDevice {
     pub product_id: u16,
     /// Which XInput slot (0-3) this device currently occupies, if known
     pub xinput_slot: Option<u32>,
+    /// XInput-capable, but all four XInput slots were taken by other devices when it
+    /// was enumerated, so it has no `xinput_slot` (as opposed to a DirectInput device).
+    #[serde(default)]
+    pub xinput_overflow: bool,
 }
 
 impl PhysicalDevice {
@@ -42,6 +49,7 @@ impl PhysicalDevice {
             vendor_id: 0,
             product_id: 0,
             xinput_slot: None,
+            xinput_overflow: false,
         }
     }
 
@@ -58,19 +66,32 @@ impl PhysicalDevice {
             vendor_id: 0,
             product_id: 0,
             xinput_slot: Some(slot),
+            xinput_overflow: false,
         }
     }
 }
 
 /// Represents the user's desired mapping: physical device → virtual XInput slot
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
 pub struct SlotAssignment {
     /// ID of the physical device
     pub device_id: String,
-    /// Target XInput slot (0-3)
+    /// Target XInput slot (0-3). In Force mode, assignments sharing a slot are
+    /// merged into one virtual controller (buttons OR'd, axes from the last mover).
     pub slot: u8,
     /// Whether this assignment is enabled
     pub enabled: bool,
+    /// Input remapping applied while forwarding (Force mode)
+    #[serde(default)]
+    pub mapping: InputMapping,
+    /// Forward in place: the target is the XInput slot the device already occupies
+    /// (`slot` is only used if it has none), so the virtual controller replaces it 1:1.
+    #[serde(default)]
+    pub passthrough: bool,
+    /// Route this device with a different mode than the profile's. A profile that
+    /// ends up with both modes runs them side by side (see `InputLoop::start`).
+    #[serde(default)]
+    pub routing_mode: Option<RoutingMode>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -79,6 +100,9 @@ pub struct DriverStatus {
     pub vigembus_installed: bool,
     pub hidhide_version: Option<String>,
     pub vigembus_version: Option<String>,
+    /// XInput DLL actually loaded (Windows), e.g. "xinput1_4.dll".
+    #[serde(default)]
+    pub xinput_version: Option<String>,
 }
 
 impl Default for DriverStatus {
@@ -88,17 +112,74 @@ impl Default for DriverStatus {
             vigembus_installed: false,
             hidhide_version: None,
             vigembus_version: None,
+            xinput_version: None,
         }
     }
 }
 
-/// XInput gamepad state for forwarding
-#[derive(Debug, Clone, Default)]
+/// Buttons and axes a physical controller actually has, for the mapping editor.
+///
+/// Names use the XInput vocabulary (`mapping::buttons::NAMES`, `mapping::XINPUT_AXES`)
+/// where the platform can tell; generic HID controls are reported as "Button N" and
+/// by HID usage name (e.g. "Z", "Rz", "Hat").
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DeviceCapabilities {
+    pub buttons: Vec<String>,
+    pub axes: Vec<String>,
+}
+
+impl DeviceCapabilities {
+    /// The fixed layout every XInput gamepad exposes (Guide excluded; it's undocumented).
+    pub fn xinput() -> Self {
+        use crate::mapping::{buttons, XINPUT_AXES};
+        Self {
+            buttons: buttons::NAMES
+                .iter()
+                .filter(|(bit, _)| *bit != buttons::GUIDE)
+                .map(|(_, name)| name.to_string())
+                .collect(),
+            axes: XINPUT_AXES.iter().map(|a| a.to_string()).collect(),
+        }
+    }
+}
+
+/// How a HidHide candidate path relates to the device it was listed for.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum HideCandidateKind {
+    /// The device's own instance path (what PadSwitch hides by default).
+    Instance,
+    /// A child devnode's instance path (e.g. the HID collections of a composite pad).
+    Child,
+    /// A `\\?\HID#...` interface path of the device or one of its children.
+    Interface,
+    /// An ancestor devnode (USB composite parent, Bluetooth container, ...).
+    Parent,
+}
+
+/// A path that could be put on the HidHide blacklist for a device.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HideCandidate {
+    pub path: String,
+    pub kind: HideCandidateKind,
+    /// Whether the path is currently on the blacklist.
+    pub blacklisted: bool,
+}
+
+/// XInput gamepad state for forwarding. Sent as-is in events and command results;
+/// the field names are part of the frontend contract (`GamepadState` in
+/// `types/controller.ts`), so rename them only together with it.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
 pub struct GamepadState {
+    /// XInput button bits (see `mapping::buttons`; `button_states` decodes them).
     pub buttons: u16,
+    /// 0-255.
     pub left_trigger: u8,
+    /// 0-255.
     pub right_trigger: u8,
+    /// -32768-32767, positive right.
     pub thumb_lx: i16,
+    /// -32768-32767, positive up.
     pub thumb_ly: i16,
     pub thumb_rx: i16,
     pub thumb_ry: i16,
@@ -0,0 +1,154 @@
+use crate::device::PhysicalDevice;
+use crate::error::Result;
+use crate::state::AppState;
+use serde::Serialize;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+/// Quiet period after the last device-mutating operation before re-enumerating.
+/// Long enough to cover a Minimal-mode reorder of a few devices.
+const DEBOUNCE: Duration = Duration::from_millis(750);
+
+/// Schedule a debounced background re-enumeration.
+///
+/// Each call bumps the refresh generation; a refresh only runs if no newer call
+/// (and no explicit enumeration via `get_connected_devices`) happened during the
+/// quiet period, so bursts of toggles collapse into a single `devices-updated` event.
+pub fn schedule(app: &AppHandle) {
+    let state = app.state::<AppState>();
+    let generation = state.refresh_generation().fetch_add(1, Ordering::SeqCst) + 1;
+    let app = app.clone();
+
+    let spawned = std::thread::Builder::new()
+        .name("padswitch-device-refresh".into())
+        .spawn(move || {
+            std::thread::sleep(DEBOUNCE);
+            let state = app.state::<AppState>();
+            if state.refresh_generation().load(Ordering::SeqCst) != generation {
+                return; // Superseded by a newer request or an explicit enumeration
+            }
+            refresh_now(&app);
+        });
+    if let Err(e) = spawned {
+        log::warn!("Failed to spawn device refresh thread: {}", e);
+    }
+}
+
+/// Cancel any pending debounced refresh (used when devices were just enumerated explicitly).
+pub fn cancel_pending(state: &AppState) {
+    state.refresh_generation().fetch_add(1, Ordering::SeqCst);
+}
+
+/// What a re-enumeration changed, by device id, so the UI doesn't diff the list itself.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DeviceDiff {
+    pub added: Vec<PhysicalDevice>,
+    pub removed: Vec<PhysicalDevice>,
+    /// Devices in both lists whose fields differ (new values), e.g. hidden ones
+    /// that vanished, kept with `connected: false`.
+    pub changed: Vec<PhysicalDevice>,
+}
+
+impl DeviceDiff {
+    pub fn between(previous: &[PhysicalDevice], devices: &[PhysicalDevice]) -> Self {
+        let mut diff = Self::default();
+        for dev in devices {
+            match previous.iter().find(|p| p.id == dev.id) {
+                None => diff.added.push(dev.clone()),
+                Some(old) if old != dev => diff.changed.push(dev.clone()),
+                Some(_) => {}
+            }
+        }
+        diff.removed = previous
+            .iter()
+            .filter(|p| !devices.iter().any(|d| d.id == p.id))
+            .cloned()
+            .collect();
+        diff
+    }
+}
+
+/// Emit `devices-updated` with the full list and what changed since `previous`.
+pub fn emit_updated(app: &AppHandle, previous: &[PhysicalDevice], devices: &[PhysicalDevice]) {
+    let diff = DeviceDiff::between(previous, devices);
+    let _ = app.emit(
+        "devices-updated",
+        serde_json::json!({ "devices": devices, "diff": diff }),
+    );
+}
+
+fn refresh_now(app: &AppHandle) {
+    if let Err(e) = refresh(app) {
+        log::warn!("Background device refresh failed: {}", e);
+    }
+}
+
+/// Re-enumerate immediately, merge into `Inner::devices` and emit `devices-updated`.
+/// Returns the merged list.
+pub fn refresh(app: &AppHandle) -> Result<Vec<PhysicalDevice>> {
+    let state = app.state::<AppState>();
+    let manager = state.manager().clone();
+    let fresh = manager.enumerate_devices()?;
+
+    let (previous, devices) = {
+        let mut inner = state.lock_inner();
+        let merged = merge_devices(&inner.devices, fresh);
+        let previous = std::mem::replace(&mut inner.devices, merged);
+        (previous, inner.devices.clone())
+    };
+
+    emit_updated(app, &previous, &devices);
+    Ok(devices)
+}
+
+/// Merge a fresh enumeration with the cached list.
+///
+/// Enumeration can't observe our own hide/disable state, so `hidden` and a
+/// confirmed `xinput_slot` are carried over from the cache. Cached devices that
+/// vanished are dropped (unplugged), unless PadSwitch still hides or disables
+/// them: those are kept with `connected: false` so the UI can still restore them.
+fn merge_devices(cached: &[PhysicalDevice], fresh: Vec<PhysicalDevice>) -> Vec<PhysicalDevice> {
+    let mut merged: Vec<PhysicalDevice> = fresh
+        .into_iter()
+        .map(|mut dev| {
+            if let Some(old) = cached.iter().find(|c| c.id == dev.id) {
+                dev.hidden = old.hidden;
+                if dev.xinput_slot.is_none() {
+                    dev.xinput_slot = old.xinput_slot;
+                }
+            }
+            dev
+        })
+        .collect();
+
+    for old in cached.iter().filter(|old| old.hidden) {
+        if !merged.iter().any(|d| d.id == old.id) {
+            let mut gone = old.clone();
+            gone.connected = false;
+            merged.push(gone);
+        }
+    }
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unplugged_devices_are_dropped_unless_hidden() {
+        let mut hidden = PhysicalDevice::from_xinput_slot(1);
+        hidden.hidden = true;
+        let cached = vec![PhysicalDevice::from_xinput_slot(0), hidden];
+
+        let merged = merge_devices(&cached, vec![PhysicalDevice::from_xinput_slot(2)]);
+        let ids: Vec<&str> = merged.iter().map(|d| d.id.as_str()).collect();
+        assert_eq!(ids, ["xinput-2", "xinput-1"]);
+        assert!(!merged[1].connected && merged[1].hidden);
+
+        let diff = DeviceDiff::between(&cached, &merged);
+        assert_eq!(diff.removed.len(), 1);
+        assert_eq!(diff.removed[0].id, "xinput-0");
+    }
+}
@@ -0,0 +1,197 @@
+use crate::device::{DeviceType, DriverStatus, PhysicalDevice};
+use crate::error::Result;
+use crate::platform::AppInfo;
+use crate::state::{AppState, BackgroundError, EffectiveRoutingMode};
+use serde::Serialize;
+
+/// One enumerated device, as it appears in the slot table.
+#[derive(Debug, Clone, Serialize)]
+pub struct DeviceSlotRow {
+    pub name: String,
+    pub vendor_id: u16,
+    pub product_id: u16,
+    pub instance_path: String,
+    pub device_type: DeviceType,
+    pub is_xinput: bool,
+    pub xinput_slot: Option<u32>,
+    /// XInput-capable but left without a slot (all four were taken).
+    pub xinput_overflow: bool,
+}
+
+/// Every enumerated device and its XInput slot, as rows and as a plain-text table
+/// to paste into a support thread.
+#[derive(Debug, Clone, Serialize)]
+pub struct DeviceSlotTable {
+    pub rows: Vec<DeviceSlotRow>,
+    pub text: String,
+}
+
+/// Build the slot table, slotted devices first in slot order.
+pub fn device_slot_table(devices: &[PhysicalDevice]) -> DeviceSlotTable {
+    let mut rows: Vec<DeviceSlotRow> = devices
+        .iter()
+        .map(|d| DeviceSlotRow {
+            name: d.name.clone(),
+            vendor_id: d.vendor_id,
+            product_id: d.product_id,
+            instance_path: d.instance_path.clone(),
+            device_type: d.device_type.clone(),
+            is_xinput: d.device_type == DeviceType::XInput,
+            xinput_slot: d.xinput_slot,
+            xinput_overflow: d.xinput_overflow,
+        })
+        .collect();
+    rows.sort_by(|a, b| {
+        let slot = |r: &DeviceSlotRow| r.xinput_slot.unwrap_or(u32::MAX);
+        slot(a).cmp(&slot(b)).then_with(|| a.name.cmp(&b.name))
+    });
+    let text = format_table(&rows);
+    DeviceSlotTable { rows, text }
+}
+
+fn format_table(rows: &[DeviceSlotRow]) -> String {
+    let header = ["Slot", "Name", "VID:PID", "Type", "Instance path"];
+    let cells: Vec<[String; 5]> = rows
+        .iter()
+        .map(|r| {
+            let slot = match (r.xinput_slot, r.xinput_overflow) {
+                (Some(slot), _) => slot.to_string(),
+                (None, true) => "full".to_string(),
+                (None, false) => "-".to_string(),
+            };
+            [
+                slot,
+                r.name.clone(),
+                format!("{:04X}:{:04X}", r.vendor_id, r.product_id),
+                format!("{:?}", r.device_type),
+                r.instance_path.clone(),
+            ]
+        })
+        .collect();
+
+    let mut widths = header.map(str::len);
+    for row in &cells {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    let line = |row: &[String]| {
+        row.iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect::<Vec<_>>()
+            .join(" | ")
+            .trim_end()
+            .to_string()
+    };
+
+    let mut out = vec![line(&header.map(String::from))];
+    out.push(widths.map(|w| "-".repeat(w)).join("-+-"));
+    out.extend(cells.iter().map(|row| line(row.as_slice())));
+    if rows.is_empty() {
+        out.push("(no devices found)".to_string());
+    }
+    out.join("\n")
+}
+
+/// Everything a bug report needs about this machine and session.
+#[derive(Debug, Clone, Serialize)]
+pub struct Diagnostics {
+    pub app: AppInfo,
+    pub backend: String,
+    pub driver_status: DriverStatus,
+    pub routing: EffectiveRoutingMode,
+    pub forwarding_active: bool,
+    pub devices: DeviceSlotTable,
+    pub last_background_error: Option<BackgroundError>,
+}
+
+/// Assemble the diagnostics bundle. Enumerates devices afresh but doesn't replace
+/// the cached list, so collecting diagnostics never changes what the UI shows.
+pub fn collect(state: &AppState, app_version: String) -> Result<Diagnostics> {
+    let manager = state.manager().clone();
+    let devices = manager.enumerate_devices()?;
+    let driver_status = manager.check_drivers()?;
+    let inner = state.lock_inner();
+    let routing = inner.effective_routing_mode();
+    let forwarding_active = inner.forwarding_active();
+    drop(inner);
+    Ok(Diagnostics {
+        app: crate::platform::app_info(app_version),
+        backend: manager.backend_description(),
+        driver_status,
+        routing,
+        forwarding_active,
+        devices: device_slot_table(&devices),
+        last_background_error: state.last_background_error(),
+    })
+}
+
+/// Longest text `copy_diagnostics_to_clipboard` produces: fits in one chat message
+/// (Discord caps them at 2000 characters) with room for a code fence.
+pub const CLIPBOARD_MAX_CHARS: usize = 1900;
+
+impl Diagnostics {
+    /// The bundle as plain text of at most `max_chars` characters. Summary lines come
+    /// first; device rows that don't fit are dropped and counted at the end.
+    pub fn to_text(&self, max_chars: usize) -> String {
+        let app = &self.app;
+        let drivers = &self.driver_status;
+        let version = |v: &Option<String>| v.clone().unwrap_or_else(|| "missing".into());
+        let mut lines = vec![
+            format!(
+                "PadSwitch {} on {} {} ({}){}",
+                app.app_version,
+                app.os_name,
+                app.os_version,
+                app.arch,
+                if app.elevated { ", elevated" } else { "" }
+            ),
+            format!("Backend: {}", self.backend),
+            format!(
+                "Drivers: HidHide {}, ViGEmBus {}, XInput {}",
+                version(&drivers.hidhide_version),
+                version(&drivers.vigembus_version),
+                drivers.xinput_version.as_deref().unwrap_or("-")
+            ),
+            format!(
+                "Routing: {:?} ({}), forwarding {}",
+                self.routing.mode,
+                self.routing.reason,
+                if self.forwarding_active {
+                    "active"
+                } else {
+                    "idle"
+                }
+            ),
+        ];
+        if let Some(error) = &self.last_background_error {
+            lines.push(format!(
+                "Last error ({}, {}): {}",
+                error.source,
+                error.at.to_rfc3339(),
+                error.message
+            ));
+        }
+        lines.push(String::new());
+
+        let mut out: String = lines.join("\n");
+        let table: Vec<&str> = self.devices.text.lines().collect();
+        for (i, line) in table.iter().enumerate() {
+            let note = format!("\n({} more rows)", table.len() - i);
+            let room = if i + 1 < table.len() {
+                note.chars().count()
+            } else {
+                0
+            };
+            if out.chars().count() + 1 + line.chars().count() + room > max_chars {
+                out.push_str(&note);
+                break;
+            }
+            out.push('\n');
+            out.push_str(line);
+        }
+
+        out.chars().take(max_chars).collect()
+    }
+}
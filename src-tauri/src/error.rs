@@ -14,12 +14,25 @@ pub enum PadSwitchError {
     #[error("ViGEmBus error: {0}")]
     ViGEm(String),
 
+    #[error("XInput unavailable: {0}")]
+    XInputUnavailable(String),
+
     #[error("Forwarding error: {0}")]
     Forwarding(String),
 
     #[error("Config error: {0}")]
     Config(String),
 
+    /// The operation needs forwarding to be in a different state (e.g. stop while stopped).
+    #[error("Invalid state: {0}")]
+    InvalidState(String),
+
+    #[error("Administrator privileges required: {0}")]
+    ElevationRequired(String),
+
+    #[error("Elevation cancelled: {0}")]
+    ElevationCancelled(String),
+
     #[error("Platform error: {0}")]
     Platform(String),
 
@@ -0,0 +1,322 @@
+/// Raw HID access for diagnostics (Windows-only).
+///
+/// Resolves a device instance path to its HID interface path via SetupAPI and
+/// reads input reports with `HidD_GetInputReport`. Used by the "read raw report"
+/// troubleshooting command to inspect controllers that map incorrectly.
+///
+/// Reads are synchronous control transfers, so they are bounded by the HID
+/// class driver's own timeout and can't hang waiting for input.
+///
+/// Also lists a device's buttons and axes from its report descriptor for the
+/// mapping editor (`device_capabilities`).
+
+#[cfg(target_os = "windows")]
+pub mod imp {
+    use crate::device::DeviceCapabilities;
+    use crate::error::{PadSwitchError, Result};
+    use windows::core::{GUID, PCWSTR};
+    use windows::Win32::Devices::DeviceAndDriverInstallation::{
+        SetupDiDestroyDeviceInfoList, SetupDiEnumDeviceInterfaces, SetupDiGetClassDevsW,
+        SetupDiGetDeviceInstanceIdW, SetupDiGetDeviceInterfaceDetailW, DIGCF_DEVICEINTERFACE,
+        DIGCF_PRESENT, HDEVINFO, SP_DEVICE_INTERFACE_DATA, SP_DEVICE_INTERFACE_DETAIL_DATA_W,
+        SP_DEVINFO_DATA,
+    };
+    use windows::Win32::Devices::HumanInterfaceDevice::{
+        HidD_FreePreparsedData, HidD_GetHidGuid, HidD_GetInputReport, HidD_GetPreparsedData,
+        HidP_GetButtonCaps, HidP_GetCaps, HidP_GetValueCaps, HidP_Input, HIDP_BUTTON_CAPS,
+        HIDP_CAPS, HIDP_VALUE_CAPS, PHIDP_PREPARSED_DATA,
+    };
+    use windows::Win32::Foundation::{CloseHandle, HANDLE};
+    use windows::Win32::Storage::FileSystem::{
+        CreateFileW, FILE_ATTRIBUTE_NORMAL, FILE_GENERIC_READ, FILE_GENERIC_WRITE, FILE_SHARE_READ,
+        FILE_SHARE_WRITE, OPEN_EXISTING,
+    };
+
+    /// An open handle to a HID device interface. Closed on drop.
+    pub struct HidDevice {
+        handle: HANDLE,
+        input_report_len: usize,
+    }
+
+    impl Drop for HidDevice {
+        fn drop(&mut self) {
+            unsafe {
+                let _ = CloseHandle(self.handle);
+            }
+        }
+    }
+
+    impl HidDevice {
+        /// Open the HID interface belonging to a device instance path.
+        pub fn open(instance_path: &str) -> Result<Self> {
+            let interface_path = find_interface_path(instance_path)?;
+            let wide: Vec<u16> = interface_path
+                .encode_utf16()
+                .chain(std::iter::once(0))
+                .collect();
+
+            let handle = unsafe {
+                CreateFileW(
+                    PCWSTR(wide.as_ptr()),
+                    (FILE_GENERIC_READ | FILE_GENERIC_WRITE).0,
+                    FILE_SHARE_READ | FILE_SHARE_WRITE,
+                    None,
+                    OPEN_EXISTING,
+                    FILE_ATTRIBUTE_NORMAL,
+                    None,
+                )
+                .map_err(|e| {
+                    PadSwitchError::Platform(format!(
+                        "Failed to open HID device {}: {}",
+                        instance_path, e
+                    ))
+                })?
+            };
+
+            let input_report_len = unsafe { input_report_length(handle) };
+            let Some(input_report_len) = input_report_len else {
+                unsafe {
+                    let _ = CloseHandle(handle);
+                }
+                return Err(PadSwitchError::Platform(format!(
+                    "Failed to read HID capabilities for {}",
+                    instance_path
+                )));
+            };
+
+            Ok(Self {
+                handle,
+                input_report_len,
+            })
+        }
+
+        /// Read the current input report (report ID 0). The first byte is the report ID.
+        pub fn read_input_report(&self) -> Result<Vec<u8>> {
+            let mut buffer = vec![0u8; self.input_report_len];
+            let ok = unsafe {
+                HidD_GetInputReport(
+                    self.handle,
+                    buffer.as_mut_ptr() as *mut _,
+                    buffer.len() as u32,
+                )
+            };
+            if !ok.as_bool() {
+                return Err(PadSwitchError::Platform(
+                    "HidD_GetInputReport failed (device may not support polled reports)".into(),
+                ));
+            }
+            Ok(buffer)
+        }
+
+        /// List input buttons (Button usage page) and Generic Desktop axes.
+        pub fn capabilities(&self) -> Result<DeviceCapabilities> {
+            unsafe {
+                let mut preparsed = PHIDP_PREPARSED_DATA::default();
+                if !HidD_GetPreparsedData(self.handle, &mut preparsed).as_bool() {
+                    return Err(PadSwitchError::Platform(
+                        "Failed to read HID preparsed data".into(),
+                    ));
+                }
+                let caps = input_capabilities(preparsed);
+                let _ = HidD_FreePreparsedData(preparsed);
+                caps
+            }
+        }
+    }
+
+    const USAGE_PAGE_GENERIC_DESKTOP: u16 = 0x01;
+    const USAGE_PAGE_BUTTON: u16 = 0x09;
+
+    /// HID Generic Desktop usage names for the axes gamepads use.
+    fn axis_name(usage: u16) -> Option<&'static str> {
+        match usage {
+            0x30 => Some("X"),
+            0x31 => Some("Y"),
+            0x32 => Some("Z"),
+            0x33 => Some("Rx"),
+            0x34 => Some("Ry"),
+            0x35 => Some("Rz"),
+            0x36 => Some("Slider"),
+            0x37 => Some("Dial"),
+            0x39 => Some("Hat"),
+            _ => None,
+        }
+    }
+
+    unsafe fn input_capabilities(preparsed: PHIDP_PREPARSED_DATA) -> Result<DeviceCapabilities> {
+        let mut caps = HIDP_CAPS::default();
+        if HidP_GetCaps(preparsed, &mut caps).is_err() {
+            return Err(PadSwitchError::Platform(
+                "Failed to read HID capabilities".into(),
+            ));
+        }
+
+        let mut buttons: Vec<u16> = Vec::new();
+        let mut len = caps.NumberInputButtonCaps;
+        let mut button_caps = vec![HIDP_BUTTON_CAPS::default(); len as usize];
+        if len > 0
+            && HidP_GetButtonCaps(HidP_Input, button_caps.as_mut_ptr(), &mut len, preparsed).is_ok()
+        {
+            for bc in button_caps.iter().take(len as usize) {
+                if bc.UsagePage != USAGE_PAGE_BUTTON {
+                    continue;
+                }
+                let (min, max) = if bc.IsRange.as_bool() {
+                    (bc.Anonymous.Range.UsageMin, bc.Anonymous.Range.UsageMax)
+                } else {
+                    (bc.Anonymous.NotRange.Usage, bc.Anonymous.NotRange.Usage)
+                };
+                buttons.extend(min..=max);
+            }
+        }
+        buttons.sort_unstable();
+        buttons.dedup();
+
+        let mut axes: Vec<&str> = Vec::new();
+        let mut len = caps.NumberInputValueCaps;
+        let mut value_caps = vec![HIDP_VALUE_CAPS::default(); len as usize];
+        if len > 0
+            && HidP_GetValueCaps(HidP_Input, value_caps.as_mut_ptr(), &mut len, preparsed).is_ok()
+        {
+            for vc in value_caps.iter().take(len as usize) {
+                if vc.UsagePage != USAGE_PAGE_GENERIC_DESKTOP {
+                    continue;
+                }
+                let (min, max) = if vc.IsRange.as_bool() {
+                    (vc.Anonymous.Range.UsageMin, vc.Anonymous.Range.UsageMax)
+                } else {
+                    (vc.Anonymous.NotRange.Usage, vc.Anonymous.NotRange.Usage)
+                };
+                for name in (min..=max).filter_map(axis_name) {
+                    if !axes.contains(&name) {
+                        axes.push(name);
+                    }
+                }
+            }
+        }
+
+        Ok(DeviceCapabilities {
+            buttons: buttons.iter().map(|b| format!("Button {}", b)).collect(),
+            axes: axes.into_iter().map(String::from).collect(),
+        })
+    }
+
+    /// Query the input report length from the device's preparsed data.
+    unsafe fn input_report_length(handle: HANDLE) -> Option<usize> {
+        let mut preparsed = PHIDP_PREPARSED_DATA::default();
+        if !HidD_GetPreparsedData(handle, &mut preparsed).as_bool() {
+            return None;
+        }
+        let mut caps = HIDP_CAPS::default();
+        let status = HidP_GetCaps(preparsed, &mut caps);
+        let _ = HidD_FreePreparsedData(preparsed);
+        if status.is_err() || caps.InputReportByteLength == 0 {
+            return None;
+        }
+        Some(caps.InputReportByteLength as usize)
+    }
+
+    /// Find the HID device interface path (`\\?\hid#...`) for a device instance path.
+    fn find_interface_path(instance_path: &str) -> Result<String> {
+        let hid_guid: GUID = unsafe { HidD_GetHidGuid() };
+        unsafe {
+            let dev_info = SetupDiGetClassDevsW(
+                Some(&hid_guid as *const GUID),
+                PCWSTR::null(),
+                None,
+                DIGCF_PRESENT | DIGCF_DEVICEINTERFACE,
+            )
+            .map_err(|e| PadSwitchError::Platform(format!("SetupDiGetClassDevsW failed: {}", e)))?;
+
+            let result = find_interface_in_set(dev_info, &hid_guid, instance_path);
+            let _ = SetupDiDestroyDeviceInfoList(dev_info);
+            result
+        }
+    }
+
+    unsafe fn find_interface_in_set(
+        dev_info: HDEVINFO,
+        hid_guid: &GUID,
+        instance_path: &str,
+    ) -> Result<String> {
+        let target = crate::platform::normalize_instance_path(instance_path);
+        let mut index: u32 = 0;
+
+        loop {
+            let mut iface = SP_DEVICE_INTERFACE_DATA {
+                cbSize: std::mem::size_of::<SP_DEVICE_INTERFACE_DATA>() as u32,
+                ..Default::default()
+            };
+            if SetupDiEnumDeviceInterfaces(dev_info, None, hid_guid, index, &mut iface).is_err() {
+                break;
+            }
+            index += 1;
+
+            // First call: get required detail size
+            let mut required: u32 = 0;
+            let _ = SetupDiGetDeviceInterfaceDetailW(
+                dev_info,
+                &iface,
+                None,
+                0,
+                Some(&mut required),
+                None,
+            );
+            if required == 0 {
+                continue;
+            }
+
+            // Second call: detail data (variable-length struct) + owning devinfo
+            let mut buffer = vec![0u8; required as usize];
+            let detail = buffer.as_mut_ptr() as *mut SP_DEVICE_INTERFACE_DETAIL_DATA_W;
+            (*detail).cbSize = std::mem::size_of::<SP_DEVICE_INTERFACE_DETAIL_DATA_W>() as u32;
+            let mut dev_data = SP_DEVINFO_DATA {
+                cbSize: std::mem::size_of::<SP_DEVINFO_DATA>() as u32,
+                ..Default::default()
+            };
+            if SetupDiGetDeviceInterfaceDetailW(
+                dev_info,
+                &iface,
+                Some(detail),
+                required,
+                None,
+                Some(&mut dev_data),
+            )
+            .is_err()
+            {
+                continue;
+            }
+
+            let mut id_buf = vec![0u16; 512];
+            let mut id_len: u32 = 0;
+            if SetupDiGetDeviceInstanceIdW(
+                dev_info,
+                &dev_data,
+                Some(&mut id_buf),
+                Some(&mut id_len),
+            )
+            .is_err()
+            {
+                continue;
+            }
+            let device_id = String::from_utf16_lossy(&id_buf[..id_len.saturating_sub(1) as usize]);
+            if crate::platform::normalize_instance_path(&device_id) != target {
+                continue;
+            }
+
+            // DevicePath is a null-terminated wide string starting at the field offset
+            let path_ptr = std::ptr::addr_of!((*detail).DevicePath) as *const u16;
+            let max_chars = (required as usize
+                - std::mem::offset_of!(SP_DEVICE_INTERFACE_DETAIL_DATA_W, DevicePath))
+                / 2;
+            let path_slice = std::slice::from_raw_parts(path_ptr, max_chars);
+            let end = path_slice.iter().position(|&c| c == 0).unwrap_or(max_chars);
+            return Ok(String::from_utf16_lossy(&path_slice[..end]));
+        }
+
+        Err(PadSwitchError::DeviceNotFound(format!(
+            "No HID interface found for {}",
+            instance_path
+        )))
+    }
+}
@@ -5,7 +5,7 @@
 /// apps to still access them.
 ///
 /// Key operations:
-/// - Get/set blacklist (device instance paths to hide)
+/// - Get/set blacklist (device instance and interface paths to hide)
 /// - Get/set whitelist (application paths allowed to see hidden devices)
 /// - Enable/disable hiding globally
 ///
@@ -15,7 +15,9 @@
 
 #[cfg(target_os = "windows")]
 pub mod imp {
+    use super::{decode_multi_string, encode_multi_string};
     use crate::error::{PadSwitchError, Result};
+    use crate::platform::{normalize_instance_path, same_instance_path};
     use windows::core::PCWSTR;
     use windows::Win32::Foundation::{CloseHandle, HANDLE};
     use windows::Win32::Storage::FileSystem::{
@@ -30,7 +32,6 @@ pub mod imp {
     const IOCTL_SET_WHITELIST: u32 = 0x80016004;
     const IOCTL_GET_BLACKLIST: u32 = 0x80016008;
     const IOCTL_SET_BLACKLIST: u32 = 0x8001600C;
-    #[allow(dead_code)]
     const IOCTL_GET_ACTIVE: u32 = 0x80016010;
     const IOCTL_SET_ACTIVE: u32 = 0x80016014;
 
@@ -74,23 +75,33 @@ pub mod imp {
             Self::open().is_ok()
         }
 
-        /// Add a device instance path to the blacklist (devices to hide).
-        pub fn add_to_blacklist(&self, instance_path: &str) -> Result<()> {
+        /// Add device instance and/or interface paths to the blacklist (devices to hide).
+        pub fn add_to_blacklist(&self, paths: &[String]) -> Result<()> {
             let mut list = self.ioctl_get_list(IOCTL_GET_BLACKLIST)?;
-            let normalized = instance_path.to_uppercase();
-            if !list.iter().any(|s| s.to_uppercase() == normalized) {
-                list.push(instance_path.to_string());
+            let before = list.len();
+            for path in paths {
+                if !list.iter().any(|s| same_instance_path(s, path)) {
+                    list.push(path.clone());
+                }
+            }
+            if list.len() != before {
                 self.ioctl_set_list(IOCTL_SET_BLACKLIST, &list)?;
             }
             Ok(())
         }
 
-        /// Remove a device instance path from the blacklist.
-        pub fn remove_from_blacklist(&self, instance_path: &str) -> Result<()> {
+        /// Current blacklist entries.
+        pub fn blacklist(&self) -> Result<Vec<String>> {
+            self.ioctl_get_list(IOCTL_GET_BLACKLIST)
+        }
+
+        /// Remove device instance and/or interface paths from the blacklist.
+        pub fn remove_from_blacklist(&self, paths: &[String]) -> Result<()> {
             let mut list = self.ioctl_get_list(IOCTL_GET_BLACKLIST)?;
-            let normalized = instance_path.to_uppercase();
+            let normalized: Vec<String> =
+                paths.iter().map(|p| normalize_instance_path(p)).collect();
             let before = list.len();
-            list.retain(|s| s.to_uppercase() != normalized);
+            list.retain(|s| !normalized.contains(&normalize_instance_path(s)));
             if list.len() != before {
                 self.ioctl_set_list(IOCTL_SET_BLACKLIST, &list)?;
             }
@@ -98,11 +109,25 @@ pub mod imp {
         }
 
         /// Add an application path to the whitelist (apps allowed to see hidden devices).
-        pub fn add_to_whitelist(&self, app_path: &str) -> Result<()> {
+        /// Returns `true` if the entry was added, `false` if it was already present.
+        pub fn add_to_whitelist(&self, app_path: &str) -> Result<bool> {
             let mut list = self.ioctl_get_list(IOCTL_GET_WHITELIST)?;
             let normalized = app_path.to_uppercase();
-            if !list.iter().any(|s| s.to_uppercase() == normalized) {
-                list.push(app_path.to_string());
+            if list.iter().any(|s| s.to_uppercase() == normalized) {
+                return Ok(false);
+            }
+            list.push(app_path.to_string());
+            self.ioctl_set_list(IOCTL_SET_WHITELIST, &list)?;
+            Ok(true)
+        }
+
+        /// Remove an application path from the whitelist.
+        pub fn remove_from_whitelist(&self, app_path: &str) -> Result<()> {
+            let mut list = self.ioctl_get_list(IOCTL_GET_WHITELIST)?;
+            let normalized = app_path.to_uppercase();
+            let before = list.len();
+            list.retain(|s| s.to_uppercase() != normalized);
+            if list.len() != before {
                 self.ioctl_set_list(IOCTL_SET_WHITELIST, &list)?;
             }
             Ok(())
@@ -128,6 +153,26 @@ pub mod imp {
             Ok(())
         }
 
+        /// Whether HidHide is globally active (hiding blacklisted devices).
+        pub fn is_active(&self) -> Result<bool> {
+            let mut value: u8 = 0;
+            let mut bytes_returned: u32 = 0;
+            unsafe {
+                DeviceIoControl(
+                    self.handle,
+                    IOCTL_GET_ACTIVE,
+                    None,
+                    0,
+                    Some(&mut value as *mut u8 as *mut _),
+                    std::mem::size_of::<u8>() as u32,
+                    Some(&mut bytes_returned),
+                    None,
+                )
+                .map_err(|e| PadSwitchError::HidHide(format!("is_active failed: {}", e)))?;
+            }
+            Ok(value != 0)
+        }
+
         /// Get a multi-string list via IOCTL (two-call pattern: get size, then get data).
         fn ioctl_get_list(&self, ioctl_code: u32) -> Result<Vec<String>> {
             let mut bytes_returned: u32 = 0;
@@ -172,7 +217,7 @@ pub mod imp {
 
         /// Set a multi-string list via IOCTL.
         fn ioctl_set_list(&self, ioctl_code: u32, strings: &[String]) -> Result<()> {
-            let buffer = encode_multi_string(strings);
+            let buffer = encode_multi_string(strings)?;
             let mut bytes_returned: u32 = 0;
             unsafe {
                 DeviceIoControl(
@@ -190,45 +235,124 @@ pub mod imp {
             Ok(())
         }
     }
+}
 
-    /// Encode a list of strings as a double-null-terminated UTF-16LE byte buffer.
-    fn encode_multi_string(strings: &[String]) -> Vec<u8> {
-        let mut wide: Vec<u16> = Vec::new();
-        for s in strings {
-            wide.extend(s.encode_utf16());
-            wide.push(0); // null-terminate each string
+/// Encode a list of strings as a double-null-terminated UTF-16LE byte buffer.
+///
+/// Rejects empty strings and strings with interior NULs: either would end the
+/// list early (or split an entry), so HidHide would store a different list.
+#[cfg(any(target_os = "windows", test))]
+pub(crate) fn encode_multi_string(strings: &[String]) -> crate::error::Result<Vec<u8>> {
+    let mut wide: Vec<u16> = Vec::new();
+    for s in strings {
+        if s.is_empty() || s.contains('\0') {
+            return Err(crate::error::PadSwitchError::HidHide(format!(
+                "Refusing to write an empty or NUL-containing entry: {:?}",
+                s
+            )));
         }
-        wide.push(0); // final null for double-null termination
+        wide.extend(s.encode_utf16());
+        wide.push(0); // null-terminate each string
+    }
+    wide.push(0); // final null for double-null termination
 
-        // Convert to bytes (little-endian)
-        wide.iter().flat_map(|&w| w.to_le_bytes()).collect()
+    // Convert to bytes (little-endian)
+    Ok(wide.iter().flat_map(|&w| w.to_le_bytes()).collect())
+}
+
+/// Decode a double-null-terminated UTF-16LE byte buffer into strings.
+/// A final entry missing its terminator is still returned.
+#[cfg(any(target_os = "windows", test))]
+pub(crate) fn decode_multi_string(bytes: &[u8]) -> Vec<String> {
+    if bytes.len() < 2 {
+        return vec![];
     }
 
-    /// Decode a double-null-terminated UTF-16LE byte buffer into strings.
-    fn decode_multi_string(bytes: &[u8]) -> Vec<String> {
-        if bytes.len() < 2 {
-            return vec![];
-        }
+    // Convert bytes to u16 values
+    let wide: Vec<u16> = bytes
+        .chunks_exact(2)
+        .map(|chunk| u16::from_le_bytes([chunk[0], chunk[1]]))
+        .collect();
 
-        // Convert bytes to u16 values
-        let wide: Vec<u16> = bytes
-            .chunks_exact(2)
-            .map(|chunk| u16::from_le_bytes([chunk[0], chunk[1]]))
-            .collect();
-
-        let mut strings = Vec::new();
-        let mut current = Vec::new();
-        for &ch in &wide {
-            if ch == 0 {
-                if current.is_empty() {
-                    break; // double null = end of list
-                }
-                strings.push(String::from_utf16_lossy(&current));
-                current.clear();
-            } else {
-                current.push(ch);
+    let mut strings = Vec::new();
+    let mut current = Vec::new();
+    for &ch in &wide {
+        if ch == 0 {
+            if current.is_empty() {
+                return strings; // double null = end of list
             }
+            strings.push(String::from_utf16_lossy(&current));
+            current.clear();
+        } else {
+            current.push(ch);
         }
-        strings
+    }
+    if !current.is_empty() {
+        strings.push(String::from_utf16_lossy(&current));
+    }
+    strings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(strings: &[&str]) -> Vec<String> {
+        let owned: Vec<String> = strings.iter().map(|s| s.to_string()).collect();
+        decode_multi_string(&encode_multi_string(&owned).unwrap())
+    }
+
+    #[test]
+    fn empty_list_is_a_single_terminator() {
+        assert_eq!(encode_multi_string(&[]).unwrap(), vec![0, 0]);
+        assert!(round_trip(&[]).is_empty());
+        assert!(decode_multi_string(&[]).is_empty());
+    }
+
+    #[test]
+    fn round_trips_single_and_multiple_entries() {
+        let single = [r"HID\VID_045E&PID_028E\6&ABC&0&0000"];
+        assert_eq!(round_trip(&single), single);
+
+        let several = [r"USB\VID_054C&PID_0CE6\1", r"\\?\HID#VID_054C&PID_0CE6#7&1"];
+        assert_eq!(round_trip(&several), several);
+    }
+
+    #[test]
+    fn round_trips_unicode_paths() {
+        let paths = [
+            r"C:\Spiele\Überlauf.exe",
+            r"D:\ゲーム\パッド.exe",
+            "C:\\🎮\\pad.exe",
+        ];
+        assert_eq!(round_trip(&paths), paths);
+    }
+
+    #[test]
+    fn entry_ends_with_one_null_and_list_with_two() {
+        let bytes = encode_multi_string(&["AB".to_string()]).unwrap();
+        assert_eq!(bytes, vec![b'A', 0, b'B', 0, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn decode_stops_at_the_double_null() {
+        // Anything after the terminator (e.g. a reused buffer) is ignored
+        let mut bytes = encode_multi_string(&["A".to_string()]).unwrap();
+        bytes.extend([b'B', 0, 0, 0]);
+        assert_eq!(decode_multi_string(&bytes), ["A"]);
+    }
+
+    #[test]
+    fn decode_keeps_an_unterminated_last_entry() {
+        assert_eq!(decode_multi_string(&[b'A', 0, 0, 0, b'B', 0]), ["A", "B"]);
+        assert_eq!(decode_multi_string(&[b'A', 0, 0, 0]), ["A"]);
+        assert_eq!(decode_multi_string(&[b'A', 0]), ["A"]);
+    }
+
+    #[test]
+    fn rejects_empty_and_nul_containing_entries() {
+        assert!(encode_multi_string(&["".to_string()]).is_err());
+        assert!(encode_multi_string(&["A\0B".to_string()]).is_err());
+        assert!(encode_multi_string(&["ok".to_string(), "bad\0".to_string()]).is_err());
     }
 }
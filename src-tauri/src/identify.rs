@@ -0,0 +1,127 @@
+use crate::platform::PlatformServices;
+use crate::state::AppState;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+/// How long each controller rumbles.
+pub const PULSE: Duration = Duration::from_millis(600);
+
+/// Gap between pulses so players can tell which pad buzzed.
+const PAUSE: Duration = Duration::from_millis(900);
+
+/// Consecutive polls a new button press must stay held before `detect_slot` accepts it.
+const CONFIRM_POLLS: u32 = 3;
+
+/// Poll interval for `detect_slot` (~60Hz).
+const DETECT_POLL: Duration = Duration::from_millis(16);
+
+/// Wait up to `timeout` for a button press and return the XInput slot it came from.
+///
+/// Buttons already held when detection starts are ignored for the whole run, even
+/// if they flicker, and a new press only counts once the same button has stayed
+/// down for `CONFIRM_POLLS` polls in a row — spurious one-frame bits and laggy
+/// state from before the baseline can't pick the wrong slot.
+pub fn detect_slot(manager: &dyn PlatformServices, timeout: Duration) -> Option<u32> {
+    // Snapshot current button state for all 4 slots
+    let mut ignored = [0u16; 4];
+    for slot in 0..4u32 {
+        if let Ok(gs) = manager.read_gamepad_state(&slot.to_string()) {
+            ignored[slot as usize] = gs.buttons;
+        }
+    }
+
+    // Per slot: new bits seen on the previous poll, and how many polls they've held
+    let mut held = [0u16; 4];
+    let mut streak = [0u32; 4];
+    let deadline = std::time::Instant::now() + timeout;
+    while std::time::Instant::now() < deadline {
+        for slot in 0..4u32 {
+            let i = slot as usize;
+            let pressed = match manager.read_gamepad_state(&slot.to_string()) {
+                Ok(gs) => gs.buttons & !ignored[i],
+                Err(_) => 0,
+            };
+            let still_held = pressed & held[i];
+            (held[i], streak[i]) = match (still_held, pressed) {
+                (0, 0) => (0, 0),
+                (0, _) => (pressed, 1),
+                _ => (still_held, streak[i] + 1),
+            };
+            if streak[i] >= CONFIRM_POLLS {
+                log::info!("Detected button press on XInput slot {}", slot);
+                return Some(slot);
+            }
+        }
+        std::thread::sleep(DETECT_POLL);
+    }
+
+    None // Timeout — no input detected
+}
+
+/// Rumble one XInput slot at full strength for `duration`, then stop the motors.
+pub fn pulse(
+    manager: &dyn PlatformServices,
+    slot: u32,
+    duration: Duration,
+) -> crate::error::Result<()> {
+    let path = slot.to_string();
+    manager.set_rumble(&path, u16::MAX, u16::MAX)?;
+    std::thread::sleep(duration);
+    manager.set_rumble(&path, 0, 0)
+}
+
+/// Rumble each connected XInput slot in turn on a background thread.
+///
+/// Emits `identify-step { slot, step, total }` before each pulse and
+/// `identify-done { cancelled }` at the end. Starting a new run or calling
+/// `cancel` bumps the identify generation, which stops the current run
+/// before its next pulse. Unconnected slots are skipped.
+pub fn start_all(app: &AppHandle) {
+    let state = app.state::<AppState>();
+    let generation = state.identify_generation().fetch_add(1, Ordering::SeqCst) + 1;
+    let app = app.clone();
+
+    let spawned = std::thread::Builder::new()
+        .name("padswitch-identify".into())
+        .spawn(move || {
+            let state = app.state::<AppState>();
+            let manager = state.manager().clone();
+            let is_current = || state.identify_generation().load(Ordering::SeqCst) == generation;
+
+            let slots: Vec<u32> = (0..4u32)
+                .filter(|slot| manager.read_gamepad_state(&slot.to_string()).is_ok())
+                .collect();
+
+            let mut cancelled = false;
+            for (step, &slot) in slots.iter().enumerate() {
+                if !is_current() {
+                    cancelled = true;
+                    break;
+                }
+                let _ = app.emit(
+                    "identify-step",
+                    serde_json::json!({ "slot": slot, "step": step, "total": slots.len() }),
+                );
+                if let Err(e) = pulse(&*manager, slot, PULSE) {
+                    log::warn!("Identify: rumble failed on slot {}: {}", slot, e);
+                }
+                if step + 1 < slots.len() {
+                    std::thread::sleep(PAUSE);
+                }
+            }
+
+            let _ = app.emit(
+                "identify-done",
+                serde_json::json!({ "cancelled": cancelled }),
+            );
+        });
+    if let Err(e) = spawned {
+        log::warn!("Failed to spawn identify thread: {}", e);
+    }
+}
+
+/// Stop a running identify-all sequence before its next pulse.
+pub fn cancel(state: &AppState) {
+    state.identify_generation().fetch_add(1, Ordering::SeqCst);
+}
@@ -1,19 +1,389 @@
-use crate::config::RoutingMode;
+use crate::config::{Profile, RoutingMode, VirtualSubtype};
+#[cfg(target_os = "windows")]
+use crate::device::GamepadState;
 use crate::error::Result;
+use crate::mapping::{AxisOverride, InputMapping};
 use crate::platform::PlatformServices;
+use crate::state::AppState;
+use serde::Serialize;
+use std::collections::BTreeMap;
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
+use std::sync::mpsc::{RecvTimeoutError, SyncSender};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+/// How long `InputLoop::start` waits for the loop to confirm it is up. Covers a
+/// Minimal-mode reorder of several devices and ViGEm target plug-in.
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How often a blocked `InputLoop::start` checks for `StartCancel::cancel`.
+const CANCEL_POLL: Duration = Duration::from_millis(50);
+
+/// Message the loop reports when a start is cancelled.
+const START_CANCELLED: &str = "Forwarding start cancelled";
+
+/// ViGEmBus connection attempts at Force-mode start, and the backoff step between them.
+#[cfg(target_os = "windows")]
+const VIGEM_CONNECT_ATTEMPTS: u32 = 3;
+#[cfg(target_os = "windows")]
+const VIGEM_CONNECT_BACKOFF: Duration = Duration::from_millis(200);
 
 /// A slot assignment resolved to real device data for the input loop.
 /// Created by commands.rs from SlotAssignment + device list lookup.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct ResolvedAssignment {
+    /// PadSwitch device ID of the physical device
+    pub device_id: String,
     /// Real device instance path (e.g., "USB\VID_045E&PID_028E\6&ABC")
     pub instance_path: String,
     /// XInput slot this device currently occupies (0-3), if known
     pub xinput_slot: Option<u32>,
     /// Target virtual slot (0-3)
     pub target_slot: u8,
+    /// Input remapping applied before writing to the virtual controller
+    pub mapping: InputMapping,
+    /// Target slot is the device's own XInput slot; see `SlotAssignment::passthrough`.
+    pub passthrough: bool,
+    /// Per-assignment override of the session's routing mode.
+    pub routing_mode: Option<RoutingMode>,
+}
+
+impl ResolvedAssignment {
+    /// The mode this assignment is routed with when the session default is `mode`.
+    pub fn effective_mode(&self, mode: &RoutingMode) -> RoutingMode {
+        self.routing_mode.clone().unwrap_or_else(|| mode.clone())
+    }
+}
+
+/// Distinct routing modes a session with these assignments runs, Minimal first.
+pub fn session_modes(assignments: &[ResolvedAssignment], mode: &RoutingMode) -> Vec<RoutingMode> {
+    [RoutingMode::Minimal, RoutingMode::Force]
+        .into_iter()
+        .filter(|m| assignments.iter().any(|a| a.effective_mode(mode) == *m))
+        .collect()
+}
+
+/// One virtual controller created by the running Force-mode session.
+#[derive(Debug, Clone, Serialize)]
+pub struct VirtualControllerInfo {
+    /// Creation order (0-based); targets are created in target-slot order.
+    pub index: usize,
+    pub target_slot: u8,
+    /// XInput user index (player LED) Windows gave the virtual pad, if known.
+    pub xinput_slot: Option<u32>,
+    /// Physical devices feeding this controller (several when merged).
+    pub device_ids: Vec<String>,
+    /// How rumble a game sends this controller reaches the physical pads.
+    pub rumble: RumblePath,
+}
+
+/// Which path forwards rumble from a virtual controller to its physical pads.
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+pub enum RumblePath {
+    /// ViGEm output notifications, handled on a side thread per target.
+    Notification,
+    /// Registering for notifications failed. ViGEmBus has no call to read a
+    /// target's motor state back, so there is nothing to poll instead.
+    Unavailable,
+    /// Turned off in settings, or not supported by this backend.
+    Off,
+    /// None of the pads feeding this controller has rumble motors.
+    NoMotors,
+}
+
+/// Why a slot assignment won't be forwarded.
+#[derive(Debug, Clone, Serialize)]
+pub enum UnresolvedReason {
+    /// No known device matches the assignment's device_id.
+    DeviceNotFound,
+    /// The assignment is switched off.
+    Disabled,
+    /// Force mode on Windows reads by XInput slot, and this device has none.
+    NoXInputSlot,
+    /// Like `NoXInputSlot`, but because all four XInput slots are taken by other
+    /// devices; freeing one (or unplugging a pad) lets it forward.
+    XInputSlotsFull,
+    /// The device was seen before but is no longer plugged in.
+    Disconnected,
+    /// The target slot is the XInput index another device keeps through a
+    /// passthrough assignment.
+    SlotReserved,
+}
+
+/// A slot assignment that was dropped during resolution, with the reason.
+#[derive(Debug, Clone, Serialize)]
+pub struct UnresolvedAssignment {
+    pub device_id: String,
+    pub target_slot: u8,
+    pub reason: UnresolvedReason,
+}
+
+/// Full resolution result: what the loop would forward, and what it would skip.
+#[derive(Debug, Clone, Serialize)]
+pub struct AssignmentResolution {
+    pub resolved: Vec<ResolvedAssignment>,
+    pub unresolved: Vec<UnresolvedAssignment>,
+    /// Devices that feed more than one resolved target. Allowed, but usually a
+    /// mistake, so the UI warns about them.
+    pub shared_devices: Vec<String>,
+}
+
+/// Per-session forwarding options, derived from the active profile.
+#[derive(Debug, Clone, Default)]
+pub struct ForwardingOptions {
+    /// Force mode (Windows): also disable physical devices via SetupDi after the
+    /// virtual targets are up, and re-enable them on cleanup.
+    pub hard_hide: bool,
+    /// Controller model the virtual targets identify as (Windows Force mode).
+    pub virtual_subtype: VirtualSubtype,
+    /// Extra apps (exe names or full paths) to whitelist in HidHide while forwarding.
+    pub whitelist_apps: Vec<String>,
+    /// Persistently-hidden instance paths; cleanup leaves these (and HidHide) active.
+    pub persistent_hidden: Vec<String>,
+    /// Minimal mode: heartbeat timeout for the crash watchdog (0 = no watchdog).
+    pub minimal_watchdog_secs: u32,
+    /// Minimal mode: leave devices that were already disabled alone.
+    pub minimal_cleanup_only_own: bool,
+    /// Force mode: only forward while one of these executables is in the foreground
+    /// (neutral input otherwise). Empty = always forward.
+    pub focus_exe_names: Vec<String>,
+    /// Force mode: pass rumble from the virtual controllers to the physical pads.
+    pub forward_rumble: bool,
+    /// Force mode: before unplugging the virtual controllers, send them a neutral
+    /// frame and hold it this long. `None` unplugs them straight away.
+    pub stop_settle: Option<std::time::Duration>,
+    /// Force mode failsafe: run Reset all when this chord is held on any source.
+    pub reset_chord: Option<ResetChord>,
+    /// Linux: per-device axis overrides, by device id.
+    pub axis_overrides: BTreeMap<String, Vec<AxisOverride>>,
+    /// Force mode (Windows): model of each virtual target from the profile's
+    /// virtual layout, in creation order. Empty = `virtual_subtype` for all.
+    pub virtual_layout: Vec<VirtualSubtype>,
+}
+
+/// Buttons that must all be held on one controller, and for how long.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResetChord {
+    /// XInput button bits (see `mapping::buttons`).
+    pub buttons: u16,
+    pub hold: Duration,
+}
+
+/// Debounced chord detection: fires once the chord has been held without a break
+/// for the whole hold time, then not again until it's released.
+#[cfg_attr(target_os = "macos", allow(dead_code))]
+struct ChordDetector {
+    chord: Option<ResetChord>,
+    held_since: Option<std::time::Instant>,
+    fired: bool,
+}
+
+#[cfg_attr(target_os = "macos", allow(dead_code))]
+impl ChordDetector {
+    fn new(chord: Option<ResetChord>) -> Self {
+        Self {
+            chord,
+            held_since: None,
+            fired: false,
+        }
+    }
+
+    /// Whether `buttons` (one source's raw button bits) hold the whole chord.
+    fn matches(&self, buttons: u16) -> bool {
+        self.chord.is_some_and(|c| buttons & c.buttons == c.buttons)
+    }
+
+    /// Feed whether any source holds the chord right now; `true` when it fires.
+    fn update(&mut self, held: bool) -> bool {
+        let Some(chord) = self.chord else {
+            return false;
+        };
+        if !held {
+            self.held_since = None;
+            self.fired = false;
+            return false;
+        }
+        let since = *self.held_since.get_or_insert_with(std::time::Instant::now);
+        if self.fired || since.elapsed() < chord.hold {
+            return false;
+        }
+        self.fired = true;
+        true
+    }
+}
+
+impl ForwardingOptions {
+    pub fn from_profile(profile: &Profile) -> Self {
+        Self {
+            hard_hide: profile.hard_hide,
+            virtual_subtype: profile.virtual_subtype,
+            whitelist_apps: vec![],
+            persistent_hidden: vec![],
+            minimal_watchdog_secs: 0,
+            minimal_cleanup_only_own: true,
+            focus_exe_names: vec![],
+            forward_rumble: false,
+            stop_settle: None,
+            reset_chord: None,
+            axis_overrides: BTreeMap::new(),
+            virtual_layout: vec![],
+        }
+    }
+}
+
+/// Lets `cancel_start_forwarding` abort a start in progress. The starting thread
+/// holds `AppState`'s inner lock for the whole start, so this lives outside it.
+#[derive(Debug, Default)]
+pub struct StartCancel {
+    starting: AtomicBool,
+    cancelled: AtomicBool,
+}
+
+impl StartCancel {
+    /// Ask the start in progress to abort. Returns `false` if nothing is starting.
+    pub fn cancel(&self) -> bool {
+        if !self.starting.load(Ordering::SeqCst) {
+            return false;
+        }
+        self.cancelled.store(true, Ordering::SeqCst);
+        true
+    }
+}
+
+/// Frames a latency measurement collects.
+const PROBE_SAMPLES: usize = 20;
+
+/// `LatencyProbe::armed` when no measurement is running.
+const PROBE_IDLE: i16 = -1;
+
+/// `wButtons` bit no XInput button uses, so games ignore it. The latency probe
+/// marks frames with it.
+const PROBE_MARKER: u16 = 0x0800;
+
+/// Software pipeline latency of one virtual controller.
+#[derive(Debug, Clone, Serialize)]
+pub struct LatencyReport {
+    pub target_slot: u8,
+    pub samples: usize,
+    pub min_us: u64,
+    pub avg_us: u64,
+    pub max_us: u64,
+}
+
+/// Latency measurement shared with the Windows Force loop. While armed, the loop
+/// flips `PROBE_MARKER` in the frames it writes to the probed target and notes
+/// when that iteration's source reads started; the measuring thread polls the
+/// virtual controller's XInput slot and times how long the flip takes to come
+/// back. The next flip waits until then. That covers read, map, merge and the
+/// ViGEm/XInput round trip; USB polling, the physical pad and the game's own
+/// input lag are not in it.
+#[derive(Debug)]
+pub struct LatencyProbe {
+    /// Target slot being measured, or `PROBE_IDLE`.
+    armed: std::sync::atomic::AtomicI16,
+    marker: Mutex<ProbeMarker>,
+}
+
+#[derive(Debug, Default)]
+struct ProbeMarker {
+    /// Marker bit the probed target currently carries
+    on: bool,
+    /// Start of the iteration that flipped it, until the flip is read back
+    flipped_at: Option<std::time::Instant>,
+}
+
+impl Default for LatencyProbe {
+    fn default() -> Self {
+        Self {
+            armed: std::sync::atomic::AtomicI16::new(PROBE_IDLE),
+            marker: Mutex::new(ProbeMarker::default()),
+        }
+    }
+}
+
+impl LatencyProbe {
+    /// Collect `PROBE_SAMPLES` frames from the target at `target_slot` by polling
+    /// its virtual controller at `virtual_slot` on the calling thread, for up to
+    /// `timeout`. Fails if another measurement is running or no frame came back.
+    pub fn measure(
+        &self,
+        manager: &dyn PlatformServices,
+        target_slot: u8,
+        virtual_slot: u32,
+        timeout: Duration,
+    ) -> Result<LatencyReport> {
+        // Claim the probe before touching the marker, which a running measurement owns
+        if self
+            .armed
+            .compare_exchange(
+                PROBE_IDLE,
+                target_slot.into(),
+                Ordering::SeqCst,
+                Ordering::SeqCst,
+            )
+            .is_err()
+        {
+            return Err(crate::error::PadSwitchError::InvalidState(
+                "A latency measurement is already running".into(),
+            ));
+        }
+        *self.marker.lock().unwrap() = ProbeMarker::default();
+
+        let slot = virtual_slot.to_string();
+        let deadline = std::time::Instant::now() + timeout;
+        let mut samples: Vec<Duration> = Vec::with_capacity(PROBE_SAMPLES);
+        while samples.len() < PROBE_SAMPLES && std::time::Instant::now() < deadline {
+            let pending = {
+                let marker = self.marker.lock().unwrap();
+                marker.flipped_at.map(|at| (marker.on, at))
+            };
+            if let Some((on, flipped_at)) = pending {
+                if let Ok(state) = manager.read_gamepad_state(&slot) {
+                    if (state.buttons & PROBE_MARKER != 0) == on {
+                        samples.push(flipped_at.elapsed());
+                        self.marker.lock().unwrap().flipped_at = None;
+                    }
+                }
+            }
+            std::thread::yield_now();
+        }
+        self.armed.store(PROBE_IDLE, Ordering::SeqCst);
+
+        let micros: Vec<u64> = samples.iter().map(|d| d.as_micros() as u64).collect();
+        let (Some(&min_us), Some(&max_us)) = (micros.iter().min(), micros.iter().max()) else {
+            return Err(crate::error::PadSwitchError::Forwarding(format!(
+                "No frames from virtual controller {} were read back",
+                target_slot
+            )));
+        };
+        Ok(LatencyReport {
+            target_slot,
+            samples: micros.len(),
+            min_us,
+            avg_us: micros.iter().sum::<u64>() / micros.len() as u64,
+            max_us,
+        })
+    }
+
+    /// The target slot the loop should mark frames for, if a measurement is running.
+    #[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+    fn armed_slot(&self) -> Option<u8> {
+        u8::try_from(self.armed.load(Ordering::SeqCst)).ok()
+    }
+
+    /// Marker bit for the probed target's next frame, whose iteration started at
+    /// `iteration_start`. Flips once the previous flip was read back.
+    #[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+    fn next_marker(&self, iteration_start: std::time::Instant) -> bool {
+        let mut marker = self.marker.lock().unwrap();
+        if marker.flipped_at.is_none() {
+            marker.on = !marker.on;
+            marker.flipped_at = Some(iteration_start);
+        }
+        marker.on
+    }
 }
 
 /// Manages the input forwarding loop.
@@ -22,9 +392,15 @@ pub struct ResolvedAssignment {
 /// Supports two modes:
 /// - **Minimal**: Disable/re-enable physical devices in desired order via SetupDi.
 /// - **Force**: HidHide + ViGEm virtual controllers + input forwarding at ~1000Hz.
+///
+/// Assignments can override the mode; a session with both runs them side by side.
 pub struct InputLoop {
     running: Arc<AtomicBool>,
     thread_handle: Option<std::thread::JoinHandle<()>>,
+    /// Target table published by the loop thread once its virtual controllers exist.
+    virtual_controllers: Arc<Mutex<Vec<VirtualControllerInfo>>>,
+    start_cancel: Arc<StartCancel>,
+    latency: Arc<LatencyProbe>,
 }
 
 impl InputLoop {
@@ -32,15 +408,37 @@ impl InputLoop {
         Self {
             running: Arc::new(AtomicBool::new(false)),
             thread_handle: None,
+            virtual_controllers: Arc::new(Mutex::new(Vec::new())),
+            start_cancel: Arc::new(StartCancel::default()),
+            latency: Arc::new(LatencyProbe::default()),
         }
     }
 
-    /// Start the forwarding loop with resolved assignments and routing mode.
+    /// Handle for cancelling a start from another thread.
+    pub fn start_cancel(&self) -> Arc<StartCancel> {
+        self.start_cancel.clone()
+    }
+
+    /// Handle for measuring latency without holding `AppState`'s inner lock.
+    pub fn latency_probe(&self) -> Arc<LatencyProbe> {
+        self.latency.clone()
+    }
+
+    /// Start the forwarding loop with resolved assignments, routing mode and options.
+    /// `mode` applies to assignments without their own `routing_mode`.
+    ///
+    /// Blocks until the loop confirms it is forwarding (devices hidden, virtual
+    /// targets plugged in) or reports why it couldn't start, up to `STARTUP_TIMEOUT`.
+    /// A `StartCancel::cancel` meanwhile clears `running`; the loop notices between
+    /// startup steps, undoes what it did so far and the start fails.
+    /// Failures after startup are reported to the frontend through `app`.
     pub fn start(
         &mut self,
+        app: AppHandle,
         manager: Arc<dyn PlatformServices>,
         assignments: Vec<ResolvedAssignment>,
         mode: RoutingMode,
+        options: ForwardingOptions,
     ) -> Result<()> {
         if self.running.load(Ordering::SeqCst) {
             return Ok(());
@@ -48,15 +446,31 @@ impl InputLoop {
 
         let running = self.running.clone();
         running.store(true, Ordering::SeqCst);
+        self.start_cancel.cancelled.store(false, Ordering::SeqCst);
+        self.start_cancel.starting.store(true, Ordering::SeqCst);
+
+        let (startup_tx, startup_rx) = std::sync::mpsc::sync_channel(1);
+        let reporter = LoopReporter {
+            app,
+            startup: Some(startup_tx),
+            virtual_controllers: self.virtual_controllers.clone(),
+            latency: self.latency.clone(),
+        };
+
+        let (minimal, force): (Vec<_>, Vec<_>) = assignments
+            .into_iter()
+            .partition(|a| a.effective_mode(&mode) == RoutingMode::Minimal);
 
         let handle = std::thread::Builder::new()
             .name("padswitch-input-loop".into())
-            .spawn(move || match mode {
-                RoutingMode::Minimal => run_minimal(running, assignments),
-                RoutingMode::Force => run_force_forwarding(running, manager, assignments),
+            .spawn(move || match (minimal.is_empty(), force.is_empty()) {
+                (_, true) => run_minimal(running, reporter, minimal, options),
+                (true, false) => run_force_forwarding(running, reporter, manager, force, options),
+                (false, false) => run_hybrid(running, reporter, manager, minimal, force, options),
             })
             .map_err(|e| {
                 self.running.store(false, Ordering::SeqCst);
+                self.start_cancel.starting.store(false, Ordering::SeqCst);
                 crate::error::PadSwitchError::Forwarding(format!(
                     "Failed to spawn input loop: {}",
                     e
@@ -64,7 +478,34 @@ impl InputLoop {
             })?;
 
         self.thread_handle = Some(handle);
-        Ok(())
+
+        let deadline = std::time::Instant::now() + STARTUP_TIMEOUT;
+        let outcome = loop {
+            match startup_rx.recv_timeout(CANCEL_POLL) {
+                Err(RecvTimeoutError::Timeout) if std::time::Instant::now() < deadline => {
+                    if self.start_cancel.cancelled.load(Ordering::SeqCst) {
+                        self.running.store(false, Ordering::SeqCst);
+                    }
+                }
+                outcome => break outcome,
+            }
+        };
+        self.start_cancel.starting.store(false, Ordering::SeqCst);
+        let cancelled = self.start_cancel.cancelled.swap(false, Ordering::SeqCst);
+
+        let failure = match outcome {
+            // Finished before it noticed the cancel; it's stopping now anyway
+            Ok(Ok(())) if cancelled => START_CANCELLED.into(),
+            Ok(Ok(())) => return Ok(()),
+            Ok(Err(message)) => message,
+            Err(RecvTimeoutError::Timeout) => format!(
+                "Input loop did not confirm startup within {}s",
+                STARTUP_TIMEOUT.as_secs()
+            ),
+            Err(RecvTimeoutError::Disconnected) => "Input loop exited during startup".into(),
+        };
+        self.stop();
+        Err(crate::error::PadSwitchError::Forwarding(failure))
     }
 
     /// Stop the forwarding loop.
@@ -73,11 +514,20 @@ impl InputLoop {
         if let Some(handle) = self.thread_handle.take() {
             let _ = handle.join();
         }
+        self.virtual_controllers.lock().unwrap().clear();
     }
 
     pub fn is_running(&self) -> bool {
         self.running.load(Ordering::SeqCst)
     }
+
+    /// Virtual controllers of the running session; empty when not forwarding in Force mode.
+    pub fn virtual_controllers(&self) -> Vec<VirtualControllerInfo> {
+        if !self.is_running() {
+            return vec![];
+        }
+        self.virtual_controllers.lock().unwrap().clone()
+    }
 }
 
 impl Drop for InputLoop {
@@ -86,13 +536,458 @@ impl Drop for InputLoop {
     }
 }
 
+// ---------------------------------------------------------------------------
+// Error reporting
+// ---------------------------------------------------------------------------
+
+/// Startup handshake and error reporting for the loop thread.
+///
+/// The loop thread must never lock `AppState`'s inner state: `stop()` joins it while
+/// the caller holds that lock. Failures are therefore only logged, sent back over the
+/// startup channel, emitted as events, or recorded under the separate
+/// `last_background_error` lock.
+struct LoopReporter {
+    app: AppHandle,
+    startup: Option<SyncSender<std::result::Result<(), String>>>,
+    virtual_controllers: Arc<Mutex<Vec<VirtualControllerInfo>>>,
+    #[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+    latency: Arc<LatencyProbe>,
+}
+
+impl LoopReporter {
+    /// Publish the virtual controller table for `get_virtual_controllers`.
+    #[cfg(not(target_os = "macos"))]
+    fn publish_targets(&self, targets: Vec<VirtualControllerInfo>) {
+        *self.virtual_controllers.lock().unwrap() = targets;
+    }
+
+    /// Confirm the loop is up; `InputLoop::start` returns `Ok`.
+    fn started(&mut self) {
+        if let Some(tx) = self.startup.take() {
+            let _ = tx.send(Ok(()));
+        }
+    }
+
+    /// Report a failure that ends the loop and clear the running flag. During
+    /// startup it becomes the error returned by `InputLoop::start`; afterwards it
+    /// is emitted as `forwarding-error` plus a final `forwarding-status { active: false }`.
+    fn fatal(&mut self, running: &AtomicBool, message: String) {
+        log::error!("{}", message);
+        running.store(false, Ordering::SeqCst);
+        if let Some(tx) = self.startup.take() {
+            if tx.send(Err(message.clone())).is_ok() {
+                return;
+            }
+        }
+        self.record(&message);
+        let _ = self.app.emit(
+            "forwarding-error",
+            serde_json::json!({ "error": message, "fatal": true }),
+        );
+        let _ = self.app.emit(
+            "forwarding-status",
+            serde_json::json!({ "active": false, "error": message }),
+        );
+    }
+
+    /// Report a startup step that is taking a retry, as `forwarding-progress`.
+    #[cfg(target_os = "windows")]
+    fn progress(&self, message: String) {
+        log::info!("{}", message);
+        let _ = self.app.emit(
+            "forwarding-progress",
+            serde_json::json!({ "message": message }),
+        );
+    }
+
+    /// Between startup steps: if the start was cancelled (`running` cleared), report
+    /// it as the startup failure. The caller then undoes its steps so far.
+    #[cfg(not(target_os = "macos"))]
+    fn start_cancelled(&mut self, running: &AtomicBool) -> bool {
+        if running.load(Ordering::SeqCst) {
+            return false;
+        }
+        self.fatal(running, START_CANCELLED.into());
+        true
+    }
+
+    /// Report a problem the loop can continue past (e.g. one device failed to hide).
+    #[cfg(not(target_os = "macos"))]
+    fn error(&self, message: String) {
+        log::error!("{}", message);
+        self.record(&message);
+        let _ = self.app.emit(
+            "forwarding-error",
+            serde_json::json!({ "error": message, "fatal": false }),
+        );
+    }
+
+    /// A source device dropped out (or couldn't be opened); the rest keep forwarding.
+    #[cfg(target_os = "linux")]
+    fn device_lost(&self, instance_path: &str, reason: &str) {
+        self.error(format!("Lost {}: {}", instance_path, reason));
+        let _ = self.app.emit(
+            "forwarding-device-lost",
+            serde_json::json!({ "instance_path": instance_path, "reason": reason }),
+        );
+    }
+
+    /// A lost source device was reopened and is forwarding again.
+    #[cfg(target_os = "linux")]
+    fn device_restored(&self, instance_path: &str) {
+        log::info!("Reopened {}", instance_path);
+        let _ = self.app.emit(
+            "forwarding-device-restored",
+            serde_json::json!({ "instance_path": instance_path }),
+        );
+    }
+
+    /// The reset chord was held: run Reset all on its own thread, since it stops
+    /// forwarding and so joins this one.
+    #[cfg(not(target_os = "macos"))]
+    fn reset_chord_fired(&self) {
+        log::warn!("Reset chord held — running Reset all");
+        let app = self.app.clone();
+        let spawned = std::thread::Builder::new()
+            .name("padswitch-chord-reset".into())
+            .spawn(move || {
+                if let Err(e) = crate::commands::reset_all(app.clone(), app.state::<AppState>()) {
+                    log::warn!("Reset from chord failed: {}", e);
+                }
+            });
+        if let Err(e) = spawned {
+            log::warn!("Failed to spawn chord reset: {}", e);
+        }
+    }
+
+    /// Keep the failure for `get_last_background_error`.
+    fn record(&self, message: &str) {
+        if let Some(state) = self.app.try_state::<AppState>() {
+            state.record_background_error("forwarding", message);
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Merging: several physical sources feeding one virtual target
+// ---------------------------------------------------------------------------
+
+/// Group slot-sorted assignments by target slot. Assignments that share a target
+/// slot drive a single virtual controller. Returns each source's group index
+/// (parallel to `sorted`) and the number of groups.
+#[cfg(not(target_os = "macos"))]
+fn target_groups(sorted: &[ResolvedAssignment]) -> (Vec<usize>, usize) {
+    let mut group_of = Vec::with_capacity(sorted.len());
+    let mut count = 0;
+    let mut prev_slot = None;
+    for ra in sorted {
+        if prev_slot != Some(ra.target_slot) {
+            count += 1;
+            prev_slot = Some(ra.target_slot);
+        }
+        group_of.push(count - 1);
+    }
+    (group_of, count)
+}
+
+/// Describe each target group: its slot and the devices feeding it.
+#[cfg(not(target_os = "macos"))]
+fn target_table(
+    sorted: &[ResolvedAssignment],
+    group_of: &[usize],
+    group_count: usize,
+) -> Vec<VirtualControllerInfo> {
+    (0..group_count)
+        .map(|g| {
+            let members: Vec<&ResolvedAssignment> = sorted
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| group_of[*i] == g)
+                .map(|(_, ra)| ra)
+                .collect();
+            VirtualControllerInfo {
+                index: g,
+                target_slot: members.first().map_or(0, |ra| ra.target_slot),
+                xinput_slot: None,
+                device_ids: members.iter().map(|ra| ra.device_id.clone()).collect(),
+                rumble: RumblePath::Off,
+            }
+        })
+        .collect()
+}
+
+/// Connect to ViGEmBus, retrying briefly: connecting fails transiently right after the
+/// driver starts or another client disconnects. Returns the last error if all fail.
+#[cfg(target_os = "windows")]
+fn connect_vigem(
+    reporter: &LoopReporter,
+) -> std::result::Result<vigem_client::Client, vigem_client::Error> {
+    let mut attempt = 1;
+    loop {
+        match vigem_client::Client::connect() {
+            Ok(client) => return Ok(client),
+            Err(e) if attempt < VIGEM_CONNECT_ATTEMPTS => {
+                reporter.progress(format!(
+                    "ViGEmBus connect failed ({:?}) — retrying ({}/{})",
+                    e,
+                    attempt + 1,
+                    VIGEM_CONNECT_ATTEMPTS
+                ));
+                std::thread::sleep(VIGEM_CONNECT_BACKOFF * attempt);
+                attempt += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Passthrough: XInput hands a new pad the lowest free user index, so while `target`
+/// sits below `slot`, keep it there as a placeholder and plug in another. Dropping the
+/// placeholders afterwards frees those lower slots again.
+#[cfg(target_os = "windows")]
+fn land_on_slot<'a>(
+    client: &'a vigem_client::Client,
+    id: vigem_client::TargetId,
+    mut target: vigem_client::Xbox360Wired<'a>,
+    slot: u32,
+) -> vigem_client::Xbox360Wired<'a> {
+    let mut placeholders = Vec::new();
+    while target.get_user_index().is_ok_and(|index| index < slot) && placeholders.len() < 4 {
+        let mut next = vigem_client::Xbox360Wired::new(client, id);
+        if let Err(e) = next.plugin_wait() {
+            log::warn!(
+                "Passthrough: could not move virtual pad to slot {}: {:?}",
+                slot,
+                e
+            );
+            break;
+        }
+        placeholders.push(std::mem::replace(&mut target, next));
+    }
+    match target.get_user_index() {
+        Ok(index) if index != slot => {
+            log::warn!(
+                "Passthrough: virtual pad landed on slot {} instead of {}",
+                index,
+                slot
+            )
+        }
+        _ => {}
+    }
+    target
+}
+
+/// Minimum axis change that makes a source "the one that moved last".
+/// Keeps stick noise on an idle controller from stealing the axes.
+#[cfg(target_os = "windows")]
+const MERGE_STICK_EPSILON: i32 = 2048;
+#[cfg(target_os = "windows")]
+const MERGE_TRIGGER_EPSILON: i32 = 16;
+
+/// Combines several physical sources into one output frame:
+/// buttons are OR'd, sticks and triggers come from whichever source moved last.
+#[cfg(target_os = "windows")]
+#[derive(Default)]
+struct SourceMerger {
+    sources: std::collections::HashMap<usize, GamepadState>,
+    axis_owner: Option<usize>,
+}
+
+#[cfg(target_os = "windows")]
+impl SourceMerger {
+    fn update(&mut self, source: usize, state: GamepadState) {
+        let moved = match self.sources.get(&source) {
+            Some(prev) => axes_moved(prev, &state),
+            None => true,
+        };
+        if moved || self.axis_owner.is_none() {
+            self.axis_owner = Some(source);
+        }
+        self.sources.insert(source, state);
+    }
+
+    fn output(&self) -> Option<GamepadState> {
+        let owner = self.sources.get(&self.axis_owner?)?;
+        let mut out = owner.clone();
+        out.buttons = self.sources.values().fold(0, |b, s| b | s.buttons);
+        Some(out)
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn axes_moved(prev: &GamepadState, next: &GamepadState) -> bool {
+    let stick = |a: i16, b: i16| (a as i32 - b as i32).abs() >= MERGE_STICK_EPSILON;
+    let trigger = |a: u8, b: u8| (a as i32 - b as i32).abs() >= MERGE_TRIGGER_EPSILON;
+    stick(prev.thumb_lx, next.thumb_lx)
+        || stick(prev.thumb_ly, next.thumb_ly)
+        || stick(prev.thumb_rx, next.thumb_rx)
+        || stick(prev.thumb_ry, next.thumb_ry)
+        || trigger(prev.left_trigger, next.left_trigger)
+        || trigger(prev.right_trigger, next.right_trigger)
+}
+
+// ---------------------------------------------------------------------------
+// Focus gating: pause forwarding while the game isn't the foreground window
+// ---------------------------------------------------------------------------
+
+/// How often the foreground window is checked; lookups are too slow for every poll.
+#[cfg(not(target_os = "macos"))]
+const FOCUS_CHECK_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Tracks whether one of the configured executables owns the foreground window.
+/// The window system is queried on a `FocusSampler` thread; polling only reads its flag.
+#[cfg(not(target_os = "macos"))]
+struct FocusGate {
+    sampler: Option<crate::process_watcher::FocusSampler>,
+    focused: bool,
+}
+
+#[cfg(not(target_os = "macos"))]
+impl FocusGate {
+    fn new(exe_names: Vec<String>) -> Self {
+        let sampler = if exe_names.is_empty() {
+            None
+        } else {
+            crate::process_watcher::FocusSampler::start(exe_names, FOCUS_CHECK_INTERVAL)
+        };
+        Self {
+            sampler,
+            focused: true,
+        }
+    }
+
+    /// Whether to forward right now. Always true with no executables configured
+    /// or when the foreground process can't be determined, so gating fails open.
+    fn poll(&mut self) -> bool {
+        let focused = self.sampler.as_ref().is_none_or(|s| s.focused());
+        if focused != self.focused {
+            log::info!(
+                "Force mode: game window {} — {} forwarding",
+                if focused { "focused" } else { "lost focus" },
+                if focused { "resuming" } else { "pausing" }
+            );
+            self.focused = focused;
+        }
+        focused
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Hybrid: per-assignment routing modes in one session
+// ---------------------------------------------------------------------------
+
+/// Run a session that mixes modes. The Minimal reorder runs first on a helper
+/// thread (so the physical slots settle before the virtual targets take theirs),
+/// then Force forwarding runs on this one. Either side failing clears `running`,
+/// which stops the other; the helper cleans up before this returns.
+fn run_hybrid(
+    running: Arc<AtomicBool>,
+    mut reporter: LoopReporter,
+    manager: Arc<dyn PlatformServices>,
+    minimal: Vec<ResolvedAssignment>,
+    mut force: Vec<ResolvedAssignment>,
+    options: ForwardingOptions,
+) {
+    log::info!(
+        "Hybrid mode: {} Minimal and {} Force assignments",
+        minimal.len(),
+        force.len()
+    );
+
+    let (startup_tx, startup_rx) = std::sync::mpsc::sync_channel(1);
+    let minimal_reporter = LoopReporter {
+        app: reporter.app.clone(),
+        startup: Some(startup_tx),
+        virtual_controllers: reporter.virtual_controllers.clone(),
+        latency: reporter.latency.clone(),
+    };
+    let minimal_running = running.clone();
+    let minimal_options = options.clone();
+    let spawned = std::thread::Builder::new()
+        .name("padswitch-input-loop-minimal".into())
+        .spawn(move || run_minimal(minimal_running, minimal_reporter, minimal, minimal_options));
+    let minimal_thread = match spawned {
+        Ok(handle) => handle,
+        Err(e) => {
+            reporter.fatal(
+                &running,
+                format!("Failed to spawn Minimal-mode thread: {}", e),
+            );
+            return;
+        }
+    };
+
+    let failure = match startup_rx.recv() {
+        Ok(Ok(())) => None,
+        Ok(Err(message)) => Some(message),
+        Err(_) => Some("Minimal-mode thread exited during startup".into()),
+    };
+    if let Some(message) = failure {
+        running.store(false, Ordering::SeqCst);
+        let _ = minimal_thread.join();
+        reporter.fatal(&running, message);
+        return;
+    }
+
+    // The reorder moved physical pads to new XInput indices; read from where they are now
+    if let Err(e) = refresh_source_slots(&*manager, &mut force) {
+        running.store(false, Ordering::SeqCst);
+        let _ = minimal_thread.join();
+        reporter.fatal(
+            &running,
+            format!("Failed to re-enumerate after reordering: {}", e),
+        );
+        return;
+    }
+
+    run_force_forwarding(running.clone(), reporter, manager, force, options);
+
+    running.store(false, Ordering::SeqCst);
+    let _ = minimal_thread.join();
+}
+
+/// Re-read each assignment's current XInput slot from a fresh enumeration, matched
+/// by instance path. A source no longer enumerated loses its slot. Passthrough
+/// targets follow their device, as in `resolve_one`.
+fn refresh_source_slots(
+    manager: &dyn PlatformServices,
+    assignments: &mut [ResolvedAssignment],
+) -> Result<()> {
+    let devices = manager.enumerate_devices()?;
+    for ra in assignments.iter_mut() {
+        let slot = devices
+            .iter()
+            .find(|d| crate::platform::same_instance_path(&d.instance_path, &ra.instance_path))
+            .and_then(|d| d.xinput_slot);
+        if slot != ra.xinput_slot {
+            log::info!(
+                "Hybrid mode: {} moved from XInput slot {:?} to {:?}",
+                ra.instance_path,
+                ra.xinput_slot,
+                slot
+            );
+        }
+        ra.xinput_slot = slot;
+        if let (true, Some(slot)) = (ra.passthrough, slot) {
+            ra.target_slot = slot as u8;
+        }
+    }
+    Ok(())
+}
+
 // ---------------------------------------------------------------------------
 // Minimal mode: disable/re-enable devices via SetupDi
 // ---------------------------------------------------------------------------
 
 #[cfg(target_os = "windows")]
-fn run_minimal(running: Arc<AtomicBool>, assignments: Vec<ResolvedAssignment>) {
+fn run_minimal(
+    running: Arc<AtomicBool>,
+    mut reporter: LoopReporter,
+    assignments: Vec<ResolvedAssignment>,
+    options: ForwardingOptions,
+) {
     use crate::setupdi::imp;
+    use crate::watchdog::Heartbeat;
 
     log::info!(
         "Minimal mode: reordering {} devices",
@@ -103,14 +998,46 @@ fn run_minimal(running: Arc<AtomicBool>, assignments: Vec<ResolvedAssignment>) {
     let mut sorted = assignments.clone();
     sorted.sort_by_key(|a| a.target_slot);
 
-    // Use real instance paths for SetupDi operations
-    let paths: Vec<&str> = sorted.iter().map(|a| a.instance_path.as_str()).collect();
+    // Use real instance paths for SetupDi operations. Devices the user had already
+    // disabled are left out, so cleanup never enables something they turned off.
+    // A device can only take one place in the order; the lowest target slot wins.
+    let distinct = distinct_paths(&sorted);
+    let paths: Vec<&str> = distinct
+        .iter()
+        .map(String::as_str)
+        .filter(|path| {
+            let skip = options.minimal_cleanup_only_own && imp::is_disabled(path);
+            if skip {
+                log::info!(
+                    "Minimal mode: {} was already disabled — leaving it alone",
+                    path
+                );
+            }
+            !skip
+        })
+        .collect();
+
+    // Step 0: If the app is killed from here on, the watchdog re-enables `paths`.
+    // Dropped (heartbeat removed) when this function returns.
+    let heartbeat = match options.minimal_watchdog_secs {
+        0 => None,
+        secs => Heartbeat::start(&paths, secs)
+            .map_err(|e| reporter.error(format!("Failed to start Minimal-mode watchdog: {}", e)))
+            .ok(),
+    };
+    let beat = || {
+        if let Some(heartbeat) = &heartbeat {
+            heartbeat.beat();
+        }
+    };
 
-    // Step 1: Disable all assigned devices
+    // Step 1: Disable all assigned devices, remembering which ones we actually disabled
+    let mut disabled: Vec<&str> = Vec::new();
     for path in &paths {
         log::info!("Minimal mode: disabling {}", path);
-        if let Err(e) = imp::disable_device(path) {
-            log::error!("Failed to disable {}: {}", path, e);
+        match imp::disable_device(path) {
+            Ok(()) => disabled.push(*path),
+            Err(e) => reporter.error(format!("Failed to disable {}: {}", path, e)),
         }
     }
 
@@ -126,19 +1053,31 @@ fn run_minimal(running: Arc<AtomicBool>, assignments: Vec<ResolvedAssignment>) {
         if let Err(e) = imp::enable_device(path) {
             log::error!("Failed to enable {}: {}", path, e);
         }
+        beat();
         std::thread::sleep(std::time::Duration::from_millis(100));
     }
 
     log::info!("Minimal mode: reorder complete, holding state");
+    reporter.started();
 
     // Hold state — thread stays alive so stop() can clean up
     while running.load(Ordering::SeqCst) {
+        beat();
         std::thread::sleep(std::time::Duration::from_millis(500));
     }
 
-    // Cleanup: re-enable all devices in case any were left disabled
-    log::info!("Minimal mode: cleanup — re-enabling all devices");
-    for path in &paths {
+    // Cleanup: re-enable the devices we disabled in case any were left disabled
+    // (or every assigned device, if the user opted out of tracking)
+    let restore = if options.minimal_cleanup_only_own {
+        &disabled
+    } else {
+        &paths
+    };
+    log::info!(
+        "Minimal mode: cleanup — re-enabling {} devices",
+        restore.len()
+    );
+    for path in restore {
         if let Err(e) = imp::enable_device(path) {
             log::warn!("Cleanup enable failed for {}: {}", path, e);
         }
@@ -146,16 +1085,29 @@ fn run_minimal(running: Arc<AtomicBool>, assignments: Vec<ResolvedAssignment>) {
 }
 
 #[cfg(target_os = "linux")]
-fn run_minimal(running: Arc<AtomicBool>, _assignments: Vec<ResolvedAssignment>) {
+fn run_minimal(
+    running: Arc<AtomicBool>,
+    mut reporter: LoopReporter,
+    _assignments: Vec<ResolvedAssignment>,
+    _options: ForwardingOptions,
+) {
     // Minimal mode is not supported on Linux — the preflight check in state.rs
-    // should already block this, but log an error defensively.
-    log::error!("Minimal mode is not supported on Linux. Use Force mode instead.");
-    running.store(false, Ordering::SeqCst);
+    // should already block this, but report an error defensively.
+    reporter.fatal(
+        &running,
+        "Minimal mode is not supported on Linux. Use Force mode instead.".into(),
+    );
 }
 
 #[cfg(target_os = "macos")]
-fn run_minimal(running: Arc<AtomicBool>, _assignments: Vec<ResolvedAssignment>) {
+fn run_minimal(
+    running: Arc<AtomicBool>,
+    mut reporter: LoopReporter,
+    _assignments: Vec<ResolvedAssignment>,
+    _options: ForwardingOptions,
+) {
     log::info!("Minimal mode: stub (macOS)");
+    reporter.started();
     while running.load(Ordering::SeqCst) {
         std::thread::sleep(std::time::Duration::from_millis(500));
     }
@@ -168,11 +1120,13 @@ fn run_minimal(running: Arc<AtomicBool>, _assignments: Vec<ResolvedAssignment>)
 #[cfg(target_os = "windows")]
 fn run_force_forwarding(
     running: Arc<AtomicBool>,
+    mut reporter: LoopReporter,
     manager: Arc<dyn PlatformServices>,
     assignments: Vec<ResolvedAssignment>,
+    options: ForwardingOptions,
 ) {
     use crate::hidhide::imp::HidHide;
-    use crate::vigem::imp::to_xgamepad;
+    use crate::vigem::imp::{target_id, to_xgamepad};
 
     log::info!(
         "Force mode: starting with {} assignments",
@@ -185,129 +1139,401 @@ fn run_force_forwarding(
 
     // Step 1: Whitelist ourselves so we can still read hidden devices
     if let Err(e) = manager.whitelist_self() {
-        log::error!("Failed to whitelist self: {}", e);
-        running.store(false, Ordering::SeqCst);
+        reporter.fatal(&running, format!("Failed to whitelist self: {}", e));
         return;
     }
 
+    // Step 1b: Whitelist overlay/streaming apps that are running now. Only entries we
+    // added are tracked, so cleanup never removes the user's own whitelist entries.
+    let mut whitelisted: Vec<String> = Vec::new();
+    for path in resolve_whitelist_apps(&options.whitelist_apps) {
+        match manager.whitelist_app(&path) {
+            Ok(true) => {
+                log::info!("Force mode: whitelisted {}", path);
+                whitelisted.push(path);
+            }
+            Ok(false) => {}
+            Err(e) => log::warn!("Failed to whitelist {}: {}", path, e),
+        }
+    }
+
     // Step 2: Hide all assigned physical devices using real instance paths
-    let instance_paths: Vec<String> = sorted.iter().map(|a| a.instance_path.clone()).collect();
+    let instance_paths = distinct_paths(&sorted);
 
     for path in &instance_paths {
         log::info!("Force mode: hiding {}", path);
         if let Err(e) = manager.hide_device(path) {
-            log::error!("Failed to hide {}: {}", path, e);
+            reporter.error(format!("Failed to hide {}: {}", path, e));
         }
     }
 
+    if reporter.start_cancelled(&running) {
+        cleanup_force(
+            &manager,
+            &instance_paths,
+            &whitelisted,
+            &options.persistent_hidden,
+        );
+        return;
+    }
+
     // Step 3: Activate HidHide
     match HidHide::open() {
         Ok(hh) => {
-            if let Err(e) = hh.set_active(true) {
-                log::error!("Failed to activate HidHide: {}", e);
+            let result = hh.set_active(true);
+            crate::audit::record("activate-hiding", "-", &result);
+            if let Err(e) = result {
+                reporter.error(format!("Failed to activate HidHide: {}", e));
             }
         }
         Err(e) => {
-            log::error!("Failed to open HidHide for activation: {}", e);
-            running.store(false, Ordering::SeqCst);
+            cleanup_force(
+                &manager,
+                &instance_paths,
+                &whitelisted,
+                &options.persistent_hidden,
+            );
+            reporter.fatal(
+                &running,
+                format!("Failed to open HidHide for activation: {}", e),
+            );
             return;
         }
     }
 
     // Step 4: Connect to ViGEmBus — client lives on this thread's stack
-    let client = match vigem_client::Client::connect() {
+    let client = match connect_vigem(&reporter) {
         Ok(c) => c,
         Err(e) => {
-            log::error!("Failed to connect to ViGEmBus: {:?}", e);
-            cleanup_force(&manager, &instance_paths);
-            running.store(false, Ordering::SeqCst);
+            cleanup_force(
+                &manager,
+                &instance_paths,
+                &whitelisted,
+                &options.persistent_hidden,
+            );
+            reporter.fatal(&running, format!("Failed to connect to ViGEmBus: {:?}", e));
             return;
         }
     };
 
-    // Step 5: Create virtual Xbox 360 targets in slot order
+    if reporter.start_cancelled(&running) {
+        drop(client);
+        cleanup_force(
+            &manager,
+            &instance_paths,
+            &whitelisted,
+            &options.persistent_hidden,
+        );
+        return;
+    }
+
+    // Step 5: Create one virtual target per target slot, in slot order. Assignments
+    // sharing a slot are merged into that target. A virtual layout picks each
+    // target's model and may add idle targets after the assigned ones. A non-360
+    // subtype only changes the IDs; if the bus rejects it, fall back to Xbox 360.
+    let (group_of, group_count) = target_groups(&sorted);
     let mut targets: Vec<vigem_client::Xbox360Wired<'_>> = Vec::new();
-    for _ in &sorted {
-        let mut target = vigem_client::Xbox360Wired::new(&client, vigem_client::TargetId::XBOX360_WIRED);
-        match target.plugin_wait() {
-            Ok(()) => targets.push(target),
+    for group in 0..group_count.max(options.virtual_layout.len()) {
+        let subtype = options
+            .virtual_layout
+            .get(group)
+            .copied()
+            .unwrap_or(options.virtual_subtype);
+        let mut id = target_id(subtype);
+        let mut target = vigem_client::Xbox360Wired::new(&client, id);
+        let mut plugged = target.plugin_wait();
+        if plugged.is_err() && subtype != VirtualSubtype::Xbox360 {
+            log::warn!(
+                "Failed to plug in {:?} virtual controller — falling back to Xbox 360",
+                subtype
+            );
+            id = vigem_client::TargetId::XBOX360_WIRED;
+            target = vigem_client::Xbox360Wired::new(&client, id);
+            plugged = target.plugin_wait();
+        }
+        match plugged {
+            Ok(()) => {
+                let mut members = sorted.iter().zip(&group_of).filter(|(_, &g)| g == group);
+                if let Some((ra, _)) = members.find(|(ra, _)| ra.passthrough) {
+                    target = land_on_slot(&client, id, target, ra.target_slot as u32);
+                }
+                targets.push(target);
+            }
             Err(e) => {
-                log::error!("Failed to plug in virtual controller: {:?}", e);
-                cleanup_force(&manager, &instance_paths);
-                running.store(false, Ordering::SeqCst);
+                drop(targets);
+                cleanup_force(
+                    &manager,
+                    &instance_paths,
+                    &whitelisted,
+                    &options.persistent_hidden,
+                );
+                reporter.fatal(
+                    &running,
+                    format!("Failed to plug in virtual controller: {:?}", e),
+                );
                 return;
             }
         }
     }
 
-    // Step 6: Load XInput handle for reading physical state
-    let xinput = match rusty_xinput::XInputHandle::load_default() {
-        Ok(h) => h,
-        Err(e) => {
-            log::error!("Failed to load XInput: {:?}", e);
-            cleanup_force(&manager, &instance_paths);
-            running.store(false, Ordering::SeqCst);
-            return;
+    if reporter.start_cancelled(&running) {
+        drop(targets);
+        cleanup_force(
+            &manager,
+            &instance_paths,
+            &whitelisted,
+            &options.persistent_hidden,
+        );
+        return;
+    }
+
+    // Step 6: Hard hide — disable physical devices now that the virtual targets are up.
+    // Devices whose driver drops the XInput slot on disable will stop reporting;
+    // this is meant for games that bypass HidHide via raw HID.
+    let mut disabled_paths: Vec<String> = Vec::new();
+    if options.hard_hide {
+        for path in &instance_paths {
+            log::info!("Force mode: hard hide — disabling {}", path);
+            match manager.disable_device(path) {
+                Ok(()) => disabled_paths.push(path.clone()),
+                Err(e) => reporter.error(format!("Failed to disable {}: {}", path, e)),
+            }
         }
-    };
+    }
+
+    // Step 6b: Forward rumble from each target to the pads feeding it, off this
+    // thread so the poll loop's timing is untouched
+    let mut table = target_table(&sorted, &group_of, group_count);
+    let mut rumble_slots: Vec<u32> = Vec::new();
+    let mut rumble_threads: Vec<std::thread::JoinHandle<()>> = Vec::new();
+    for (group, (info, target)) in table.iter_mut().zip(targets.iter_mut()).enumerate() {
+        info.xinput_slot = target.get_user_index().ok();
+        if !options.forward_rumble {
+            continue;
+        }
+        // Pads without motors are skipped; if the probe fails, try them anyway
+        let slots: Vec<u32> = sorted
+            .iter()
+            .zip(&group_of)
+            .filter(|(_, &g)| g == group)
+            .filter_map(|(ra, _)| ra.xinput_slot)
+            .filter(|slot| manager.supports_rumble(&slot.to_string()).unwrap_or(true))
+            .collect();
+        if slots.is_empty() {
+            info.rumble = RumblePath::NoMotors;
+            continue;
+        }
+        rumble_slots.extend(&slots);
+        if let Some(thread) = forward_rumble(target, &manager, slots) {
+            rumble_threads.push(thread);
+            info.rumble = RumblePath::Notification;
+        } else {
+            info.rumble = RumblePath::Unavailable;
+            reporter.error(format!(
+                "Rumble forwarding unavailable for virtual controller {}",
+                info.index
+            ));
+        }
+    }
+    // Target slot per target, for the latency probe
+    let probe_slots: Vec<u8> = table.iter().map(|info| info.target_slot).collect();
+    reporter.publish_targets(table);
 
     log::info!("Force mode: forwarding loop active");
+    reporter.started();
 
-    // Step 7: Poll loop at ~1000Hz — read from real XInput slots, write to virtual targets
+    // Step 7: Poll loop at ~1000Hz — read from real XInput slots through the platform's
+    // shared XInput handle, merge per target, write to virtual targets
+    let mut mergers: Vec<SourceMerger> =
+        (0..group_count).map(|_| SourceMerger::default()).collect();
+    let mut focus = FocusGate::new(options.focus_exe_names.clone());
+    let mut paused = false;
+    let mut chord = ChordDetector::new(options.reset_chord);
+    // This cycle's raw state per XInput slot
+    let mut reads: Vec<(u32, Option<GamepadState>)> = Vec::with_capacity(sorted.len());
+    // Each source's slot as the platform addresses it, built once rather than per frame
+    let slot_ids: Vec<Option<String>> = sorted
+        .iter()
+        .map(|ra| ra.xinput_slot.map(|slot| slot.to_string()))
+        .collect();
     while running.load(Ordering::SeqCst) {
+        let iteration_start = std::time::Instant::now();
+        // While the game isn't focused, hold every target at neutral
+        if !focus.poll() {
+            if !paused {
+                for target in targets.iter_mut() {
+                    let _ = target.update(&to_xgamepad(&GamepadState::default()));
+                }
+                paused = true;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(1));
+            continue;
+        }
+        paused = false;
+
+        let mut chord_held = false;
+        reads.clear();
         for (i, ra) in sorted.iter().enumerate() {
-            let Some(slot) = ra.xinput_slot else {
+            let (Some(slot), Some(slot_id)) = (ra.xinput_slot, &slot_ids[i]) else {
                 continue; // Skip devices without a known XInput slot
             };
-            if let Ok(state) = xinput.get_state(slot) {
-                let gamepad = crate::device::GamepadState {
-                    buttons: state.raw.Gamepad.wButtons,
-                    left_trigger: state.raw.Gamepad.bLeftTrigger,
-                    right_trigger: state.raw.Gamepad.bRightTrigger,
-                    thumb_lx: state.raw.Gamepad.sThumbLX,
-                    thumb_ly: state.raw.Gamepad.sThumbLY,
-                    thumb_rx: state.raw.Gamepad.sThumbRX,
-                    thumb_ry: state.raw.Gamepad.sThumbRY,
-                };
-                let xgamepad = to_xgamepad(&gamepad);
-                let _ = targets[i].update(&xgamepad);
+            // A pad assigned to several targets is read once and fanned out
+            let raw = match reads.iter().find(|(s, _)| *s == slot) {
+                Some((_, raw)) => raw.clone(),
+                None => {
+                    let raw = manager.read_gamepad_state(slot_id).ok();
+                    if let Some(gamepad) = &raw {
+                        chord_held |= chord.matches(gamepad.buttons);
+                    }
+                    reads.push((slot, raw.clone()));
+                    raw
+                }
+            };
+            if let Some(mut gamepad) = raw {
+                ra.mapping.apply(&mut gamepad);
+                mergers[group_of[i]].update(i, gamepad);
             }
         }
+        if chord.update(chord_held) {
+            reporter.reset_chord_fired();
+        }
+        let probed = reporter.latency.armed_slot();
+        for ((target, merger), &target_slot) in targets.iter_mut().zip(&mergers).zip(&probe_slots) {
+            let Some(mut gamepad) = merger.output() else {
+                continue;
+            };
+            if probed == Some(target_slot) && reporter.latency.next_marker(iteration_start) {
+                gamepad.buttons |= PROBE_MARKER;
+            }
+            let _ = target.update(&to_xgamepad(&gamepad));
+        }
         std::thread::sleep(std::time::Duration::from_millis(1));
     }
 
     log::info!("Force mode: stopping — cleaning up");
 
-    // Step 8: Drop targets (unplugs virtual controllers), then unhide devices
+    // Step 8: Release every input first, so a game that races the unplug against the
+    // last frame doesn't keep a button held
+    if let Some(settle) = options.stop_settle {
+        for target in targets.iter_mut() {
+            let _ = target.update(&to_xgamepad(&GamepadState::default()));
+        }
+        std::thread::sleep(settle);
+    }
+
+    // Step 9: Drop targets (unplugs virtual controllers, ending the rumble threads),
+    // stop any motors still running, re-enable hard-hidden devices, then unhide devices
     drop(targets);
-    cleanup_force(&manager, &instance_paths);
+    // A notification already in flight could otherwise restart a motor after the reset
+    for thread in rumble_threads {
+        let _ = thread.join();
+    }
+    for slot in &rumble_slots {
+        let _ = manager.set_rumble(&slot.to_string(), 0, 0);
+    }
+    for path in &disabled_paths {
+        if let Err(e) = manager.enable_device(path) {
+            log::warn!("Cleanup enable failed for {}: {}", path, e);
+        }
+    }
+    cleanup_force(
+        &manager,
+        &instance_paths,
+        &whitelisted,
+        &options.persistent_hidden,
+    );
 }
 
+/// Register for `target`'s output notifications and set the motors of the pads in
+/// `slots` whenever the game changes them. Returns the notification thread, which
+/// ends when the target is unplugged, or `None` if registering failed.
 #[cfg(target_os = "windows")]
-fn cleanup_force(manager: &Arc<dyn PlatformServices>, instance_paths: &[String]) {
-    use crate::hidhide::imp::HidHide;
+fn forward_rumble(
+    target: &mut vigem_client::Xbox360Wired<'_>,
+    manager: &Arc<dyn PlatformServices>,
+    slots: Vec<u32>,
+) -> Option<std::thread::JoinHandle<()>> {
+    let notification = match target.request_notification() {
+        Ok(notification) => notification,
+        Err(e) => {
+            log::warn!("Failed to register for rumble notifications: {:?}", e);
+            return None;
+        }
+    };
+    let manager = manager.clone();
+    let slot_ids: Vec<String> = slots.iter().map(u32::to_string).collect();
+    let thread = notification.spawn_thread(move |_, n| {
+        // Motor speeds arrive as 0-255; XInput takes 0-65535
+        let (left, right) = (
+            u16::from(n.large_motor) * 257,
+            u16::from(n.small_motor) * 257,
+        );
+        for slot in &slot_ids {
+            if let Err(e) = manager.set_rumble(slot, left, right) {
+                log::debug!("Rumble forward to slot {} failed: {}", slot, e);
+            }
+        }
+    });
+    Some(thread)
+}
 
-    // Deactivate HidHide
-    if let Ok(hh) = HidHide::open() {
-        let _ = hh.set_active(false);
+#[cfg(target_os = "windows")]
+fn cleanup_force(
+    manager: &Arc<dyn PlatformServices>,
+    instance_paths: &[String],
+    whitelisted: &[String],
+    persistent_hidden: &[String],
+) {
+    // Deactivate HidHide, unless persistently-hidden devices still need it
+    if persistent_hidden.is_empty() {
+        let _ = manager.deactivate_hiding();
     }
 
-    // Unhide all devices
-    for path in instance_paths {
+    // Unhide all devices except persistently-hidden ones
+    let is_persistent = |path: &str| {
+        persistent_hidden
+            .iter()
+            .any(|p| crate::platform::same_instance_path(p, path))
+    };
+    for path in instance_paths.iter().filter(|p| !is_persistent(p)) {
         if let Err(e) = manager.unhide_device(path) {
             log::warn!("Cleanup unhide failed for {}: {}", path, e);
         }
     }
+
+    // Remove only the whitelist entries we added
+    for path in whitelisted {
+        if let Err(e) = manager.unwhitelist_app(path) {
+            log::warn!("Cleanup unwhitelist failed for {}: {}", path, e);
+        }
+    }
+}
+
+/// Resolve whitelist entries to full image paths. Entries with a path separator
+/// are used as-is; bare exe names match currently running processes.
+#[cfg(target_os = "windows")]
+fn resolve_whitelist_apps(entries: &[String]) -> Vec<String> {
+    let mut paths = Vec::new();
+    for entry in entries {
+        if entry.contains('\\') || entry.contains('/') {
+            paths.push(entry.clone());
+        } else {
+            paths.extend(crate::process_watcher::find_process_paths(entry));
+        }
+    }
+    paths
 }
 
 #[cfg(target_os = "linux")]
 fn run_force_forwarding(
     running: Arc<AtomicBool>,
+    mut reporter: LoopReporter,
     _manager: Arc<dyn PlatformServices>,
     assignments: Vec<ResolvedAssignment>,
+    options: ForwardingOptions,
 ) {
-    use evdev::uinput::VirtualDeviceBuilder;
-    use evdev::{AbsoluteAxisCode, AbsInfo, UinputAbsSetup, InputEvent, EventType};
+    use crate::mapping::{AxisLayout, EventMapper};
+    use evdev::InputEvent;
 
     log::info!(
         "Force mode (Linux): starting with {} assignments",
@@ -318,124 +1544,242 @@ fn run_force_forwarding(
     let mut sorted = assignments.clone();
     sorted.sort_by_key(|a| a.target_slot);
 
-    // Step 1: Open and grab all physical devices
-    let mut physical_devices: Vec<evdev::Device> = Vec::new();
-    for ra in &sorted {
-        let mut device = match evdev::Device::open(&ra.instance_path) {
-            Ok(d) => d,
+    // Step 1: Open and grab each physical device once; a pad assigned to several
+    // targets feeds all of them. One that fails (e.g. unplugged since the last
+    // scan) is skipped and retried while the loop runs.
+    let source_paths = distinct_paths(&sorted);
+    let source_of: Vec<usize> = sorted
+        .iter()
+        .map(|ra| {
+            source_paths
+                .iter()
+                .position(|p| crate::platform::same_instance_path(p, &ra.instance_path))
+                .unwrap_or_default()
+        })
+        .collect();
+    let mut physical_devices: Vec<Option<evdev::Device>> = Vec::new();
+    for path in &source_paths {
+        match open_and_grab(path) {
+            Ok(device) => physical_devices.push(Some(device)),
             Err(e) => {
-                log::error!("Failed to open {}: {}", ra.instance_path, e);
-                // Release any already-grabbed devices
-                drop(physical_devices);
-                running.store(false, Ordering::SeqCst);
-                return;
+                reporter.device_lost(path, &e);
+                physical_devices.push(None);
             }
-        };
-
-        // EVIOCGRAB — exclusive access, other apps (games) won't see this device
-        if let Err(e) = device.grab() {
-            log::error!("Failed to grab {}: {}", ra.instance_path, e);
-            drop(physical_devices);
-            running.store(false, Ordering::SeqCst);
-            return;
         }
-        log::info!("Grabbed: {} ({})", ra.instance_path, device.name().unwrap_or("?"));
-
-        physical_devices.push(device);
+    }
+    if physical_devices.iter().all(Option::is_none) {
+        reporter.fatal(
+            &running,
+            "None of the assigned devices could be opened".into(),
+        );
+        return;
+    }
+    if reporter.start_cancelled(&running) {
+        return; // Dropping the devices releases their grabs
     }
 
-    // Step 2: Create virtual uinput devices, one per physical device, in slot order
-    let mut virtual_devices: Vec<evdev::uinput::VirtualDevice> = Vec::new();
-    for (i, phys) in physical_devices.iter().enumerate() {
-        let virt_name = format!("PadSwitch Virtual Controller {}", i + 1);
-        let mut builder = VirtualDeviceBuilder::new()
-            .map_err(|e| {
-                log::error!("Failed to create VirtualDeviceBuilder: {}", e);
-            });
-
-        let mut builder = match builder {
-            Ok(b) => b,
-            Err(()) => {
-                drop(virtual_devices);
-                drop(physical_devices);
-                running.store(false, Ordering::SeqCst);
-                return;
-            }
-        };
+    // Per-assignment event mappers (axis overrides, trigger/button remapping)
+    let new_mapper = |ra: &ResolvedAssignment, device: &evdev::Device| {
+        let overrides = options.axis_overrides.get(&ra.device_id);
+        let layout = AxisLayout::new(overrides.map_or(&[][..], Vec::as_slice));
+        EventMapper::new(ra.mapping.clone(), layout, device)
+    };
+    let mut mappers: Vec<Option<EventMapper>> = sorted
+        .iter()
+        .zip(&source_of)
+        .map(|(ra, &s)| physical_devices[s].as_ref().map(|p| new_mapper(ra, p)))
+        .collect();
 
-        builder = builder.name(&virt_name);
-
-        // Copy supported keys from physical device
-        if let Some(keys) = phys.supported_keys() {
-            builder = builder.with_keys(&keys).unwrap_or(builder);
-        }
-
-        // Copy absolute axes with their ranges from physical device
-        if let Some(abs_axes) = phys.supported_absolute_axes() {
-            for axis in abs_axes.iter() {
-                if let Some(info) = phys.get_absinfo(&axis) {
-                    let setup = UinputAbsSetup::new(
-                        axis,
-                        AbsInfo::new(
-                            info.value(),
-                            info.minimum(),
-                            info.maximum(),
-                            info.fuzz(),
-                            info.flat(),
-                            info.resolution(),
-                        ),
-                    );
-                    builder = builder.with_absolute_axis(&setup).unwrap_or(builder);
-                }
+    // Step 2: Create virtual uinput devices, one per target slot, in slot order.
+    // Physical devices sharing a target slot are merged into one virtual device
+    // advertising the union of their capabilities. A target none of whose sources
+    // opened is created once one comes back, rather than as a device with no inputs.
+    let (group_of, group_count) = target_groups(&sorted);
+    let group_members: Vec<Vec<usize>> = (0..group_count)
+        .map(|g| (0..sorted.len()).filter(|&i| group_of[i] == g).collect())
+        .collect();
+    let mut virtual_devices: Vec<Option<evdev::uinput::VirtualDevice>> = Vec::new();
+    let mut neutral_events: Vec<Vec<InputEvent>> = Vec::new();
+    for (g, members) in group_members.iter().enumerate() {
+        match build_virtual_group(g, members, &physical_devices, &source_of, &mappers) {
+            Ok(Some((vd, neutral))) => {
+                virtual_devices.push(Some(vd));
+                neutral_events.push(neutral);
             }
-        }
-
-        match builder.build() {
-            Ok(vd) => {
-                log::info!("Created virtual device: {}", virt_name);
-                virtual_devices.push(vd);
+            Ok(None) => {
+                log::warn!(
+                    "PadSwitch Virtual Controller {} deferred until one of its devices is back",
+                    g + 1
+                );
+                virtual_devices.push(None);
+                neutral_events.push(Vec::new());
             }
-            Err(e) => {
-                log::error!("Failed to build virtual device {}: {}", virt_name, e);
+            Err(message) => {
                 drop(virtual_devices);
                 drop(physical_devices);
-                running.store(false, Ordering::SeqCst);
+                reporter.fatal(&running, message);
                 return;
             }
         }
     }
 
-    log::info!("Force mode (Linux): forwarding loop active — {} devices", sorted.len());
+    if reporter.start_cancelled(&running) {
+        return; // Dropping the virtual devices unplugs them
+    }
+
+    reporter.publish_targets(target_table(&sorted, &group_of, group_count));
+
+    log::info!(
+        "Force mode (Linux): forwarding loop active — {} devices -> {} virtual",
+        source_paths.len(),
+        group_count
+    );
+    reporter.started();
 
     // Step 3: Poll loop — read events from physical devices and forward to virtual devices
     // Use non-blocking reads with short sleep (~1ms) for low latency
-    for phys in &mut physical_devices {
+    for phys in physical_devices.iter_mut().flatten() {
         if let Err(e) = phys.set_nonblocking(true) {
             log::warn!("Failed to set non-blocking on {}: {}", phys.name().unwrap_or("?"), e);
         }
     }
 
+    // Keys each source currently holds, so merged targets only release a button
+    // once no source holds it (buttons OR'd; axes follow the last mover naturally).
+    let mut held_keys: Vec<std::collections::HashSet<u16>> = vec![Default::default(); sorted.len()];
+
+    let mut focus = FocusGate::new(options.focus_exe_names.clone());
+    let mut paused = false;
+    let mut next_rescan = std::time::Instant::now() + RESCAN_INTERVAL;
+    let mut lost: Vec<(usize, String)> = Vec::new();
+    // Raw (unmapped) XInput button bits each source holds, for the reset chord
+    let mut source_buttons: Vec<u16> = vec![0; source_paths.len()];
+    let mut chord = ChordDetector::new(options.reset_chord);
+
     while running.load(Ordering::SeqCst) {
         let mut had_events = false;
 
-        for (i, phys) in physical_devices.iter_mut().enumerate() {
+        // While the game isn't focused, release everything once and drop input
+        let focused = focus.poll();
+        if !focused && !paused {
+            for (g, virt) in virtual_devices.iter_mut().enumerate() {
+                let Some(virt) = virt else {
+                    continue;
+                };
+                if let Err(e) = virt.emit(&neutral_events[g]) {
+                    log::warn!("Failed to neutralize virtual device {}: {}", g, e);
+                }
+            }
+            held_keys.iter_mut().for_each(|held| held.clear());
+        }
+        paused = !focused;
+
+        for (s, phys) in physical_devices.iter_mut().enumerate() {
+            let Some(phys) = phys else {
+                continue;
+            };
             match phys.fetch_events() {
+                Ok(_) if paused => {} // Drained so nothing replays on resume
                 Ok(events) => {
                     let events: Vec<InputEvent> = events.collect();
-                    if !events.is_empty() {
+                    track_buttons(&events, &mut source_buttons[s]);
+                    // Fan the batch out to every assignment this pad feeds
+                    for i in (0..sorted.len()).filter(|&i| source_of[i] == s) {
+                        let Some(mapper) = &mut mappers[i] else {
+                            continue;
+                        };
+                        let events = mapper.map(events.clone());
+                        let events = merge_key_events(events, i, &group_of, &mut held_keys);
+                        if events.is_empty() {
+                            continue;
+                        }
                         had_events = true;
-                        if let Err(e) = virtual_devices[i].emit(&events) {
-                            log::warn!("Failed to emit events to virtual device {}: {}", i, e);
+                        let g = group_of[i];
+                        let Some(virt) = &mut virtual_devices[g] else {
+                            continue;
+                        };
+                        if let Err(e) = virt.emit(&events) {
+                            log::warn!("Failed to emit events to virtual device {}: {}", g, e);
                         }
                     }
                 }
                 Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
                     // No events available — normal for non-blocking
                 }
+                Err(e) if e.raw_os_error() == Some(ENODEV) => lost.push((s, e.to_string())),
                 Err(e) => {
-                    log::warn!("Error reading from physical device {}: {}", i, e);
+                    log::warn!("Error reading from physical device {}: {}", s, e);
+                }
+            }
+        }
+
+        if chord.update(source_buttons.iter().any(|&b| chord.matches(b))) {
+            reporter.reset_chord_fired();
+        }
+
+        // Drop unplugged sources and release whatever they held on each target
+        for (s, reason) in lost.drain(..) {
+            source_buttons[s] = 0;
+            physical_devices[s] = None;
+            for i in (0..sorted.len()).filter(|&i| source_of[i] == s) {
+                mappers[i] = None;
+                let g = group_of[i];
+                let release = release_source(i, &group_of, &mut held_keys, &neutral_events[g]);
+                let Some(virt) = &mut virtual_devices[g] else {
+                    continue;
+                };
+                if let Err(e) = virt.emit(&release) {
+                    log::warn!("Failed to release virtual device {}: {}", g, e);
                 }
             }
+            reporter.device_lost(&source_paths[s], &reason);
+        }
+
+        // Try to get lost devices back (e.g. a pad that was replugged)
+        let now = std::time::Instant::now();
+        if physical_devices.iter().any(Option::is_none) && now >= next_rescan {
+            next_rescan = now + RESCAN_INTERVAL;
+            for (s, path) in source_paths.iter().enumerate() {
+                if physical_devices[s].is_some() {
+                    continue;
+                }
+                let Ok(device) = open_and_grab(path) else {
+                    continue;
+                };
+                if let Err(e) = device.set_nonblocking(true) {
+                    log::warn!("Failed to set non-blocking on {}: {}", path, e);
+                }
+                for (i, ra) in sorted
+                    .iter()
+                    .enumerate()
+                    .filter(|&(i, _)| source_of[i] == s)
+                {
+                    mappers[i] = Some(new_mapper(ra, &device));
+                }
+                physical_devices[s] = Some(device);
+                // Targets deferred at startup get their device now that a source is open
+                for i in (0..sorted.len()).filter(|&i| source_of[i] == s) {
+                    let g = group_of[i];
+                    if virtual_devices[g].is_some() {
+                        continue;
+                    }
+                    match build_virtual_group(
+                        g,
+                        &group_members[g],
+                        &physical_devices,
+                        &source_of,
+                        &mappers,
+                    ) {
+                        Ok(Some((vd, neutral))) => {
+                            virtual_devices[g] = Some(vd);
+                            neutral_events[g] = neutral;
+                        }
+                        Ok(None) => {}
+                        Err(message) => log::warn!("{}", message),
+                    }
+                }
+                reporter.device_restored(path);
+            }
         }
 
         // Sleep briefly to avoid busy-spinning; ~1ms matches the Windows 1000Hz rate
@@ -446,6 +1790,19 @@ fn run_force_forwarding(
 
     log::info!("Force mode (Linux): stopping — releasing devices");
 
+    // Release every input before unplugging (see the Windows loop)
+    if let Some(settle) = options.stop_settle {
+        for (g, virt) in virtual_devices.iter_mut().enumerate() {
+            let Some(virt) = virt else {
+                continue;
+            };
+            if let Err(e) = virt.emit(&neutral_events[g]) {
+                log::warn!("Failed to neutralize virtual device {}: {}", g, e);
+            }
+        }
+        std::thread::sleep(settle);
+    }
+
     // Step 4: Cleanup — dropping virtual_devices unplugs them, dropping physical_devices
     // releases the EVIOCGRAB. Explicit drop for clarity.
     drop(virtual_devices);
@@ -454,14 +1811,295 @@ fn run_force_forwarding(
     log::info!("Force mode (Linux): cleanup complete");
 }
 
+/// Build the virtual device for target group `g` from its open sources. `None`
+/// while none of them is open, since there would be no capabilities to copy.
+#[cfg(target_os = "linux")]
+fn build_virtual_group(
+    g: usize,
+    members: &[usize],
+    sources: &[Option<evdev::Device>],
+    source_of: &[usize],
+    mappers: &[Option<crate::mapping::EventMapper>],
+) -> std::result::Result<Option<(evdev::uinput::VirtualDevice, Vec<evdev::InputEvent>)>, String> {
+    use evdev::{AbsInfo, AbsoluteAxisCode, EventType, InputEvent, KeyCode, UinputAbsSetup};
+
+    if !members.iter().any(|&i| sources[source_of[i]].is_some()) {
+        return Ok(None);
+    }
+    let virt_name = format!("PadSwitch Virtual Controller {}", g + 1);
+    let mut builder = evdev::uinput::VirtualDevice::builder()
+        .map_err(|e| format!("Failed to create VirtualDeviceBuilder: {}", e))?;
+
+    builder = builder.name(&virt_name);
+
+    // Copy supported keys from the physical devices, plus any keys the mappings emit
+    let mut keys = evdev::AttributeSet::<KeyCode>::new();
+    for &i in members {
+        let (Some(phys), Some(mapper)) = (&sources[source_of[i]], &mappers[i]) else {
+            continue;
+        };
+        if let Some(phys_keys) = phys.supported_keys() {
+            for key in phys_keys.iter() {
+                keys.insert(key);
+            }
+        }
+        for key in mapper.extra_keys() {
+            keys.insert(key);
+        }
+    }
+    builder = builder
+        .with_keys(&keys)
+        .map_err(|e| format!("Failed to set keys on {}: {}", virt_name, e))?;
+
+    // Neutral frame for this target: every key up, sticks centred, triggers released
+    let mut neutral: Vec<InputEvent> = keys
+        .iter()
+        .map(|key| InputEvent::new(EventType::KEY.0, key.0, 0))
+        .collect();
+
+    // Copy absolute axes with their ranges, as the mapper re-addresses them
+    // (first source wins for shared axes)
+    let mut added_axes: Vec<AbsoluteAxisCode> = Vec::new();
+    for &i in members {
+        let (Some(phys), Some(mapper)) = (&sources[source_of[i]], &mappers[i]) else {
+            continue;
+        };
+        let Ok(absinfo) = phys.get_absinfo() else {
+            continue;
+        };
+        for (axis, info) in absinfo {
+            let Some(target) = mapper.virtual_axis(axis) else {
+                continue;
+            };
+            if added_axes.contains(&target) {
+                continue;
+            }
+            added_axes.push(target);
+            neutral.push(InputEvent::new(
+                EventType::ABSOLUTE.0,
+                target.0,
+                neutral_axis_value(target, info.minimum(), info.maximum()),
+            ));
+            let setup = UinputAbsSetup::new(
+                target,
+                AbsInfo::new(
+                    info.value(),
+                    info.minimum(),
+                    info.maximum(),
+                    info.fuzz(),
+                    info.flat(),
+                    info.resolution(),
+                ),
+            );
+            builder = builder
+                .with_absolute_axis(&setup)
+                .map_err(|e| format!("Failed to add {:?} to {}: {}", target, virt_name, e))?;
+        }
+    }
+
+    let vd = builder
+        .build()
+        .map_err(|e| format!("Failed to build virtual device {}: {}", virt_name, e))?;
+    log::info!("Created virtual device: {}", virt_name);
+    Ok(Some((vd, neutral)))
+}
+
+/// Each distinct instance path once, in assignment order. One pad may feed several
+/// targets, but it's hidden, grabbed or reordered only once.
+#[cfg_attr(target_os = "macos", allow(dead_code))]
+fn distinct_paths(assignments: &[ResolvedAssignment]) -> Vec<String> {
+    let mut paths: Vec<String> = Vec::new();
+    for ra in assignments {
+        if !paths
+            .iter()
+            .any(|p| crate::platform::same_instance_path(p, &ra.instance_path))
+        {
+            paths.push(ra.instance_path.clone());
+        }
+    }
+    paths
+}
+
+/// Resting value of an absolute axis: triggers and pedals at their minimum, hats at 0,
+/// sticks centred.
+#[cfg(target_os = "linux")]
+fn neutral_axis_value(axis: evdev::AbsoluteAxisCode, minimum: i32, maximum: i32) -> i32 {
+    use evdev::AbsoluteAxisCode as Abs;
+    match axis {
+        Abs::ABS_Z | Abs::ABS_RZ | Abs::ABS_GAS | Abs::ABS_BRAKE => minimum,
+        Abs::ABS_HAT0X | Abs::ABS_HAT0Y => 0,
+        _ => minimum + (maximum - minimum) / 2,
+    }
+}
+
+/// How often the Linux loop retries opening devices it lost or couldn't open.
+#[cfg(target_os = "linux")]
+const RESCAN_INTERVAL: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// Update a source's XInput button bits from its raw key events.
+#[cfg(target_os = "linux")]
+fn track_buttons(events: &[evdev::InputEvent], buttons: &mut u16) {
+    for event in events
+        .iter()
+        .filter(|e| e.event_type() == evdev::EventType::KEY)
+    {
+        let Some(bit) = crate::mapping::EVDEV_BUTTON_MAP
+            .iter()
+            .find(|(key, _)| key.0 == event.code())
+            .map(|(_, bit)| *bit)
+        else {
+            continue;
+        };
+        match event.value() {
+            0 => *buttons &= !bit,
+            1 => *buttons |= bit,
+            _ => {} // Autorepeat
+        }
+    }
+}
+
+/// `read(2)` error for an evdev node whose device was unplugged.
+#[cfg(target_os = "linux")]
+const ENODEV: i32 = 19;
+
+/// Open an evdev node and take it exclusively (EVIOCGRAB), so other apps (games)
+/// no longer see it.
+#[cfg(target_os = "linux")]
+fn open_and_grab(path: &str) -> std::result::Result<evdev::Device, String> {
+    let mut device =
+        evdev::Device::open(path).map_err(|e| format!("Failed to open {}: {}", path, e))?;
+    device
+        .grab()
+        .map_err(|e| format!("Failed to grab {}: {}", path, e))?;
+    log::info!("Grabbed: {} ({})", path, device.name().unwrap_or("?"));
+    Ok(device)
+}
+
+/// Events that release whatever a lost `source` was holding on its target: the
+/// whole neutral frame if it fed the target alone, otherwise only the keys no other
+/// source still holds (axes follow the remaining sources).
+#[cfg(target_os = "linux")]
+fn release_source(
+    source: usize,
+    group_of: &[usize],
+    held_keys: &mut [std::collections::HashSet<u16>],
+    neutral: &[evdev::InputEvent],
+) -> Vec<evdev::InputEvent> {
+    let group = group_of[source];
+    if group_of.iter().filter(|&&g| g == group).count() < 2 {
+        return neutral.to_vec();
+    }
+    let held = std::mem::take(&mut held_keys[source]);
+    held.into_iter()
+        .filter(|code| {
+            !held_keys
+                .iter()
+                .enumerate()
+                .any(|(i, keys)| group_of[i] == group && keys.contains(code))
+        })
+        .map(|code| evdev::InputEvent::new(evdev::EventType::KEY.0, code, 0))
+        .collect()
+}
+
+/// Drop key events from `source` that would contradict another source in the same
+/// merged target (a press while already held, a release while still held).
+#[cfg(target_os = "linux")]
+fn merge_key_events(
+    events: Vec<evdev::InputEvent>,
+    source: usize,
+    group_of: &[usize],
+    held_keys: &mut [std::collections::HashSet<u16>],
+) -> Vec<evdev::InputEvent> {
+    let group = group_of[source];
+    if group_of.iter().filter(|&&g| g == group).count() < 2 {
+        return events;
+    }
+
+    events
+        .into_iter()
+        .filter(|event| {
+            if event.event_type() != evdev::EventType::KEY {
+                return true;
+            }
+            let code = event.code();
+            let held_elsewhere = held_keys
+                .iter()
+                .enumerate()
+                .any(|(i, held)| i != source && group_of[i] == group && held.contains(&code));
+            match event.value() {
+                0 => {
+                    held_keys[source].remove(&code);
+                }
+                _ => {
+                    held_keys[source].insert(code);
+                }
+            }
+            !held_elsewhere
+        })
+        .collect()
+}
+
 #[cfg(target_os = "macos")]
 fn run_force_forwarding(
     running: Arc<AtomicBool>,
+    mut reporter: LoopReporter,
     _manager: Arc<dyn PlatformServices>,
     _assignments: Vec<ResolvedAssignment>,
+    _options: ForwardingOptions,
 ) {
     log::info!("Force mode: stub (macOS)");
+    reporter.started();
     while running.load(Ordering::SeqCst) {
         std::thread::sleep(std::time::Duration::from_millis(500));
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::device::{DeviceType, PhysicalDevice};
+    use crate::platform::mock::MockPlatform;
+
+    fn pad(path: &str, slot: u32) -> PhysicalDevice {
+        let mut device = PhysicalDevice::new(path.into(), path.into(), DeviceType::XInput);
+        device.xinput_slot = Some(slot);
+        device
+    }
+
+    fn source(path: &str, slot: u32, passthrough: bool) -> ResolvedAssignment {
+        ResolvedAssignment {
+            device_id: path.into(),
+            instance_path: path.into(),
+            xinput_slot: Some(slot),
+            target_slot: 3,
+            mapping: InputMapping::default(),
+            passthrough,
+            routing_mode: Some(RoutingMode::Force),
+        }
+    }
+
+    #[test]
+    fn hybrid_force_sources_follow_a_minimal_reorder() {
+        let mock = MockPlatform::new(vec![pad("HID\\PAD_A", 0), pad("HID\\PAD_B", 1)]);
+        let mut force = vec![
+            source("HID\\PAD_B", 1, false),
+            source("hid\\pad_a", 0, true),
+        ];
+
+        // The Minimal half re-enabled PAD_B first, so it now sits in slot 0
+        mock.set_devices(vec![pad("HID\\PAD_B", 0), pad("HID\\PAD_A", 1)]);
+        refresh_source_slots(&mock, &mut force).unwrap();
+
+        assert_eq!(force[0].xinput_slot, Some(0));
+        assert_eq!(force[0].target_slot, 3);
+        assert_eq!(force[1].xinput_slot, Some(1));
+        assert_eq!(force[1].target_slot, 1, "passthrough follows its device");
+
+        mock.set_devices(vec![pad("HID\\PAD_A", 0)]);
+        refresh_source_slots(&mock, &mut force).unwrap();
+        assert_eq!(
+            force[0].xinput_slot, None,
+            "unplugged sources lose their slot"
+        );
+    }
+}
@@ -1,50 +1,81 @@
+mod audit;
 mod commands;
 mod config;
+mod conflicts;
+mod control_server;
 mod device;
+mod device_refresh;
+mod diagnostics;
 mod error;
+mod hid;
 mod hidhide;
+mod identify;
 mod input_loop;
+mod mapping;
 mod platform;
 mod process_watcher;
+mod recording;
 mod setupdi;
+mod slot_preview;
 mod state;
 mod tray;
 mod vigem;
+mod watchdog;
 
 use state::AppState;
-use tauri::Manager;
+use tauri::{Emitter, Manager};
 
 /// Path to the lockfile used to detect dirty shutdowns.
 fn lockfile_path() -> Option<std::path::PathBuf> {
-    dirs::config_dir().map(|d| d.join("padswitch").join("padswitch.lock"))
+    config::data_dir().map(|d| d.join("padswitch.lock"))
 }
 
-/// Check if previous session ended dirty (lockfile exists) and perform recovery reset.
+/// Command-line flag that starts without auto-start behaviors, for recovering
+/// from a crash loop caused by bad config or an auto-started watcher/forwarding.
+const SAFE_MODE_FLAG: &str = "--safe-mode";
+
+/// Check if previous session ended dirty (lockfile exists) and perform recovery reset,
+/// unless `auto_recover_on_dirty_shutdown` is off: then only emit
+/// `dirty-shutdown-detected` so the UI can offer to recover.
+/// In safe mode only the HidHide state is undone; devices aren't re-enabled and the
+/// active profile is kept. Persistently-hidden devices stay hidden unless in safe mode.
 fn check_dirty_shutdown(app: &tauri::AppHandle) {
     let Some(path) = lockfile_path() else { return };
     if !path.exists() {
         return;
     }
-    log::warn!("Dirty shutdown detected — running automatic reset");
-
     // Remove stale lockfile first
     let _ = std::fs::remove_file(&path);
 
-    // Re-enable and unhide all devices that were known last session.
-    // We need to enumerate fresh devices since state.devices is empty at startup.
     let state = app.state::<AppState>();
-    let manager = state.manager().clone();
-
-    // Try to enumerate current devices and re-enable/unhide each
-    if let Ok(devices) = manager.enumerate_devices() {
-        for dev in &devices {
-            let _ = manager.enable_device(&dev.instance_path);
-            let _ = manager.unhide_device(&dev.instance_path);
-        }
+    let safe_mode = state.safe_mode();
+    if !safe_mode
+        && !state
+            .lock_inner()
+            .config
+            .settings
+            .auto_recover_on_dirty_shutdown
+    {
+        log::warn!("Dirty shutdown detected — automatic recovery is off");
+        let _ = app.emit("dirty-shutdown-detected", serde_json::json!({}));
+        return;
     }
+    log::warn!("Dirty shutdown detected — running automatic reset");
+    let _caller = audit::caller("recovery");
+
+    // Re-enable and unhide everything, then deactivate HidHide globally (persistent
+    // hides re-activate it after recovery)
+    let keep_hidden = if safe_mode {
+        vec![]
+    } else {
+        state.lock_inner().config.persistent_hidden.clone()
+    };
+    state.recover_devices(&keep_hidden);
 
-    // Deactivate HidHide globally
-    let _ = manager.deactivate_hiding();
+    if safe_mode {
+        log::info!("Dirty shutdown recovery complete (safe mode: unhide only)");
+        return;
+    }
 
     // Clear active profile (it may reference a state that was mid-operation)
     let mut inner = state.lock_inner();
@@ -54,8 +85,85 @@ fn check_dirty_shutdown(app: &tauri::AppHandle) {
     log::info!("Dirty shutdown recovery complete");
 }
 
+/// Warn at launch about saved profiles that can't start without admin (Minimal mode,
+/// or hard hide) when this instance isn't elevated, so the UI can offer
+/// `relaunch_elevated` before the user hits the failure in `start_forwarding`.
+fn warn_if_elevation_needed(app: &tauri::AppHandle) {
+    if platform::is_elevated() {
+        return;
+    }
+    let state = app.state::<AppState>();
+    let profiles: Vec<serde_json::Value> = {
+        let inner = state.lock_inner();
+        inner
+            .config
+            .profiles
+            .iter()
+            .filter(|p| {
+                let options = input_loop::ForwardingOptions::from_profile(p);
+                p.assignments
+                    .iter()
+                    .filter_map(|a| a.routing_mode.as_ref())
+                    .chain(std::iter::once(&p.routing_mode))
+                    .any(|mode| platform::requires_elevation(mode, &options))
+            })
+            .map(|p| serde_json::json!({ "id": p.id, "name": p.name }))
+            .collect()
+    };
+    if profiles.is_empty() {
+        return;
+    }
+    log::warn!(
+        "{} profile(s) need administrator rights and PadSwitch isn't elevated",
+        profiles.len()
+    );
+    let _ = app.emit(
+        "needs-elevation-warning",
+        serde_json::json!({ "profiles": profiles }),
+    );
+}
+
+/// Start forwarding the active profile at launch (`auto_forward_on_launch`).
+/// Runs after recovery, so the profile may have been cleared. Failures (no profile,
+/// unresolvable assignments, missing drivers or elevation) are logged and reported
+/// as `forwarding-status { active: false, error }` instead of aborting startup.
+fn auto_forward_on_launch(app: &tauri::AppHandle) {
+    match start_active_profile(app) {
+        Ok(()) => {
+            log::info!("Auto-forward on launch: forwarding started");
+            let _ = app.emit("forwarding-status", serde_json::json!({ "active": true }));
+            device_refresh::schedule(app);
+        }
+        Err(e) => {
+            log::warn!("Auto-forward on launch skipped: {}", e);
+            app.state::<AppState>()
+                .record_background_error("auto-forward", &e.to_string());
+            let _ = app.emit(
+                "forwarding-status",
+                serde_json::json!({ "active": false, "error": e.to_string() }),
+            );
+        }
+    }
+}
+
+/// Load the active profile's assignments against a fresh enumeration and start it.
+fn start_active_profile(app: &tauri::AppHandle) -> error::Result<()> {
+    let state = app.state::<AppState>();
+    let manager = state.manager().clone();
+    let devices = manager.enumerate_devices()?;
+
+    let mut inner = state.lock_inner();
+    let assignments = inner
+        .active_profile()
+        .map(|p| p.assignments.clone())
+        .ok_or_else(|| error::PadSwitchError::Forwarding("No active profile to forward".into()))?;
+    inner.devices = devices;
+    inner.assignments = assignments;
+    inner.start_forwarding(manager, app)
+}
+
 /// Create the lockfile (marks session as "in progress").
-fn create_lockfile() {
+pub(crate) fn create_lockfile() {
     if let Some(path) = lockfile_path() {
         if let Some(parent) = path.parent() {
             let _ = std::fs::create_dir_all(parent);
@@ -65,45 +173,131 @@ fn create_lockfile() {
 }
 
 /// Remove the lockfile (marks clean shutdown).
-fn remove_lockfile() {
+pub(crate) fn remove_lockfile() {
     if let Some(path) = lockfile_path() {
         let _ = std::fs::remove_file(&path);
     }
 }
 
+/// Clean exit: run the shutdown sequence, then mark it clean by removing the
+/// lockfile. Safe to call more than once.
+pub(crate) fn shutdown(app: &tauri::AppHandle) {
+    if let Some(state) = app.try_state::<AppState>() {
+        state.shutdown();
+    }
+    remove_lockfile();
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     env_logger::init();
 
-    let manager = platform::create_platform();
+    // Watchdog child spawned by Minimal mode: no UI, just watch the heartbeat
+    let args: Vec<String> = std::env::args().collect();
+    if let Some(pos) = args.iter().position(|a| a == watchdog::WATCHDOG_FLAG) {
+        watchdog::run(&args[pos + 1..]);
+        return;
+    }
+
+    let safe_mode = std::env::args().any(|arg| arg == SAFE_MODE_FLAG);
+    if safe_mode {
+        log::warn!("Safe mode: skipping auto-start behaviors");
+    }
+
+    let config = config::AppConfig::load().unwrap_or_default();
+    audit::set_enabled(config.settings.audit_log_enabled);
+    let manager = platform::create_platform(&config.settings);
+    manager.set_axis_overrides(config.axis_overrides.clone());
+    manager.set_device_selection(platform::DeviceSelection::from_config(&config));
 
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
-        .manage(AppState::new(manager))
+        .plugin(tauri_plugin_clipboard_manager::init())
+        .manage(AppState::new(manager, config, safe_mode))
         .invoke_handler(tauri::generate_handler![
             commands::get_connected_devices,
+            commands::refresh_devices,
             commands::check_driver_status,
+            commands::detect_conflicts,
+            commands::read_raw_report,
+            commands::get_device_capabilities,
+            commands::device_supports_rumble,
+            commands::get_hide_candidates,
+            commands::get_hidhide_active,
+            commands::set_hidhide_active,
             commands::toggle_device,
             commands::apply_assignments,
+            commands::get_resolved_assignments,
+            commands::preview_hidden_devices,
+            commands::rebind_slot,
+            commands::get_virtual_controllers,
+            commands::measure_latency,
             commands::start_forwarding,
             commands::stop_forwarding,
+            commands::cancel_start_forwarding,
             commands::is_forwarding,
             commands::get_profiles,
+            commands::get_profiles_with_status,
+            commands::profiles_using_device,
             commands::save_profile,
             commands::delete_profile,
             commands::activate_profile,
+            commands::set_profile_routing_mode,
+            commands::apply_remap_preset,
+            commands::set_axis_overrides,
+            commands::get_virtual_layouts,
+            commands::save_virtual_layout,
+            commands::delete_virtual_layout,
+            commands::set_profile_virtual_layout,
             commands::is_elevated,
+            commands::get_app_info,
+            commands::get_backend_description,
+            commands::dump_device_slot_table,
+            commands::collect_diagnostics,
+            commands::copy_diagnostics_to_clipboard,
+            commands::read_audit_log,
+            commands::get_audit_log_path,
+            commands::is_safe_mode,
+            commands::get_last_background_error,
+            commands::get_effective_routing_mode,
+            commands::changes_require_restart,
+            commands::plan_requires_elevation,
+            commands::relaunch_elevated,
             commands::detect_xinput_slot,
             commands::confirm_device_slot,
+            commands::pulse_slot,
+            commands::identify_all,
+            commands::test_virtual_rumble,
+            commands::cancel_identify,
+            commands::start_all_slots_preview,
+            commands::stop_all_slots_preview,
+            commands::record_input,
+            commands::replay_input,
             commands::get_game_rules,
             commands::add_game_rule,
             commands::delete_game_rule,
             commands::toggle_game_rule,
+            commands::set_game_rule_delay,
+            commands::move_game_rule,
+            commands::duplicate_game_rule,
             commands::start_process_watcher,
             commands::stop_process_watcher,
+            commands::set_auto_switch,
             commands::is_watcher_running,
+            commands::reset_device,
+            commands::get_input_devices,
+            commands::get_marked_controllers,
+            commands::mark_as_controller,
+            commands::get_persistent_hidden,
+            commands::add_persistent_hide,
+            commands::remove_persistent_hide,
             commands::reset_all,
+            commands::recover_now,
             commands::get_settings,
+            commands::get_config_schema,
+            commands::validate_config_file,
+            commands::backup_config,
+            commands::restore_config,
             commands::update_settings,
         ])
         .setup(|app| {
@@ -115,20 +309,41 @@ pub fn run() {
             // Mark this session as active
             create_lockfile();
 
-            // Auto-start process watcher if enabled in settings
+            // Tell the UI up front which profiles will need admin
+            warn_if_elevation_needed(app.handle());
+
+            // Auto-start behaviors are skipped entirely in safe mode
             let state = app.state::<AppState>();
+            if state.safe_mode() {
+                return Ok(());
+            }
+
+            // Re-apply devices the user chose to keep hidden across sessions
+            let persistent_hidden = state.lock_inner().config.persistent_hidden.clone();
+            platform::apply_persistent_hides(&**state.manager(), &persistent_hidden);
+
+            // Auto-start process watcher if enabled in settings
             let auto_switch = state.lock_inner().config.settings.auto_switch;
             if auto_switch {
                 state.lock_watcher().start(app.handle().clone());
             }
 
+            // Start the local control endpoint if enabled in settings
+            control_server::sync_with_settings(app.handle());
+
+            // Start forwarding the active profile if enabled in settings
+            let auto_forward = state.lock_inner().config.settings.auto_forward_on_launch;
+            if auto_forward {
+                auto_forward_on_launch(app.handle());
+            }
+
             Ok(())
         })
         .build(tauri::generate_context!())
         .expect("error while building tauri application")
-        .run(|_app, event| {
+        .run(|app, event| {
             if let tauri::RunEvent::Exit = event {
-                remove_lockfile();
+                shutdown(app);
             }
         });
 }
@@ -0,0 +1,1123 @@
+use crate::device::GamepadState;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// XInput button bits (matching Windows XINPUT_GAMEPAD_*).
+pub mod buttons {
+    pub const DPAD_UP: u16 = 0x0001;
+    pub const DPAD_DOWN: u16 = 0x0002;
+    pub const DPAD_LEFT: u16 = 0x0004;
+    pub const DPAD_RIGHT: u16 = 0x0008;
+    pub const START: u16 = 0x0010;
+    pub const BACK: u16 = 0x0020;
+    pub const LEFT_THUMB: u16 = 0x0040;
+    pub const RIGHT_THUMB: u16 = 0x0080;
+    pub const LEFT_SHOULDER: u16 = 0x0100;
+    pub const RIGHT_SHOULDER: u16 = 0x0200;
+    /// Only reported by the undocumented `XInputGetStateEx` (see `forward_guide_button`).
+    pub const GUIDE: u16 = 0x0400;
+    pub const A: u16 = 0x1000;
+    pub const B: u16 = 0x2000;
+    pub const X: u16 = 0x4000;
+    pub const Y: u16 = 0x8000;
+
+    /// Display names for each button bit, in XInput order.
+    pub const NAMES: [(u16, &str); 15] = [
+        (DPAD_UP, "DPadUp"),
+        (DPAD_DOWN, "DPadDown"),
+        (DPAD_LEFT, "DPadLeft"),
+        (DPAD_RIGHT, "DPadRight"),
+        (START, "Start"),
+        (BACK, "Back"),
+        (LEFT_THUMB, "LeftThumb"),
+        (RIGHT_THUMB, "RightThumb"),
+        (LEFT_SHOULDER, "LeftShoulder"),
+        (RIGHT_SHOULDER, "RightShoulder"),
+        (GUIDE, "Guide"),
+        (A, "A"),
+        (B, "B"),
+        (X, "X"),
+        (Y, "Y"),
+    ];
+
+    /// Display name of a single button bit.
+    pub fn name(button: u16) -> Option<&'static str> {
+        NAMES
+            .iter()
+            .find(|(bit, _)| *bit == button)
+            .map(|(_, n)| *n)
+    }
+}
+
+/// Axis names used by `DeviceCapabilities`, matching the `GamepadState` fields.
+pub const XINPUT_AXES: [&str; 6] = [
+    "LeftStickX",
+    "LeftStickY",
+    "RightStickX",
+    "RightStickY",
+    "LeftTrigger",
+    "RightTrigger",
+];
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub enum Trigger {
+    Left,
+    Right,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub enum Stick {
+    Left,
+    Right,
+}
+
+/// One of the six gamepad axes, in `XINPUT_AXES` order.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub enum GamepadAxis {
+    LeftStickX,
+    LeftStickY,
+    RightStickX,
+    RightStickY,
+    LeftTrigger,
+    RightTrigger,
+}
+
+/// Linux: read a raw evdev axis as a gamepad axis, for controllers (arcade sticks,
+/// odd pads) that don't use the standard layout. The target axis then only takes
+/// input from this source; a source axis that is overridden stops feeding its
+/// standard axis.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct AxisOverride {
+    /// Raw `ABS_*` code (e.g. 2 for `ABS_Z`).
+    pub source: u16,
+    pub target: GamepadAxis,
+}
+
+/// Press an output button while a source trigger is at or past `threshold`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct TriggerToButton {
+    pub trigger: Trigger,
+    /// XInput button bit to press (see `buttons`).
+    pub button: u16,
+    /// Trigger value (0-255) at which the button counts as pressed.
+    #[serde(default = "default_trigger_threshold")]
+    pub threshold: u8,
+}
+
+/// Fully press an output trigger while a source button is held.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct ButtonToTrigger {
+    /// XInput button bit to read (see `buttons`).
+    pub button: u16,
+    pub trigger: Trigger,
+}
+
+/// Report a physical button as a different output button.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct ButtonRemap {
+    /// XInput button bit to read (see `buttons`).
+    pub from: u16,
+    /// XInput button bit to press instead.
+    pub to: u16,
+}
+
+/// Canned button layouts for controllers whose face buttons are labelled
+/// differently from an Xbox pad.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub enum RemapPreset {
+    /// No remapping: positions match the Xbox layout.
+    Xbox,
+    /// Nintendo labels: A and B, and X and Y, sit swapped relative to Xbox, so the
+    /// button printed "A" acts as Xbox A.
+    Nintendo,
+    /// PlayStation pads where the east button (circle) confirms, as on Japanese
+    /// consoles: A and B are swapped, X and Y are left alone.
+    PlayStationCircleConfirm,
+}
+
+impl RemapPreset {
+    /// The button remap table this preset stands for.
+    pub fn button_remap(self) -> Vec<ButtonRemap> {
+        let swap = |a: u16, b: u16| {
+            [
+                ButtonRemap { from: a, to: b },
+                ButtonRemap { from: b, to: a },
+            ]
+        };
+        match self {
+            RemapPreset::Xbox => vec![],
+            RemapPreset::Nintendo => swap(buttons::A, buttons::B)
+                .into_iter()
+                .chain(swap(buttons::X, buttons::Y))
+                .collect(),
+            RemapPreset::PlayStationCircleConfirm => swap(buttons::A, buttons::B).to_vec(),
+        }
+    }
+}
+
+/// Which groups of inputs an assignment forwards. Masked groups are sent as
+/// neutral (released, centred, zero).
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct InputMask {
+    /// Face buttons, shoulders, Start/Back/Guide and stick clicks.
+    #[serde(default = "default_true")]
+    pub buttons: bool,
+    #[serde(default = "default_true")]
+    pub dpad: bool,
+    #[serde(default = "default_true")]
+    pub left_stick: bool,
+    #[serde(default = "default_true")]
+    pub right_stick: bool,
+    #[serde(default = "default_true")]
+    pub triggers: bool,
+}
+
+impl Default for InputMask {
+    fn default() -> Self {
+        Self {
+            buttons: true,
+            dpad: true,
+            left_stick: true,
+            right_stick: true,
+            triggers: true,
+        }
+    }
+}
+
+impl InputMask {
+    const DPAD: u16 =
+        buttons::DPAD_UP | buttons::DPAD_DOWN | buttons::DPAD_LEFT | buttons::DPAD_RIGHT;
+
+    /// Whether this mask forwards the given button bit.
+    pub fn forwards_button(&self, button: u16) -> bool {
+        if button & Self::DPAD != 0 {
+            self.dpad
+        } else {
+            self.buttons
+        }
+    }
+
+    /// Neutralize the masked groups of a state.
+    pub fn apply(&self, state: &mut GamepadState) {
+        if !self.buttons {
+            state.buttons &= Self::DPAD;
+        }
+        if !self.dpad {
+            state.buttons &= !Self::DPAD;
+        }
+        if !self.left_stick {
+            (state.thumb_lx, state.thumb_ly) = (0, 0);
+        }
+        if !self.right_stick {
+            (state.thumb_rx, state.thumb_ry) = (0, 0);
+        }
+        if !self.triggers {
+            (state.left_trigger, state.right_trigger) = (0, 0);
+        }
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_trigger_threshold() -> u8 {
+    128
+}
+
+fn default_trigger_output_max() -> u8 {
+    u8::MAX
+}
+
+/// Per-assignment input mapping, applied between reading the physical controller
+/// and writing the virtual one (Force mode only). Identity by default.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct InputMapping {
+    #[serde(default)]
+    pub trigger_to_button: Vec<TriggerToButton>,
+    #[serde(default)]
+    pub button_to_trigger: Vec<ButtonToTrigger>,
+    /// Button-to-button remaps. Buttons not listed as a source pass through.
+    #[serde(default)]
+    pub button_remap: Vec<ButtonRemap>,
+    /// Left trigger values at or below this read as 0; the rest is rescaled to 0-255.
+    #[serde(default)]
+    pub left_trigger_deadzone: u8,
+    /// Right trigger values at or below this read as 0; the rest is rescaled to 0-255.
+    #[serde(default)]
+    pub right_trigger_deadzone: u8,
+    /// Report the left trigger as `255 - value`, for pedals that read full at rest.
+    /// Applied before the deadzone, so the deadzone covers the pedal's resting end.
+    #[serde(default)]
+    pub invert_left_trigger: bool,
+    /// Report the right trigger as `255 - value`. Applied before the deadzone.
+    #[serde(default)]
+    pub invert_right_trigger: bool,
+    /// Lower bound on trigger output.
+    #[serde(default)]
+    pub trigger_output_min: u8,
+    /// Upper bound on trigger output.
+    #[serde(default = "default_trigger_output_max")]
+    pub trigger_output_max: u8,
+    /// Radial left stick deadzone (0-32767): smaller deflections read as centred and
+    /// the rest of the travel is rescaled to the full range.
+    #[serde(default)]
+    pub left_stick_deadzone: u16,
+    /// Radial right stick deadzone (0-32767).
+    #[serde(default)]
+    pub right_stick_deadzone: u16,
+    /// Minimum left stick magnitude (0-32767) for any input past the deadzone, to get
+    /// past a game's own large deadzone. The rest of the travel is rescaled above it.
+    #[serde(default)]
+    pub left_anti_deadzone: u16,
+    /// Minimum right stick magnitude (0-32767) for any nonzero input.
+    #[serde(default)]
+    pub right_anti_deadzone: u16,
+    /// Input groups to forward; the rest reach the virtual controller as neutral.
+    #[serde(default)]
+    pub input_mask: InputMask,
+}
+
+impl Default for InputMapping {
+    fn default() -> Self {
+        Self {
+            trigger_to_button: vec![],
+            button_to_trigger: vec![],
+            button_remap: vec![],
+            left_trigger_deadzone: 0,
+            right_trigger_deadzone: 0,
+            invert_left_trigger: false,
+            invert_right_trigger: false,
+            trigger_output_min: 0,
+            trigger_output_max: default_trigger_output_max(),
+            left_stick_deadzone: 0,
+            right_stick_deadzone: 0,
+            left_anti_deadzone: 0,
+            right_anti_deadzone: 0,
+            input_mask: InputMask::default(),
+        }
+    }
+}
+
+impl InputMapping {
+    /// True if applying this mapping never changes the input.
+    pub fn is_identity(&self) -> bool {
+        self.trigger_to_button.is_empty()
+            && self.button_to_trigger.is_empty()
+            && self.button_remap.iter().all(|r| r.from == r.to)
+            && self.left_trigger_deadzone == 0
+            && self.right_trigger_deadzone == 0
+            && !self.invert_left_trigger
+            && !self.invert_right_trigger
+            && self.trigger_output_min == 0
+            && self.trigger_output_max == u8::MAX
+            && self.left_stick_deadzone == 0
+            && self.right_stick_deadzone == 0
+            && self.left_anti_deadzone == 0
+            && self.right_anti_deadzone == 0
+            && self.input_mask == InputMask::default()
+    }
+
+    fn trigger_deadzone(&self, trigger: Trigger) -> u8 {
+        match trigger {
+            Trigger::Left => self.left_trigger_deadzone,
+            Trigger::Right => self.right_trigger_deadzone,
+        }
+    }
+
+    fn trigger_inverted(&self, trigger: Trigger) -> bool {
+        match trigger {
+            Trigger::Left => self.invert_left_trigger,
+            Trigger::Right => self.invert_right_trigger,
+        }
+    }
+
+    /// Apply the inversion, then the trigger deadzone, rescaling the remaining travel
+    /// to the full 0-255 range. Output clamps come later (`clamp_trigger`).
+    pub fn shape_trigger(&self, trigger: Trigger, value: u8) -> u8 {
+        let value = if self.trigger_inverted(trigger) {
+            u8::MAX - value
+        } else {
+            value
+        };
+        self.trigger_past_deadzone(trigger, value)
+    }
+
+    fn trigger_past_deadzone(&self, trigger: Trigger, value: u8) -> u8 {
+        let deadzone = self.trigger_deadzone(trigger);
+        if value <= deadzone {
+            return 0;
+        }
+        if deadzone == 0 {
+            return value;
+        }
+        let travel = (value - deadzone) as u32;
+        let span = (u8::MAX - deadzone) as u32;
+        ((travel * u8::MAX as u32 + span / 2) / span) as u8
+    }
+
+    /// Deadzone and anti-deadzone of one stick.
+    fn stick_zones(&self, stick: Stick) -> (u16, u16) {
+        match stick {
+            Stick::Left => (self.left_stick_deadzone, self.left_anti_deadzone),
+            Stick::Right => (self.right_stick_deadzone, self.right_anti_deadzone),
+        }
+    }
+
+    /// Whether `shape_stick` can change this stick's values.
+    pub fn shapes_stick(&self, stick: Stick) -> bool {
+        self.stick_zones(stick) != (0, 0)
+    }
+
+    /// Apply the radial stick deadzone, then the anti-deadzone. Deflection inside the
+    /// deadzone reads as centred; the rest is rescaled so it starts at the
+    /// anti-deadzone and reaches full at full deflection. Direction is preserved and
+    /// the output magnitude never decreases as the input grows.
+    pub fn shape_stick(&self, stick: Stick, x: i16, y: i16) -> (i16, i16) {
+        const FULL: f64 = i16::MAX as f64;
+        let (deadzone, anti) = self.stick_zones(stick);
+        let deadzone = (deadzone as f64).min(FULL - 1.0);
+        let anti = (anti as f64).min(FULL);
+        let magnitude = (x as f64).hypot(y as f64);
+        if magnitude <= deadzone {
+            return (0, 0);
+        }
+        if deadzone == 0.0 && anti == 0.0 {
+            return (x, y);
+        }
+        let travel = (magnitude.min(FULL) - deadzone) / (FULL - deadzone);
+        let shaped = anti + travel * (FULL - anti);
+        let scale = shaped / magnitude;
+        let axis = |v: i16| (v as f64 * scale).round().clamp(i16::MIN as f64, FULL) as i16;
+        (axis(x), axis(y))
+    }
+
+    /// Clamp a final trigger value to the configured output range.
+    pub fn clamp_trigger(&self, value: u8) -> u8 {
+        let max = self.trigger_output_max.max(self.trigger_output_min);
+        value.clamp(self.trigger_output_min, max)
+    }
+
+    /// Output button for a physical one, after `button_remap`.
+    pub fn remap_button(&self, button: u16) -> u16 {
+        self.button_remap
+            .iter()
+            .find(|r| r.from == button)
+            .map_or(button, |r| r.to)
+    }
+
+    /// Apply `button_remap` to a whole button mask. Sources are read from `pressed`
+    /// before any target is set, so swaps work in both directions.
+    pub fn remap_buttons(&self, pressed: u16) -> u16 {
+        if self.button_remap.is_empty() {
+            return pressed;
+        }
+        (0..16)
+            .map(|i| 1u16 << i)
+            .filter(|bit| pressed & bit != 0)
+            .fold(0, |out, bit| out | self.remap_button(bit))
+    }
+
+    /// Apply the mapping to a gamepad state read from the physical controller.
+    /// Order of operations: trigger deadzone → trigger inversion (and stick
+    /// deadzone → anti-deadzone); bindings are evaluated against that shaped source
+    /// state; output clamps are applied, then the input mask neutralizes whatever
+    /// isn't forwarded.
+    pub fn apply(&self, state: &mut GamepadState) {
+        state.left_trigger = self.shape_trigger(Trigger::Left, state.left_trigger);
+        state.right_trigger = self.shape_trigger(Trigger::Right, state.right_trigger);
+        (state.thumb_lx, state.thumb_ly) =
+            self.shape_stick(Stick::Left, state.thumb_lx, state.thumb_ly);
+        (state.thumb_rx, state.thumb_ry) =
+            self.shape_stick(Stick::Right, state.thumb_rx, state.thumb_ry);
+        let source = state.clone();
+        state.buttons = self.remap_buttons(source.buttons);
+
+        for binding in &self.trigger_to_button {
+            if source.trigger(binding.trigger) >= binding.threshold {
+                state.buttons |= binding.button;
+            }
+        }
+
+        for binding in &self.button_to_trigger {
+            if source.buttons & binding.button != 0 {
+                *state.trigger_mut(binding.trigger) = u8::MAX;
+            }
+        }
+
+        state.left_trigger = self.clamp_trigger(state.left_trigger);
+        state.right_trigger = self.clamp_trigger(state.right_trigger);
+        self.input_mask.apply(state);
+    }
+}
+
+impl GamepadState {
+    /// Every XInput button by display name (see `buttons::NAMES`) and whether it's
+    /// held, for UIs that shouldn't decode the bitmask themselves.
+    pub fn button_states(&self) -> BTreeMap<&'static str, bool> {
+        buttons::NAMES
+            .iter()
+            .map(|(bit, name)| (*name, self.buttons & bit != 0))
+            .collect()
+    }
+
+    pub fn trigger(&self, trigger: Trigger) -> u8 {
+        match trigger {
+            Trigger::Left => self.left_trigger,
+            Trigger::Right => self.right_trigger,
+        }
+    }
+
+    pub fn trigger_mut(&mut self, trigger: Trigger) -> &mut u8 {
+        match trigger {
+            Trigger::Left => &mut self.left_trigger,
+            Trigger::Right => &mut self.right_trigger,
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Linux: the input loop forwards raw evdev events, so the mapping is applied
+// per event instead of on a GamepadState.
+// ---------------------------------------------------------------------------
+
+#[cfg(target_os = "linux")]
+pub use evdev_mapping::{AxisLayout, EventMapper, EVDEV_BUTTON_MAP};
+
+#[cfg(target_os = "linux")]
+mod evdev_mapping {
+    use super::{buttons, AxisOverride, GamepadAxis, InputMapping, Stick, Trigger};
+    use evdev::{AbsoluteAxisCode, EventType, InputEvent, KeyCode};
+
+    /// Standard gamepad evdev keys and their XInput button bits.
+    pub const EVDEV_BUTTON_MAP: [(KeyCode, u16); 14] = [
+        (KeyCode::BTN_SOUTH, buttons::A),
+        (KeyCode::BTN_EAST, buttons::B),
+        (KeyCode::BTN_WEST, buttons::X),
+        (KeyCode::BTN_NORTH, buttons::Y),
+        (KeyCode::BTN_TL, buttons::LEFT_SHOULDER),
+        (KeyCode::BTN_TR, buttons::RIGHT_SHOULDER),
+        (KeyCode::BTN_SELECT, buttons::BACK),
+        (KeyCode::BTN_START, buttons::START),
+        (KeyCode::BTN_THUMBL, buttons::LEFT_THUMB),
+        (KeyCode::BTN_THUMBR, buttons::RIGHT_THUMB),
+        (KeyCode::BTN_DPAD_UP, buttons::DPAD_UP),
+        (KeyCode::BTN_DPAD_DOWN, buttons::DPAD_DOWN),
+        (KeyCode::BTN_DPAD_LEFT, buttons::DPAD_LEFT),
+        (KeyCode::BTN_DPAD_RIGHT, buttons::DPAD_RIGHT),
+    ];
+
+    fn gamepad_axis_code(axis: GamepadAxis) -> AbsoluteAxisCode {
+        match axis {
+            GamepadAxis::LeftStickX => AbsoluteAxisCode::ABS_X,
+            GamepadAxis::LeftStickY => AbsoluteAxisCode::ABS_Y,
+            GamepadAxis::RightStickX => AbsoluteAxisCode::ABS_RX,
+            GamepadAxis::RightStickY => AbsoluteAxisCode::ABS_RY,
+            GamepadAxis::LeftTrigger => AbsoluteAxisCode::ABS_Z,
+            GamepadAxis::RightTrigger => AbsoluteAxisCode::ABS_RZ,
+        }
+    }
+
+    /// A device's axis layout: the standard one, adjusted by its `AxisOverride`s.
+    #[derive(Debug, Clone, Default)]
+    pub struct AxisLayout {
+        /// (source, target) pairs; one per target (the first override wins).
+        moves: Vec<(AbsoluteAxisCode, AbsoluteAxisCode)>,
+    }
+
+    impl AxisLayout {
+        pub fn new(overrides: &[AxisOverride]) -> Self {
+            let mut moves: Vec<(AbsoluteAxisCode, AbsoluteAxisCode)> = Vec::new();
+            for o in overrides {
+                let target = gamepad_axis_code(o.target);
+                if !moves.iter().any(|(_, t)| *t == target) {
+                    moves.push((AbsoluteAxisCode(o.source), target));
+                }
+            }
+            Self { moves }
+        }
+
+        /// The axis a raw source axis is forwarded as, or `None` if it's dropped
+        /// (its standard target is fed by an override instead).
+        pub fn target_of(&self, source: AbsoluteAxisCode) -> Option<AbsoluteAxisCode> {
+            if let Some((_, target)) = self.moves.iter().find(|(s, _)| *s == source) {
+                return Some(*target);
+            }
+            if self.moves.iter().any(|(_, t)| *t == source) {
+                return None;
+            }
+            Some(source)
+        }
+
+        /// The raw axis that feeds `target`, or `None` if nothing does.
+        pub fn source_of(&self, target: AbsoluteAxisCode) -> Option<AbsoluteAxisCode> {
+            if let Some((source, _)) = self.moves.iter().find(|(_, t)| *t == target) {
+                return Some(*source);
+            }
+            if self.moves.iter().any(|(s, _)| *s == target) {
+                return None;
+            }
+            Some(target)
+        }
+
+        /// Re-address absolute axis events from the device's layout to the standard one.
+        pub fn remap(&self, events: Vec<InputEvent>) -> Vec<InputEvent> {
+            if self.moves.is_empty() {
+                return events;
+            }
+            events
+                .into_iter()
+                .filter_map(|event| {
+                    if event.event_type() != EventType::ABSOLUTE {
+                        return Some(event);
+                    }
+                    let target = self.target_of(AbsoluteAxisCode(event.code()))?;
+                    Some(InputEvent::new(
+                        EventType::ABSOLUTE.0,
+                        target.0,
+                        event.value(),
+                    ))
+                })
+                .collect()
+        }
+    }
+
+    fn key_for_button(button: u16) -> Option<KeyCode> {
+        EVDEV_BUTTON_MAP
+            .iter()
+            .find(|(_, bit)| *bit == button)
+            .map(|(key, _)| *key)
+    }
+
+    fn trigger_axis(trigger: Trigger) -> AbsoluteAxisCode {
+        match trigger {
+            Trigger::Left => AbsoluteAxisCode::ABS_Z,
+            Trigger::Right => AbsoluteAxisCode::ABS_RZ,
+        }
+    }
+
+    /// Raw range and last forwarded value of one trigger axis.
+    #[derive(Debug, Clone, Copy)]
+    struct TriggerAxis {
+        min: i32,
+        max: i32,
+        last: i32,
+        /// Held at max by a button-to-trigger binding; physical events are suppressed.
+        forced: bool,
+    }
+
+    impl TriggerAxis {
+        fn normalized(&self, value: i32) -> u8 {
+            if self.max == self.min {
+                return 0;
+            }
+            let n = (value - self.min) as f64 / (self.max - self.min) as f64;
+            (n * 255.0).round().clamp(0.0, 255.0) as u8
+        }
+
+        fn denormalized(&self, value: u8) -> i32 {
+            let n = value as f64 / 255.0;
+            self.min + (n * (self.max - self.min) as f64).round() as i32
+        }
+    }
+
+    /// Raw range and last raw value of one stick axis.
+    #[derive(Debug, Clone, Copy)]
+    struct StickAxis {
+        min: i32,
+        max: i32,
+        /// Driver-reported flat zone; values this close to centre read as 0.
+        flat: i32,
+        last: i32,
+    }
+
+    impl StickAxis {
+        fn normalized(&self, value: i32) -> i16 {
+            if self.max == self.min {
+                return 0;
+            }
+            // Odd-width ranges (e.g. 0-255) have no exact centre value
+            let centre = (self.min + self.max) as f64 / 2.0;
+            if (value as f64 - centre).abs() <= self.flat.max(1) as f64 {
+                return 0;
+            }
+            let n = (value - self.min) as f64 / (self.max - self.min) as f64;
+            (n * 65535.0 - 32768.0).round().clamp(-32768.0, 32767.0) as i16
+        }
+
+        fn denormalized(&self, value: i16) -> i32 {
+            let n = (value as f64 + 32768.0) / 65535.0;
+            self.min + (n * (self.max - self.min) as f64).round() as i32
+        }
+    }
+
+    /// Both axes of a stick, shaped together (the anti-deadzone is radial).
+    #[derive(Debug, Clone, Copy)]
+    struct StickAxes {
+        x: StickAxis,
+        y: StickAxis,
+    }
+
+    fn stick_axes(stick: Stick) -> (AbsoluteAxisCode, AbsoluteAxisCode) {
+        match stick {
+            Stick::Left => (AbsoluteAxisCode::ABS_X, AbsoluteAxisCode::ABS_Y),
+            Stick::Right => (AbsoluteAxisCode::ABS_RX, AbsoluteAxisCode::ABS_RY),
+        }
+    }
+
+    /// Applies an `InputMapping` to a stream of evdev events from one physical device.
+    pub struct EventMapper {
+        mapping: InputMapping,
+        layout: AxisLayout,
+        left: Option<TriggerAxis>,
+        right: Option<TriggerAxis>,
+        left_stick: Option<StickAxes>,
+        right_stick: Option<StickAxes>,
+        /// Current pressed state of each trigger-to-button binding.
+        pressed: Vec<bool>,
+    }
+
+    impl EventMapper {
+        pub fn new(mapping: InputMapping, layout: AxisLayout, device: &evdev::Device) -> Self {
+            // Ranges come from whichever raw axis feeds each standard one
+            let absinfo = |code: AbsoluteAxisCode| {
+                let source = layout.source_of(code)?;
+                device
+                    .get_absinfo()
+                    .ok()?
+                    .find(|(axis, _)| *axis == source)
+                    .map(|(_, info)| info)
+            };
+            let axis = |code: AbsoluteAxisCode| {
+                absinfo(code).map(|info| TriggerAxis {
+                    min: info.minimum(),
+                    max: info.maximum(),
+                    last: info.value(),
+                    forced: false,
+                })
+            };
+            let stick_axis = |code: AbsoluteAxisCode| {
+                absinfo(code).map(|info| StickAxis {
+                    min: info.minimum(),
+                    max: info.maximum(),
+                    flat: info.flat(),
+                    last: info.value(),
+                })
+            };
+            let stick = |stick: Stick| {
+                let (x, y) = stick_axes(stick);
+                Some(StickAxes {
+                    x: stick_axis(x)?,
+                    y: stick_axis(y)?,
+                })
+            };
+            let pressed = vec![false; mapping.trigger_to_button.len()];
+            Self {
+                left: axis(AbsoluteAxisCode::ABS_Z),
+                right: axis(AbsoluteAxisCode::ABS_RZ),
+                left_stick: stick(Stick::Left),
+                right_stick: stick(Stick::Right),
+                mapping,
+                layout,
+                pressed,
+            }
+        }
+
+        /// The virtual device axis a raw axis of the physical device is forwarded as.
+        pub fn virtual_axis(&self, source: AbsoluteAxisCode) -> Option<AbsoluteAxisCode> {
+            self.layout.target_of(source)
+        }
+
+        /// Keys the virtual device must advertise for trigger-to-button bindings
+        /// and button remaps.
+        pub fn extra_keys(&self) -> Vec<KeyCode> {
+            let bindings = self.mapping.trigger_to_button.iter().map(|b| b.button);
+            let remaps = self.mapping.button_remap.iter().map(|r| r.to);
+            bindings.chain(remaps).filter_map(key_for_button).collect()
+        }
+
+        fn axis_mut(&mut self, trigger: Trigger) -> Option<&mut TriggerAxis> {
+            match trigger {
+                Trigger::Left => self.left.as_mut(),
+                Trigger::Right => self.right.as_mut(),
+            }
+        }
+
+        /// Map one batch of events (typically ending in SYN_REPORT).
+        pub fn map(&mut self, events: Vec<InputEvent>) -> Vec<InputEvent> {
+            let events = self.layout.remap(events);
+            if self.mapping.is_identity() {
+                return events;
+            }
+
+            let mut out = Vec::with_capacity(events.len());
+            for event in events {
+                if event.event_type() == EventType::ABSOLUTE {
+                    let trigger = match AbsoluteAxisCode(event.code()) {
+                        AbsoluteAxisCode::ABS_Z => Some(Trigger::Left),
+                        AbsoluteAxisCode::ABS_RZ => Some(Trigger::Right),
+                        _ => None,
+                    };
+                    if let Some(trigger) = trigger {
+                        self.map_trigger(trigger, event, &mut out);
+                        continue;
+                    }
+                    let stick = match AbsoluteAxisCode(event.code()) {
+                        AbsoluteAxisCode::ABS_X | AbsoluteAxisCode::ABS_Y => Some(Stick::Left),
+                        AbsoluteAxisCode::ABS_RX | AbsoluteAxisCode::ABS_RY => Some(Stick::Right),
+                        _ => None,
+                    };
+                    if let Some(stick) = stick {
+                        if self.map_stick(stick, event, &mut out) {
+                            continue;
+                        }
+                    }
+                } else if event.event_type() == EventType::KEY {
+                    self.map_key(event, &mut out);
+                    out.push(self.remap_key(event));
+                    continue;
+                }
+                out.push(event);
+            }
+            // Masked inputs are never forwarded, so the virtual device keeps them at rest
+            out.retain(|e| self.forwards(e));
+            out
+        }
+
+        /// Whether `input_mask` lets this output event through.
+        fn forwards(&self, event: &InputEvent) -> bool {
+            let mask = &self.mapping.input_mask;
+            if event.event_type() == EventType::KEY {
+                let button = EVDEV_BUTTON_MAP
+                    .iter()
+                    .find(|(key, _)| key.0 == event.code())
+                    .map_or(0, |(_, bit)| *bit);
+                return mask.forwards_button(button);
+            }
+            if event.event_type() != EventType::ABSOLUTE {
+                return true;
+            }
+            match AbsoluteAxisCode(event.code()) {
+                AbsoluteAxisCode::ABS_X | AbsoluteAxisCode::ABS_Y => mask.left_stick,
+                AbsoluteAxisCode::ABS_RX | AbsoluteAxisCode::ABS_RY => mask.right_stick,
+                AbsoluteAxisCode::ABS_Z | AbsoluteAxisCode::ABS_RZ => mask.triggers,
+                AbsoluteAxisCode::ABS_HAT0X | AbsoluteAxisCode::ABS_HAT0Y => mask.dpad,
+                _ => true,
+            }
+        }
+
+        fn map_trigger(&mut self, trigger: Trigger, event: InputEvent, out: &mut Vec<InputEvent>) {
+            let Some(axis) = self.axis_mut(trigger) else {
+                out.push(event);
+                return;
+            };
+            let axis = *axis;
+            let normalized = self
+                .mapping
+                .shape_trigger(trigger, axis.normalized(event.value()));
+            let output = axis.denormalized(self.mapping.clamp_trigger(normalized));
+            if let Some(a) = self.axis_mut(trigger) {
+                a.last = output;
+            }
+
+            for (i, binding) in self.mapping.trigger_to_button.iter().enumerate() {
+                if binding.trigger != trigger {
+                    continue;
+                }
+                let now = normalized >= binding.threshold;
+                if now != self.pressed[i] {
+                    self.pressed[i] = now;
+                    if let Some(key) = key_for_button(binding.button) {
+                        out.push(InputEvent::new(EventType::KEY.0, key.0, now as i32));
+                    }
+                }
+            }
+
+            if !axis.forced {
+                out.push(InputEvent::new(EventType::ABSOLUTE.0, event.code(), output));
+            }
+        }
+
+        /// Reshape a stick axis event. Emits both axes, since the anti-deadzone
+        /// depends on the whole deflection. Returns false if the event passes through.
+        fn map_stick(
+            &mut self,
+            stick: Stick,
+            event: InputEvent,
+            out: &mut Vec<InputEvent>,
+        ) -> bool {
+            if !self.mapping.shapes_stick(stick) {
+                return false;
+            }
+            let axes = match stick {
+                Stick::Left => self.left_stick.as_mut(),
+                Stick::Right => self.right_stick.as_mut(),
+            };
+            let Some(axes) = axes else {
+                return false;
+            };
+            let (x_code, y_code) = stick_axes(stick);
+            if event.code() == x_code.0 {
+                axes.x.last = event.value();
+            } else {
+                axes.y.last = event.value();
+            }
+            let axes = *axes;
+
+            let (x, y) = self.mapping.shape_stick(
+                stick,
+                axes.x.normalized(axes.x.last),
+                axes.y.normalized(axes.y.last),
+            );
+            out.push(InputEvent::new(
+                EventType::ABSOLUTE.0,
+                x_code.0,
+                axes.x.denormalized(x),
+            ));
+            out.push(InputEvent::new(
+                EventType::ABSOLUTE.0,
+                y_code.0,
+                axes.y.denormalized(y),
+            ));
+            true
+        }
+
+        /// Re-address a key event according to `button_remap`.
+        fn remap_key(&self, event: InputEvent) -> InputEvent {
+            let Some(button) = EVDEV_BUTTON_MAP
+                .iter()
+                .find(|(key, _)| key.0 == event.code())
+                .map(|(_, bit)| *bit)
+            else {
+                return event;
+            };
+            match key_for_button(self.mapping.remap_button(button)) {
+                Some(key) if key.0 != event.code() => {
+                    InputEvent::new(EventType::KEY.0, key.0, event.value())
+                }
+                _ => event,
+            }
+        }
+
+        fn map_key(&mut self, event: InputEvent, out: &mut Vec<InputEvent>) {
+            let Some(button) = EVDEV_BUTTON_MAP
+                .iter()
+                .find(|(key, _)| key.0 == event.code())
+                .map(|(_, bit)| *bit)
+            else {
+                return;
+            };
+            // Ignore autorepeat (value 2)
+            if event.value() > 1 {
+                return;
+            }
+            let held = event.value() == 1;
+
+            let triggers: Vec<Trigger> = self
+                .mapping
+                .button_to_trigger
+                .iter()
+                .filter(|b| b.button == button)
+                .map(|b| b.trigger)
+                .collect();
+            let max = self.mapping.clamp_trigger(u8::MAX);
+            for trigger in triggers {
+                let Some(axis) = self.axis_mut(trigger) else {
+                    continue;
+                };
+                axis.forced = held;
+                let value = if held {
+                    axis.denormalized(max)
+                } else {
+                    axis.last
+                };
+                out.push(InputEvent::new(
+                    EventType::ABSOLUTE.0,
+                    trigger_axis(trigger).0,
+                    value,
+                ));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn magnitude(stick: (i16, i16)) -> f64 {
+        (stick.0 as f64).hypot(stick.1 as f64)
+    }
+
+    #[test]
+    fn anti_deadzone_lifts_small_input_and_keeps_full_range() {
+        let mapping = InputMapping {
+            left_anti_deadzone: 8000,
+            ..Default::default()
+        };
+        assert_eq!(mapping.shape_stick(Stick::Left, 0, 0), (0, 0));
+        assert!(magnitude(mapping.shape_stick(Stick::Left, 100, 0)) >= 8000.0);
+        assert_eq!(mapping.shape_stick(Stick::Left, i16::MAX, 0), (i16::MAX, 0));
+        // Only the configured stick is shaped
+        assert_eq!(mapping.shape_stick(Stick::Right, 100, 0), (100, 0));
+    }
+
+    #[test]
+    fn deadzone_then_anti_deadzone_is_monotonic() {
+        let mapping = InputMapping {
+            left_stick_deadzone: 6000,
+            left_anti_deadzone: 9000,
+            ..Default::default()
+        };
+        assert_eq!(mapping.shape_stick(Stick::Left, 6000, 0), (0, 0));
+        assert!(magnitude(mapping.shape_stick(Stick::Left, 6001, 0)) >= 9000.0);
+
+        let mut previous = 0.0;
+        for x in (0..=i16::MAX).step_by(97) {
+            let out = magnitude(mapping.shape_stick(Stick::Left, x, 0));
+            assert!(out >= previous, "output dropped at input {}", x);
+            previous = out;
+        }
+        assert_eq!(mapping.shape_stick(Stick::Left, i16::MAX, 0), (i16::MAX, 0));
+    }
+
+    #[test]
+    fn anti_deadzone_preserves_direction() {
+        let mapping = InputMapping {
+            right_anti_deadzone: 10000,
+            ..Default::default()
+        };
+        let (x, y) = mapping.shape_stick(Stick::Right, 300, -400);
+        assert!(x > 0 && y < 0);
+        assert!(((y as f64 / x as f64) - (-400.0 / 300.0)).abs() < 0.01);
+    }
+
+    #[test]
+    fn nintendo_preset_swaps_face_buttons() {
+        let mapping = InputMapping {
+            button_remap: RemapPreset::Nintendo.button_remap(),
+            ..Default::default()
+        };
+        let mut state = GamepadState {
+            buttons: buttons::A | buttons::Y | buttons::START,
+            ..Default::default()
+        };
+        mapping.apply(&mut state);
+        assert_eq!(state.buttons, buttons::B | buttons::X | buttons::START);
+        assert!(InputMapping {
+            button_remap: RemapPreset::Xbox.button_remap(),
+            ..Default::default()
+        }
+        .is_identity());
+    }
+
+    #[test]
+    fn gamepad_state_field_names_are_stable() {
+        let state = GamepadState {
+            buttons: buttons::A | buttons::DPAD_LEFT,
+            ..Default::default()
+        };
+        let json = serde_json::to_value(&state).unwrap();
+        let mut keys: Vec<&str> = json
+            .as_object()
+            .unwrap()
+            .keys()
+            .map(|k| k.as_str())
+            .collect();
+        keys.sort_unstable();
+        assert_eq!(
+            keys,
+            [
+                "buttons",
+                "left_trigger",
+                "right_trigger",
+                "thumb_lx",
+                "thumb_ly",
+                "thumb_rx",
+                "thumb_ry"
+            ]
+        );
+
+        let pressed = state.button_states();
+        assert_eq!(pressed.len(), buttons::NAMES.len());
+        assert!(pressed["A"] && pressed["DPadLeft"] && !pressed["B"]);
+    }
+
+    #[test]
+    fn trigger_deadzone_applies_after_inversion() {
+        let mapping = InputMapping {
+            left_trigger_deadzone: 55,
+            invert_left_trigger: true,
+            trigger_output_max: 200,
+            trigger_to_button: vec![TriggerToButton {
+                trigger: Trigger::Left,
+                button: buttons::A,
+                threshold: 250,
+            }],
+            ..Default::default()
+        };
+        // A pedal resting near full scale: inverted to near 0, inside the deadzone
+        let mut state = GamepadState {
+            left_trigger: 240,
+            ..Default::default()
+        };
+        mapping.apply(&mut state);
+        assert_eq!((state.left_trigger, state.buttons), (0, 0));
+
+        // Fully pressed (reads 0): inverted to full, past the deadzone, then clamped
+        let mut state = GamepadState {
+            left_trigger: 0,
+            ..Default::default()
+        };
+        mapping.apply(&mut state);
+        assert_eq!(state.left_trigger, 200);
+        // Bindings see the shaped value before the clamp
+        assert_eq!(state.buttons, buttons::A);
+        assert!(!mapping.is_identity());
+    }
+
+    #[test]
+    fn input_mask_neutralizes_masked_groups() {
+        let mapping = InputMapping {
+            input_mask: InputMask {
+                left_stick: false,
+                dpad: false,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let mut state = GamepadState {
+            buttons: buttons::A | buttons::DPAD_UP,
+            thumb_lx: 12000,
+            thumb_rx: -9000,
+            left_trigger: 80,
+            ..Default::default()
+        };
+        mapping.apply(&mut state);
+        assert_eq!(state.buttons, buttons::A);
+        assert_eq!((state.thumb_lx, state.thumb_rx), (0, -9000));
+        assert_eq!(state.left_trigger, 80);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn axis_override_takes_over_its_target() {
+        use evdev::AbsoluteAxisCode;
+        // An arcade stick reporting its lever on the hat axes
+        let layout = AxisLayout::new(&[AxisOverride {
+            source: AbsoluteAxisCode::ABS_HAT0X.0,
+            target: GamepadAxis::LeftStickX,
+        }]);
+        assert_eq!(
+            layout.target_of(AbsoluteAxisCode::ABS_HAT0X),
+            Some(AbsoluteAxisCode::ABS_X)
+        );
+        assert_eq!(layout.target_of(AbsoluteAxisCode::ABS_X), None);
+        assert_eq!(layout.source_of(AbsoluteAxisCode::ABS_HAT0X), None);
+        assert_eq!(
+            layout.target_of(AbsoluteAxisCode::ABS_Y),
+            Some(AbsoluteAxisCode::ABS_Y)
+        );
+    }
+}
@@ -1,26 +1,60 @@
-use crate::device::{DeviceType, DriverStatus, GamepadState, PhysicalDevice};
+use crate::device::{DeviceCapabilities, DeviceType, DriverStatus, GamepadState, PhysicalDevice};
 use crate::error::{PadSwitchError, Result};
-use crate::platform::{DeviceEnumerator, DeviceHider, VirtualControllerManager};
-use evdev::{AbsoluteAxisCode, KeyCode};
+use crate::mapping::{buttons, AxisLayout, AxisOverride, EVDEV_BUTTON_MAP, XINPUT_AXES};
+use crate::platform::{
+    to_hex, DeviceEnumerator, DeviceHider, DeviceSelection, VirtualControllerManager,
+    RAW_REPORT_TIMEOUT,
+};
+use evdev::{AbsoluteAxisCode, EventType, FFEffectCode, KeyCode};
 use std::collections::hash_map::DefaultHasher;
+use std::collections::BTreeMap;
 use std::hash::{Hash, Hasher};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 
 /// Linux platform backend using evdev for physical device enumeration
 /// and uinput for virtual controller creation (in the input loop).
-pub struct LinuxPlatform;
+pub struct LinuxPlatform {
+    /// Axis overrides by stable device id, for `read_gamepad_state`.
+    axis_overrides: Mutex<BTreeMap<String, Vec<AxisOverride>>>,
+    /// Which devices `enumerate_devices` reports.
+    device_selection: Mutex<DeviceSelection>,
+}
 
 impl LinuxPlatform {
     pub fn new() -> Self {
-        Self
+        Self {
+            axis_overrides: Mutex::new(BTreeMap::new()),
+            device_selection: Mutex::new(DeviceSelection::default()),
+        }
+    }
+
+    fn axis_layout(&self, device: &evdev::Device) -> AxisLayout {
+        let overrides = self.axis_overrides.lock().unwrap();
+        AxisLayout::new(
+            overrides
+                .get(&stable_device_id(device))
+                .map_or(&[][..], Vec::as_slice),
+        )
     }
 }
 
+/// Absolute axes in `XINPUT_AXES` order (triggers on Z/RZ, as in the input loop).
+const EVDEV_AXIS_MAP: [AbsoluteAxisCode; 6] = [
+    AbsoluteAxisCode::ABS_X,
+    AbsoluteAxisCode::ABS_Y,
+    AbsoluteAxisCode::ABS_RX,
+    AbsoluteAxisCode::ABS_RY,
+    AbsoluteAxisCode::ABS_Z,
+    AbsoluteAxisCode::ABS_RZ,
+];
+
 /// Check if an evdev device looks like a gamepad by inspecting its supported keys.
 fn is_gamepad(device: &evdev::Device) -> bool {
     let Some(keys) = device.supported_keys() else {
         return false;
     };
-    keys.contains(KeyCode::BTN_GAMEPAD) || keys.contains(KeyCode::BTN_SOUTH)
+    keys.contains(KeyCode::BTN_SOUTH)
 }
 
 /// Generate a stable device ID by hashing the physical path (or name+vid+pid as fallback).
@@ -42,36 +76,52 @@ fn stable_device_id(device: &evdev::Device) -> String {
     format!("linux-{:016x}", hasher.finish())
 }
 
+/// evdev devices that look like gamepads, or every input device with `include_all`.
+fn enumerate_evdev(include_all: bool) -> Vec<PhysicalDevice> {
+    let mut devices = Vec::new();
+
+    for (path, device) in evdev::enumerate() {
+        if !include_all && !is_gamepad(&device) {
+            continue;
+        }
+
+        let id = device.input_id();
+        let name = device.name().unwrap_or("Unknown Gamepad").to_string();
+        let instance_path = path.to_string_lossy().to_string();
+
+        devices.push(PhysicalDevice {
+            id: stable_device_id(&device),
+            name,
+            instance_path,
+            device_type: DeviceType::XInput, // Linux doesn't distinguish XInput/DirectInput
+            hidden: false,
+            connected: true,
+            vendor_id: id.vendor(),
+            product_id: id.product(),
+            xinput_slot: None, // No XInput slots on Linux
+            xinput_overflow: false,
+        });
+    }
+
+    devices
+}
+
 impl DeviceEnumerator for LinuxPlatform {
     fn enumerate_devices(&self) -> Result<Vec<PhysicalDevice>> {
-        let mut devices = Vec::new();
+        let selection = self.device_selection.lock().unwrap().clone();
+        Ok(selection.apply(enumerate_evdev(selection.is_manual())))
+    }
 
-        for (path, device) in evdev::enumerate() {
-            if !is_gamepad(&device) {
-                continue;
-            }
+    fn enumerate_input_devices(&self) -> Result<Vec<PhysicalDevice>> {
+        Ok(enumerate_evdev(true))
+    }
 
-            let id = device.input_id();
-            let name = device
-                .name()
-                .unwrap_or("Unknown Gamepad")
-                .to_string();
-            let instance_path = path.to_string_lossy().to_string();
-
-            devices.push(PhysicalDevice {
-                id: stable_device_id(&device),
-                name,
-                instance_path,
-                device_type: DeviceType::XInput, // Linux doesn't distinguish XInput/DirectInput
-                hidden: false,
-                connected: true,
-                vendor_id: id.vendor(),
-                product_id: id.product(),
-                xinput_slot: None, // No XInput slots on Linux
-            });
-        }
+    fn set_device_selection(&self, selection: DeviceSelection) {
+        *self.device_selection.lock().unwrap() = selection;
+    }
 
-        Ok(devices)
+    fn backend_description(&self) -> String {
+        "Linux: evdev grab + uinput".into()
     }
 
     fn check_drivers(&self) -> Result<DriverStatus> {
@@ -91,8 +141,99 @@ impl DeviceEnumerator for LinuxPlatform {
             } else {
                 None
             },
+            xinput_version: None,
         })
     }
+
+    fn read_raw_report(&self, instance_path: &str, count: u32) -> Result<Vec<String>> {
+        let mut device = evdev::Device::open(instance_path).map_err(|e| {
+            PadSwitchError::Platform(format!("Failed to open {}: {}", instance_path, e))
+        })?;
+        device.set_nonblocking(true).map_err(|e| {
+            PadSwitchError::Platform(format!(
+                "Failed to set non-blocking on {}: {}",
+                instance_path, e
+            ))
+        })?;
+
+        // A "frame" is every event up to and including the next SYN_REPORT,
+        // rendered as big-endian type/code/value triples.
+        let deadline = Instant::now() + RAW_REPORT_TIMEOUT;
+        let mut frames = Vec::new();
+        let mut current: Vec<u8> = Vec::new();
+        while frames.len() < count as usize && Instant::now() < deadline {
+            match device.fetch_events() {
+                Ok(events) => {
+                    for event in events {
+                        current.extend_from_slice(&event.event_type().0.to_be_bytes());
+                        current.extend_from_slice(&event.code().to_be_bytes());
+                        current.extend_from_slice(&event.value().to_be_bytes());
+                        if event.event_type() == EventType::SYNCHRONIZATION {
+                            frames.push(to_hex(&current));
+                            current.clear();
+                        }
+                    }
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                    std::thread::sleep(Duration::from_millis(1));
+                }
+                Err(e) => {
+                    return Err(PadSwitchError::Platform(format!(
+                        "Failed to read from {}: {}",
+                        instance_path, e
+                    )));
+                }
+            }
+        }
+        frames.truncate(count as usize);
+        Ok(frames)
+    }
+
+    fn is_device_present(&self, instance_path: &str) -> Result<bool> {
+        Ok(std::path::Path::new(instance_path).exists())
+    }
+
+    fn device_capabilities(&self, device: &PhysicalDevice) -> Result<DeviceCapabilities> {
+        let dev = evdev::Device::open(&device.instance_path).map_err(|e| {
+            PadSwitchError::Platform(format!("Failed to open {}: {}", device.instance_path, e))
+        })?;
+
+        let keys = dev.supported_keys();
+        let abs = dev.supported_absolute_axes();
+        // Many pads report the D-pad as a hat instead of four keys
+        let has_hat = abs.is_some_and(|a| {
+            a.contains(AbsoluteAxisCode::ABS_HAT0X) && a.contains(AbsoluteAxisCode::ABS_HAT0Y)
+        });
+
+        let buttons = EVDEV_BUTTON_MAP
+            .iter()
+            .filter(|(key, bit)| {
+                let is_dpad = matches!(
+                    *bit,
+                    buttons::DPAD_UP
+                        | buttons::DPAD_DOWN
+                        | buttons::DPAD_LEFT
+                        | buttons::DPAD_RIGHT
+                );
+                keys.is_some_and(|k| k.contains(*key)) || (is_dpad && has_hat)
+            })
+            .filter_map(|(_, bit)| buttons::name(*bit))
+            .chain(
+                keys.is_some_and(|k| k.contains(KeyCode::BTN_MODE))
+                    .then_some("Guide"),
+            )
+            .map(String::from)
+            .collect();
+
+        let axes = EVDEV_AXIS_MAP
+            .iter()
+            .zip(XINPUT_AXES)
+            .filter(|(code, _)| abs.is_some_and(|a| a.contains(**code)))
+            .map(|(_, name)| name.to_string())
+            .collect();
+
+        Ok(DeviceCapabilities { buttons, axes })
+    }
 }
 
 impl DeviceHider for LinuxPlatform {
@@ -111,6 +252,14 @@ impl DeviceHider for LinuxPlatform {
         Ok(())
     }
 
+    fn whitelist_app(&self, _app_path: &str) -> Result<bool> {
+        Ok(false)
+    }
+
+    fn unwhitelist_app(&self, _app_path: &str) -> Result<()> {
+        Ok(())
+    }
+
     fn disable_device(&self, _instance_path: &str) -> Result<()> {
         Err(PadSwitchError::PlatformNotSupported(
             "Minimal mode is not supported on Linux. Use Force mode instead.".into(),
@@ -123,6 +272,11 @@ impl DeviceHider for LinuxPlatform {
         ))
     }
 
+    fn activate_hiding(&self) -> Result<()> {
+        // No hiding driver to activate on Linux
+        Ok(())
+    }
+
     fn deactivate_hiding(&self) -> Result<()> {
         // No hiding driver to deactivate on Linux
         Ok(())
@@ -149,26 +303,43 @@ impl VirtualControllerManager for LinuxPlatform {
         })?;
 
         let mut state = GamepadState::default();
+        let layout = self.axis_layout(&device);
 
         // Read absolute axis values
-        if let Some(abs_state) = device.get_abs_state() {
-            for info in &abs_state {
-                // Map standard gamepad axes to GamepadState fields.
+        if let Ok(absinfo) = device.get_absinfo() {
+            for (code, info) in absinfo {
+                // Map standard gamepad axes (after any overrides) to GamepadState fields.
                 // evdev absolute axis values vary by device; normalize to XInput ranges.
-                match AbsoluteAxisCode(info.code) {
-                    AbsoluteAxisCode::ABS_X => state.thumb_lx = normalize_axis(info.value, info.minimum, info.maximum),
-                    AbsoluteAxisCode::ABS_Y => state.thumb_ly = normalize_axis_inverted(info.value, info.minimum, info.maximum),
-                    AbsoluteAxisCode::ABS_RX => state.thumb_rx = normalize_axis(info.value, info.minimum, info.maximum),
-                    AbsoluteAxisCode::ABS_RY => state.thumb_ry = normalize_axis_inverted(info.value, info.minimum, info.maximum),
-                    AbsoluteAxisCode::ABS_Z => state.left_trigger = normalize_trigger(info.value, info.minimum, info.maximum),
-                    AbsoluteAxisCode::ABS_RZ => state.right_trigger = normalize_trigger(info.value, info.minimum, info.maximum),
+                let Some(axis) = layout.target_of(code) else {
+                    continue;
+                };
+                let (value, minimum, maximum) = (info.value(), info.minimum(), info.maximum());
+                match axis {
+                    AbsoluteAxisCode::ABS_X => {
+                        state.thumb_lx = normalize_axis(value, minimum, maximum)
+                    }
+                    AbsoluteAxisCode::ABS_Y => {
+                        state.thumb_ly = normalize_axis_inverted(value, minimum, maximum)
+                    }
+                    AbsoluteAxisCode::ABS_RX => {
+                        state.thumb_rx = normalize_axis(value, minimum, maximum)
+                    }
+                    AbsoluteAxisCode::ABS_RY => {
+                        state.thumb_ry = normalize_axis_inverted(value, minimum, maximum)
+                    }
+                    AbsoluteAxisCode::ABS_Z => {
+                        state.left_trigger = normalize_trigger(value, minimum, maximum)
+                    }
+                    AbsoluteAxisCode::ABS_RZ => {
+                        state.right_trigger = normalize_trigger(value, minimum, maximum)
+                    }
                     _ => {}
                 }
             }
         }
 
         // Read button state
-        if let Some(keys) = device.get_key_state() {
+        if let Ok(keys) = device.get_key_state() {
             state.buttons = map_evdev_buttons_to_xinput(&keys);
         }
 
@@ -180,76 +351,31 @@ impl VirtualControllerManager for LinuxPlatform {
             "Virtual controllers are managed by the input loop on Linux".into(),
         ))
     }
-}
-
-// ---------------------------------------------------------------------------
-// Axis / button mapping helpers
-// ---------------------------------------------------------------------------
-
-/// Information about an absolute axis from evdev's cached state.
-struct AbsAxisInfo {
-    code: u16,
-    value: i32,
-    minimum: i32,
-    maximum: i32,
-}
-
-/// Extension trait to read cached absolute axis state from an evdev device.
-trait AbsStateExt {
-    fn get_abs_state(&self) -> Option<Vec<AbsAxisInfo>>;
-    fn get_key_state(&self) -> Option<evdev::AttributeSet<KeyCode>>;
-}
 
-impl AbsStateExt for evdev::Device {
-    fn get_abs_state(&self) -> Option<Vec<AbsAxisInfo>> {
-        let supported = self.supported_absolute_axes()?;
-        let mut result = Vec::new();
-        for axis in supported.iter() {
-            if let Some(info) = self.get_abs_state_by_code(&axis) {
-                result.push(AbsAxisInfo {
-                    code: axis.0,
-                    value: info.value,
-                    minimum: info.minimum,
-                    maximum: info.maximum,
-                });
-            }
-        }
-        Some(result)
+    fn set_rumble(&self, _instance_path: &str, _left: u16, _right: u16) -> Result<()> {
+        Err(PadSwitchError::PlatformNotSupported(
+            "Rumble is not supported on Linux".into(),
+        ))
     }
 
-    fn get_key_state(&self) -> Option<evdev::AttributeSet<KeyCode>> {
-        self.cached_state().key_vals()
+    fn supports_rumble(&self, instance_path: &str) -> Result<bool> {
+        let dev = evdev::Device::open(instance_path).map_err(|e| {
+            PadSwitchError::Platform(format!("Failed to open {}: {}", instance_path, e))
+        })?;
+        Ok(dev
+            .supported_ff()
+            .is_some_and(|ff| ff.contains(FFEffectCode::FF_RUMBLE)))
     }
-}
-
-/// Helper to read a single axis's AbsInfo from the device.
-trait AbsInfoExt {
-    fn get_abs_state_by_code(&self, code: &AbsoluteAxisCode) -> Option<AbsAxisInfoSimple>;
-}
-
-struct AbsAxisInfoSimple {
-    value: i32,
-    minimum: i32,
-    maximum: i32,
-}
 
-impl AbsInfoExt for evdev::Device {
-    fn get_abs_state_by_code(&self, code: &AbsoluteAxisCode) -> Option<AbsAxisInfoSimple> {
-        let state = self.cached_state();
-        let info = self.get_absinfo(code)?;
-        let value = state
-            .abs_vals()
-            .and_then(|vals| vals.get(*code))
-            .map(|ai| ai.value)
-            .unwrap_or(info.value());
-        Some(AbsAxisInfoSimple {
-            value,
-            minimum: info.minimum(),
-            maximum: info.maximum(),
-        })
+    fn set_axis_overrides(&self, overrides: BTreeMap<String, Vec<AxisOverride>>) {
+        *self.axis_overrides.lock().unwrap() = overrides;
     }
 }
 
+// ---------------------------------------------------------------------------
+// Axis / button mapping helpers
+// ---------------------------------------------------------------------------
+
 /// Normalize an evdev axis value (min..max) to XInput i16 range (-32768..32767).
 fn normalize_axis(value: i32, min: i32, max: i32) -> i16 {
     if max == min {
@@ -281,38 +407,8 @@ fn normalize_trigger(value: i32, min: i32, max: i32) -> u8 {
 
 /// Map evdev key state to XInput button bitmask.
 fn map_evdev_buttons_to_xinput(keys: &evdev::AttributeSet<KeyCode>) -> u16 {
-    let mut buttons: u16 = 0;
-
-    // XInput button constants (matching Windows XINPUT_GAMEPAD_*)
-    const DPAD_UP: u16 = 0x0001;
-    const DPAD_DOWN: u16 = 0x0002;
-    const DPAD_LEFT: u16 = 0x0004;
-    const DPAD_RIGHT: u16 = 0x0008;
-    const START: u16 = 0x0010;
-    const BACK: u16 = 0x0020;
-    const LEFT_THUMB: u16 = 0x0040;
-    const RIGHT_THUMB: u16 = 0x0080;
-    const LEFT_SHOULDER: u16 = 0x0100;
-    const RIGHT_SHOULDER: u16 = 0x0200;
-    const A: u16 = 0x1000;
-    const B: u16 = 0x2000;
-    const X: u16 = 0x4000;
-    const Y: u16 = 0x8000;
-
-    if keys.contains(KeyCode::BTN_SOUTH) { buttons |= A; }
-    if keys.contains(KeyCode::BTN_EAST) { buttons |= B; }
-    if keys.contains(KeyCode::BTN_WEST) { buttons |= X; }
-    if keys.contains(KeyCode::BTN_NORTH) { buttons |= Y; }
-    if keys.contains(KeyCode::BTN_TL) { buttons |= LEFT_SHOULDER; }
-    if keys.contains(KeyCode::BTN_TR) { buttons |= RIGHT_SHOULDER; }
-    if keys.contains(KeyCode::BTN_SELECT) { buttons |= BACK; }
-    if keys.contains(KeyCode::BTN_START) { buttons |= START; }
-    if keys.contains(KeyCode::BTN_THUMBL) { buttons |= LEFT_THUMB; }
-    if keys.contains(KeyCode::BTN_THUMBR) { buttons |= RIGHT_THUMB; }
-    if keys.contains(KeyCode::BTN_DPAD_UP) { buttons |= DPAD_UP; }
-    if keys.contains(KeyCode::BTN_DPAD_DOWN) { buttons |= DPAD_DOWN; }
-    if keys.contains(KeyCode::BTN_DPAD_LEFT) { buttons |= DPAD_LEFT; }
-    if keys.contains(KeyCode::BTN_DPAD_RIGHT) { buttons |= DPAD_RIGHT; }
-
-    buttons
+    EVDEV_BUTTON_MAP
+        .iter()
+        .filter(|(key, _)| keys.contains(*key))
+        .fold(0u16, |buttons, (_, bit)| buttons | bit)
 }
@@ -1,4 +1,4 @@
-use crate::device::{DeviceType, DriverStatus, GamepadState, PhysicalDevice};
+use crate::device::{DeviceCapabilities, DeviceType, DriverStatus, GamepadState, PhysicalDevice};
 use crate::error::{PadSwitchError, Result};
 use crate::platform::{DeviceEnumerator, DeviceHider, VirtualControllerManager};
 
@@ -24,6 +24,7 @@ impl DeviceEnumerator for MacOSPlatform {
                 vendor_id: 0x31E3,
                 product_id: 0x1100,
                 xinput_slot: Some(0),
+                xinput_overflow: false,
             },
             PhysicalDevice {
                 id: "mock-xbox-controller".into(),
@@ -35,6 +36,7 @@ impl DeviceEnumerator for MacOSPlatform {
                 vendor_id: 0x045E,
                 product_id: 0x0B12,
                 xinput_slot: Some(1),
+                xinput_overflow: false,
             },
             PhysicalDevice {
                 id: "mock-ps5-dualsense".into(),
@@ -46,18 +48,35 @@ impl DeviceEnumerator for MacOSPlatform {
                 vendor_id: 0x054C,
                 product_id: 0x0CE6,
                 xinput_slot: None,
+                xinput_overflow: false,
             },
         ])
     }
 
+    fn backend_description(&self) -> String {
+        "macOS: development stub (no hiding or virtual controllers)".into()
+    }
+
     fn check_drivers(&self) -> Result<DriverStatus> {
         Ok(DriverStatus {
             hidhide_installed: true,
             vigembus_installed: true,
             hidhide_version: Some("(mock — macOS dev mode)".into()),
             vigembus_version: Some("(mock — macOS dev mode)".into()),
+            xinput_version: None,
         })
     }
+
+    fn read_raw_report(&self, _instance_path: &str, _count: u32) -> Result<Vec<String>> {
+        Err(PadSwitchError::PlatformNotSupported(
+            "Raw reports not available on macOS".into(),
+        ))
+    }
+
+    fn device_capabilities(&self, device: &PhysicalDevice) -> Result<DeviceCapabilities> {
+        log::info!("[macOS stub] device_capabilities: {}", device.instance_path);
+        Ok(DeviceCapabilities::xinput())
+    }
 }
 
 impl DeviceHider for MacOSPlatform {
@@ -76,6 +95,16 @@ impl DeviceHider for MacOSPlatform {
         Ok(())
     }
 
+    fn whitelist_app(&self, app_path: &str) -> Result<bool> {
+        log::info!("[macOS stub] whitelist_app: {}", app_path);
+        Ok(false)
+    }
+
+    fn unwhitelist_app(&self, app_path: &str) -> Result<()> {
+        log::info!("[macOS stub] unwhitelist_app: {}", app_path);
+        Ok(())
+    }
+
     fn disable_device(&self, instance_path: &str) -> Result<()> {
         log::info!("[macOS stub] disable_device: {}", instance_path);
         Ok(())
@@ -86,6 +115,11 @@ impl DeviceHider for MacOSPlatform {
         Ok(())
     }
 
+    fn activate_hiding(&self) -> Result<()> {
+        log::info!("[macOS stub] activate_hiding");
+        Ok(())
+    }
+
     fn deactivate_hiding(&self) -> Result<()> {
         log::info!("[macOS stub] deactivate_hiding");
         Ok(())
@@ -114,4 +148,18 @@ impl VirtualControllerManager for MacOSPlatform {
             "Virtual controllers not available on macOS".into(),
         ))
     }
+
+    fn set_rumble(&self, instance_path: &str, left: u16, right: u16) -> Result<()> {
+        log::info!(
+            "[macOS stub] set_rumble: {} ({}, {})",
+            instance_path,
+            left,
+            right
+        );
+        Ok(())
+    }
+
+    fn supports_rumble(&self, _instance_path: &str) -> Result<bool> {
+        Ok(true)
+    }
 }
@@ -0,0 +1,309 @@
+use crate::device::{DeviceCapabilities, DeviceType, DriverStatus, GamepadState, PhysicalDevice};
+use crate::error::{PadSwitchError, Result};
+use crate::platform::{DeviceEnumerator, DeviceHider, DeviceSelection, VirtualControllerManager};
+use std::sync::Mutex;
+
+/// Optional JSON file with the `PhysicalDevice` list the mock should report.
+pub const MOCK_DEVICES_ENV: &str = "PADSWITCH_MOCK_DEVICES";
+
+/// A side effect the app asked the mock backend for, in call order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MockCall {
+    Hide(String),
+    Unhide(String),
+    Disable(String),
+    Enable(String),
+    WhitelistApp(String),
+    UnwhitelistApp(String),
+    ActivateHiding,
+    DeactivateHiding,
+}
+
+/// Deterministic backend for tests and hardware-free runs (`PADSWITCH_MOCK=1`).
+/// Reports a scriptable device list and records every hide/enable call instead of
+/// touching drivers.
+pub struct MockPlatform {
+    devices: Mutex<Vec<PhysicalDevice>>,
+    calls: Mutex<Vec<MockCall>>,
+    /// Applied to the scripted list, which stands in for every input device.
+    selection: Mutex<DeviceSelection>,
+}
+
+impl MockPlatform {
+    pub fn new(devices: Vec<PhysicalDevice>) -> Self {
+        Self {
+            devices: Mutex::new(devices),
+            calls: Mutex::new(Vec::new()),
+            selection: Mutex::new(DeviceSelection::default()),
+        }
+    }
+
+    /// Devices from the `PADSWITCH_MOCK_DEVICES` file, or two XInput pads and a
+    /// DirectInput pad if it isn't set.
+    pub fn from_env() -> Self {
+        let devices = match std::env::var(MOCK_DEVICES_ENV) {
+            Ok(path) => std::fs::read_to_string(&path)
+                .map_err(PadSwitchError::from)
+                .and_then(|data| Ok(serde_json::from_str(&data)?))
+                .unwrap_or_else(|e| {
+                    log::warn!("Failed to read mock devices from {}: {}", path, e);
+                    vec![]
+                }),
+            Err(_) => Self::default_devices(),
+        };
+        Self::new(devices)
+    }
+
+    fn default_devices() -> Vec<PhysicalDevice> {
+        vec![
+            PhysicalDevice::from_xinput_slot(0),
+            PhysicalDevice::from_xinput_slot(1),
+            PhysicalDevice {
+                id: "mock-dinput".into(),
+                name: "Mock DirectInput Pad".into(),
+                instance_path: "HID\\VID_054C&PID_0CE6\\MOCK".into(),
+                device_type: DeviceType::DirectInput,
+                hidden: false,
+                connected: true,
+                vendor_id: 0x054C,
+                product_id: 0x0CE6,
+                xinput_slot: None,
+                xinput_overflow: false,
+            },
+        ]
+    }
+
+    /// Replace the reported devices (e.g. to simulate an unplug).
+    pub fn set_devices(&self, devices: Vec<PhysicalDevice>) {
+        *self.devices.lock().unwrap() = devices;
+    }
+
+    /// Every recorded call so far, in order.
+    pub fn calls(&self) -> Vec<MockCall> {
+        self.calls.lock().unwrap().clone()
+    }
+
+    /// Instance paths currently on the mock blacklist.
+    pub fn hidden(&self) -> Vec<String> {
+        let mut hidden: Vec<String> = Vec::new();
+        for call in self.calls.lock().unwrap().iter() {
+            match call {
+                MockCall::Hide(path) if !hidden.contains(path) => hidden.push(path.clone()),
+                MockCall::Unhide(path) => hidden.retain(|p| p != path),
+                _ => {}
+            }
+        }
+        hidden
+    }
+
+    fn record(&self, call: MockCall) -> Result<()> {
+        self.calls.lock().unwrap().push(call);
+        Ok(())
+    }
+}
+
+impl DeviceEnumerator for MockPlatform {
+    fn enumerate_devices(&self) -> Result<Vec<PhysicalDevice>> {
+        let devices = self.devices.lock().unwrap().clone();
+        Ok(self.selection.lock().unwrap().apply(devices))
+    }
+
+    fn enumerate_input_devices(&self) -> Result<Vec<PhysicalDevice>> {
+        Ok(self.devices.lock().unwrap().clone())
+    }
+
+    fn set_device_selection(&self, selection: DeviceSelection) {
+        *self.selection.lock().unwrap() = selection;
+    }
+
+    fn backend_description(&self) -> String {
+        "Mock backend (no drivers)".into()
+    }
+
+    fn check_drivers(&self) -> Result<DriverStatus> {
+        Ok(DriverStatus {
+            hidhide_installed: true,
+            vigembus_installed: true,
+            hidhide_version: Some("(mock)".into()),
+            vigembus_version: Some("(mock)".into()),
+            xinput_version: None,
+        })
+    }
+
+    fn read_raw_report(&self, _instance_path: &str, count: u32) -> Result<Vec<String>> {
+        Ok(vec!["00".repeat(14); count as usize])
+    }
+
+    fn is_device_present(&self, instance_path: &str) -> Result<bool> {
+        Ok(self
+            .devices
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|d| crate::platform::same_instance_path(&d.instance_path, instance_path)))
+    }
+
+    fn device_capabilities(&self, _device: &PhysicalDevice) -> Result<DeviceCapabilities> {
+        Ok(DeviceCapabilities::xinput())
+    }
+}
+
+impl DeviceHider for MockPlatform {
+    fn hide_device(&self, instance_path: &str) -> Result<()> {
+        self.record(MockCall::Hide(instance_path.into()))
+    }
+
+    fn unhide_device(&self, instance_path: &str) -> Result<()> {
+        self.record(MockCall::Unhide(instance_path.into()))
+    }
+
+    fn whitelist_self(&self) -> Result<()> {
+        Ok(())
+    }
+
+    fn whitelist_app(&self, app_path: &str) -> Result<bool> {
+        self.record(MockCall::WhitelistApp(app_path.into()))?;
+        Ok(true)
+    }
+
+    fn unwhitelist_app(&self, app_path: &str) -> Result<()> {
+        self.record(MockCall::UnwhitelistApp(app_path.into()))
+    }
+
+    fn disable_device(&self, instance_path: &str) -> Result<()> {
+        self.record(MockCall::Disable(instance_path.into()))
+    }
+
+    fn enable_device(&self, instance_path: &str) -> Result<()> {
+        self.record(MockCall::Enable(instance_path.into()))
+    }
+
+    fn activate_hiding(&self) -> Result<()> {
+        self.record(MockCall::ActivateHiding)
+    }
+
+    fn deactivate_hiding(&self) -> Result<()> {
+        self.record(MockCall::DeactivateHiding)
+    }
+}
+
+impl VirtualControllerManager for MockPlatform {
+    fn create_virtual_controller(&self) -> Result<u32> {
+        Ok(0)
+    }
+
+    fn destroy_virtual_controller(&self, _index: u32) -> Result<()> {
+        Ok(())
+    }
+
+    fn read_gamepad_state(&self, _instance_path: &str) -> Result<GamepadState> {
+        Ok(GamepadState::default())
+    }
+
+    fn write_virtual_state(&self, _index: u32, _state: &GamepadState) -> Result<()> {
+        Ok(())
+    }
+
+    fn set_rumble(&self, _instance_path: &str, _left: u16, _right: u16) -> Result<()> {
+        Ok(())
+    }
+
+    fn supports_rumble(&self, _instance_path: &str) -> Result<bool> {
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::platform::{apply_persistent_hides, restore_devices, RestoreOptions};
+
+    fn paths(mock: &MockPlatform) -> Vec<String> {
+        mock.enumerate_devices()
+            .unwrap()
+            .into_iter()
+            .map(|d| d.instance_path)
+            .collect()
+    }
+
+    #[test]
+    fn restore_enables_and_unhides_every_device() {
+        let mock = MockPlatform::new(MockPlatform::default_devices());
+        let paths = paths(&mock);
+        for path in &paths {
+            mock.hide_device(path).unwrap();
+            mock.disable_device(path).unwrap();
+        }
+
+        restore_devices(&mock, &paths, &RestoreOptions::default());
+
+        assert!(mock.hidden().is_empty());
+        let calls = mock.calls();
+        for path in &paths {
+            assert!(calls.contains(&MockCall::Enable(path.clone())));
+        }
+        assert_eq!(calls.last(), Some(&MockCall::DeactivateHiding));
+    }
+
+    #[test]
+    fn restore_follows_priority_and_power_cycles() {
+        let mock = MockPlatform::new(MockPlatform::default_devices());
+        let paths = paths(&mock);
+        let options = RestoreOptions {
+            priority: vec![paths[2].to_lowercase()],
+            power_cycle: true,
+        };
+
+        restore_devices(&mock, &paths, &options);
+
+        let toggles: Vec<MockCall> = mock
+            .calls()
+            .into_iter()
+            .filter(|c| matches!(c, MockCall::Disable(_) | MockCall::Enable(_)))
+            .collect();
+        let expected: Vec<MockCall> = [&paths[2], &paths[0], &paths[1]]
+            .into_iter()
+            .flat_map(|p| [MockCall::Disable(p.clone()), MockCall::Enable(p.clone())])
+            .collect();
+        assert_eq!(toggles, expected);
+    }
+
+    #[test]
+    fn persistent_hides_touch_only_listed_devices() {
+        let mock = MockPlatform::new(MockPlatform::default_devices());
+        let persistent = vec![paths(&mock)[2].clone()];
+
+        apply_persistent_hides(&mock, &persistent);
+
+        assert_eq!(mock.hidden(), persistent);
+        assert_eq!(mock.calls().last(), Some(&MockCall::ActivateHiding));
+    }
+
+    #[test]
+    fn presence_follows_the_scripted_device_list() {
+        let mock = MockPlatform::new(MockPlatform::default_devices());
+        assert!(mock.is_device_present("xinput\\slot1").unwrap());
+
+        mock.set_devices(vec![PhysicalDevice::from_xinput_slot(0)]);
+        assert!(!mock.is_device_present("XINPUT\\SLOT1").unwrap());
+    }
+
+    #[test]
+    fn manual_selection_reports_only_marked_devices() {
+        let mock = MockPlatform::new(MockPlatform::default_devices());
+        let marked = mock.enumerate_devices().unwrap()[1].id.clone();
+
+        mock.set_device_selection(DeviceSelection::Manual(vec![marked.clone()]));
+        let ids: Vec<String> = mock
+            .enumerate_devices()
+            .unwrap()
+            .into_iter()
+            .map(|d| d.id)
+            .collect();
+        assert_eq!(ids, [marked]);
+        assert_eq!(mock.enumerate_input_devices().unwrap().len(), 3);
+
+        mock.set_device_selection(DeviceSelection::Manual(vec![]));
+        assert!(mock.enumerate_devices().unwrap().is_empty());
+    }
+}
@@ -1,11 +1,202 @@
-use crate::device::{DriverStatus, GamepadState, PhysicalDevice};
-use crate::error::Result;
+use crate::config::{AppConfig, RoutingMode, Settings};
+use crate::device::{
+    DeviceCapabilities, DeviceType, DriverStatus, GamepadState, HideCandidate, PhysicalDevice,
+};
+use crate::error::{PadSwitchError, Result};
+use crate::input_loop::ForwardingOptions;
+use crate::mapping::AxisOverride;
+use serde::Serialize;
+use std::collections::BTreeMap;
 use std::sync::Arc;
+use std::time::Duration;
 
 /// Enumerate connected physical game controllers and check driver status.
 pub trait DeviceEnumerator: Send + Sync {
     fn enumerate_devices(&self) -> Result<Vec<PhysicalDevice>>;
     fn check_drivers(&self) -> Result<DriverStatus>;
+    /// Which hiding and virtual-controller technology this backend uses, for the UI
+    /// (e.g. "Windows: HidHide + ViGEmBus").
+    fn backend_description(&self) -> String;
+    /// Read up to `count` raw input frames from a device as hex strings (troubleshooting).
+    /// Bounded by `RAW_REPORT_TIMEOUT` so it can't hang on an idle device.
+    fn read_raw_report(&self, instance_path: &str, count: u32) -> Result<Vec<String>>;
+    /// Whether the device at `instance_path` is still present. Backends override this
+    /// with a targeted lookup; the default falls back to a full enumeration.
+    fn is_device_present(&self, instance_path: &str) -> Result<bool> {
+        Ok(self
+            .enumerate_devices()?
+            .iter()
+            .any(|d| same_instance_path(&d.instance_path, instance_path)))
+    }
+    /// List the buttons and axes a device supports (read-only, for the mapping editor).
+    fn device_capabilities(&self, device: &PhysicalDevice) -> Result<DeviceCapabilities>;
+    /// Every input device, whether or not it passes the game-controller heuristic, so
+    /// the user can pick controllers by hand. Backends without a heuristic return
+    /// `enumerate_devices`.
+    fn enumerate_input_devices(&self) -> Result<Vec<PhysicalDevice>> {
+        self.enumerate_devices()
+    }
+    /// Which devices `enumerate_devices` reports from now on.
+    fn set_device_selection(&self, _selection: DeviceSelection) {}
+}
+
+/// Which devices `enumerate_devices` reports.
+#[derive(Debug, Clone, Default)]
+pub enum DeviceSelection {
+    /// Whatever the backend's heuristic classifies as a game controller.
+    #[default]
+    Heuristic,
+    /// Manual device mode: only these device ids, from all input devices.
+    Manual(Vec<String>),
+}
+
+impl DeviceSelection {
+    pub fn from_config(config: &AppConfig) -> Self {
+        if config.settings.manual_device_mode {
+            Self::Manual(config.marked_controllers.clone())
+        } else {
+            Self::Heuristic
+        }
+    }
+
+    /// Whether enumeration has to look past the heuristic.
+    pub fn is_manual(&self) -> bool {
+        matches!(self, Self::Manual(_))
+    }
+
+    /// Drop the devices this selection excludes.
+    pub fn apply(&self, mut devices: Vec<PhysicalDevice>) -> Vec<PhysicalDevice> {
+        if let Self::Manual(ids) = self {
+            devices.retain(|d| ids.contains(&d.id));
+        }
+        devices
+    }
+}
+
+/// USB vendor ID of Microsoft. Bluetooth Xbox pads enumerate as HID game
+/// controllers yet take an XInput slot through the `xinputhid` filter.
+const MICROSOFT_VID: u16 = 0x045E;
+
+/// Merge enumeration entries that are the same physical controller, keeping the
+/// richer entry (real instance path, name, VID/PID) and the XInput slot of either:
+///
+/// - entries with the same container (`container_of`, e.g. a composite pad's USB
+///   parent and HID child) collapse into one;
+/// - an XInput fallback entry (`PhysicalDevice::from_xinput_slot`) is folded into a
+///   slot-less Microsoft HID controller, which is the pad that took that slot.
+pub fn dedupe_devices(
+    devices: Vec<PhysicalDevice>,
+    container_of: impl Fn(&PhysicalDevice) -> Option<String>,
+) -> Vec<PhysicalDevice> {
+    let richness = |d: &PhysicalDevice| {
+        (
+            !is_xinput_fallback(d),
+            d.vendor_id != 0 && d.product_id != 0,
+            d.device_type == DeviceType::XInput,
+        )
+    };
+
+    let mut merged: Vec<(Option<String>, PhysicalDevice)> = Vec::new();
+    for device in devices {
+        let container = container_of(&device).map(|c| c.to_uppercase());
+        let existing = container
+            .as_ref()
+            .and_then(|c| merged.iter_mut().find(|(k, _)| k.as_ref() == Some(c)));
+        let Some((_, kept)) = existing else {
+            merged.push((container, device));
+            continue;
+        };
+        log::debug!(
+            "Merging duplicate entry {} into {}",
+            device.instance_path,
+            kept.instance_path
+        );
+        let (mut keep, other) = if richness(&device) > richness(kept) {
+            (device, kept.clone())
+        } else {
+            (kept.clone(), device)
+        };
+        absorb(&mut keep, &other);
+        *kept = keep;
+    }
+    let mut devices: Vec<PhysicalDevice> = merged.into_iter().map(|(_, d)| d).collect();
+
+    let fallbacks: Vec<PhysicalDevice> = devices
+        .iter()
+        .filter(|d| is_xinput_fallback(d))
+        .cloned()
+        .collect();
+    for fallback in fallbacks {
+        let owner = devices
+            .iter_mut()
+            .find(|d| d.vendor_id == MICROSOFT_VID && d.xinput_slot.is_none());
+        if let Some(owner) = owner {
+            log::debug!(
+                "XInput slot {:?} belongs to {} — dropping fallback entry",
+                fallback.xinput_slot,
+                owner.instance_path
+            );
+            absorb(owner, &fallback);
+            devices.retain(|d| d.id != fallback.id);
+        }
+    }
+    devices
+}
+
+/// Entry made by `PhysicalDevice::from_xinput_slot` for a slot with no known device.
+fn is_xinput_fallback(device: &PhysicalDevice) -> bool {
+    device.instance_path.starts_with("XINPUT\\")
+}
+
+/// Carry the XInput slot and type over from a duplicate entry.
+fn absorb(keep: &mut PhysicalDevice, duplicate: &PhysicalDevice) {
+    if keep.xinput_slot.is_none() && duplicate.xinput_slot.is_some() {
+        keep.xinput_slot = duplicate.xinput_slot;
+        keep.device_type = DeviceType::XInput;
+        keep.xinput_overflow = false;
+    }
+}
+
+/// Upper bound on how long `read_raw_report` may block.
+pub const RAW_REPORT_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Canonical form of a device instance or interface path, for comparing and keying
+/// only — never pass it back to SetupDi or HidHide. Sources disagree on case, on
+/// `/` vs `\`, on doubled separators (escaped strings) and on trailing separators,
+/// whitespace or NULs (multi-string lists). A leading `\\?\` is kept, so an
+/// interface path never matches its instance path.
+pub fn normalize_instance_path(path: &str) -> String {
+    let trimmed = path.trim_matches(|c: char| c.is_whitespace() || c == '\0');
+    let mut out = String::with_capacity(trimmed.len());
+    for (i, c) in trimmed.chars().enumerate() {
+        let c = if c == '/' {
+            '\\'
+        } else {
+            c.to_ascii_uppercase()
+        };
+        // Collapse doubled separators, except the leading pair of `\\?\`
+        if c == '\\' && i > 1 && out.ends_with('\\') {
+            continue;
+        }
+        out.push(c);
+    }
+    let len = out.trim_end_matches('\\').len();
+    out.truncate(len);
+    out
+}
+
+/// Whether two paths name the same device (see `normalize_instance_path`).
+pub fn same_instance_path(a: &str, b: &str) -> bool {
+    normalize_instance_path(a) == normalize_instance_path(b)
+}
+
+/// Format bytes as space-separated hex (e.g. "00 7f ff").
+pub fn to_hex(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect::<Vec<_>>()
+        .join(" ")
 }
 
 /// Hide/unhide physical devices from other applications (HidHide on Windows).
@@ -17,12 +208,33 @@ pub trait DeviceHider: Send + Sync {
     fn unhide_device(&self, instance_path: &str) -> Result<()>;
     /// Add our process to HidHide whitelist so we can still read hidden devices.
     fn whitelist_self(&self) -> Result<()>;
+    /// Add another application to the whitelist. Returns `true` if we added it
+    /// (so cleanup only removes our own entries), `false` if it was already there.
+    fn whitelist_app(&self, app_path: &str) -> Result<bool>;
+    /// Remove an application from the whitelist.
+    fn unwhitelist_app(&self, app_path: &str) -> Result<()>;
     /// Disable a device via OS APIs (minimal mode). May require admin.
     fn disable_device(&self, instance_path: &str) -> Result<()>;
     /// Enable a device via OS APIs (minimal mode).
     fn enable_device(&self, instance_path: &str) -> Result<()>;
+    /// Activate the hiding driver globally (HidHide on Windows). No-op on other platforms.
+    fn activate_hiding(&self) -> Result<()>;
     /// Deactivate the hiding driver globally (HidHide on Windows). No-op on other platforms.
     fn deactivate_hiding(&self) -> Result<()>;
+    /// Whether the hiding driver is globally active. Only HidHide has a global switch.
+    fn hiding_active(&self) -> Result<bool> {
+        Err(PadSwitchError::PlatformNotSupported(
+            "The HidHide active state is only available on Windows".into(),
+        ))
+    }
+    /// Every path that could be blacklisted for a device (instance, children, HID
+    /// interfaces, parents) and whether each is on the blacklist now. Read-only,
+    /// for debugging devices that stay visible; only HidHide has a blacklist.
+    fn hide_candidates(&self, _instance_path: &str) -> Result<Vec<HideCandidate>> {
+        Err(PadSwitchError::PlatformNotSupported(
+            "HidHide candidate paths are only available on Windows".into(),
+        ))
+    }
 }
 
 /// Create/destroy virtual XInput controllers and forward gamepad state.
@@ -31,6 +243,14 @@ pub trait VirtualControllerManager: Send + Sync {
     fn destroy_virtual_controller(&self, index: u32) -> Result<()>;
     fn read_gamepad_state(&self, instance_path: &str) -> Result<GamepadState>;
     fn write_virtual_state(&self, index: u32, state: &GamepadState) -> Result<()>;
+    /// Set the rumble motor speeds of a physical controller (0 stops the motor).
+    fn set_rumble(&self, instance_path: &str, left: u16, right: u16) -> Result<()>;
+    /// Whether `set_rumble` on this controller reaches any motor. Takes the same
+    /// paths as `set_rumble`; paths it can't address report `false`.
+    fn supports_rumble(&self, instance_path: &str) -> Result<bool>;
+    /// Per-device axis overrides (by device id) for `read_gamepad_state`. Only the
+    /// Linux backend reads raw axes; the others ignore them.
+    fn set_axis_overrides(&self, _overrides: BTreeMap<String, Vec<AxisOverride>>) {}
 }
 
 /// Combined trait for full platform support.
@@ -39,6 +259,7 @@ pub trait PlatformServices: DeviceEnumerator + DeviceHider + VirtualControllerMa
 // Blanket impl: anything implementing all three sub-traits is a PlatformServices.
 impl<T: DeviceEnumerator + DeviceHider + VirtualControllerManager> PlatformServices for T {}
 
+pub mod mock;
 #[cfg(target_os = "windows")]
 mod windows;
 #[cfg(target_os = "macos")]
@@ -46,22 +267,125 @@ mod macos;
 #[cfg(target_os = "linux")]
 mod linux;
 
+/// Set to `1` to run against `mock::MockPlatform` instead of real drivers.
+pub const MOCK_ENV: &str = "PADSWITCH_MOCK";
+
 /// Create the platform-appropriate service provider (singleton-friendly).
-pub fn create_platform() -> Arc<dyn PlatformServices> {
+/// `settings` are read once at startup (e.g. which XInput DLL to load).
+/// Device state changes made through it go to the audit log when enabled.
+pub fn create_platform(settings: &Settings) -> Arc<dyn PlatformServices> {
+    Arc::new(crate::audit::AuditedPlatform::new(create_backend(settings)))
+}
+
+fn create_backend(settings: &Settings) -> Arc<dyn PlatformServices> {
+    if std::env::var(MOCK_ENV).is_ok_and(|v| v == "1") {
+        log::warn!("{}=1 — using the mock platform backend", MOCK_ENV);
+        return Arc::new(mock::MockPlatform::from_env());
+    }
     #[cfg(target_os = "windows")]
     {
-        Arc::new(windows::WindowsPlatform::new())
+        Arc::new(windows::WindowsPlatform::new(
+            settings.xinput_version,
+            settings.forward_guide_button,
+            settings.hide_interface_paths,
+        ))
     }
     #[cfg(target_os = "macos")]
     {
+        let _ = settings;
         Arc::new(macos::MacOSPlatform::new())
     }
     #[cfg(target_os = "linux")]
     {
+        let _ = settings;
         Arc::new(linux::LinuxPlatform::new())
     }
 }
 
+/// How long a power-cycled device stays disabled before it's re-enabled.
+const POWER_CYCLE_PAUSE: Duration = Duration::from_millis(250);
+
+/// How `restore_devices` brings devices back.
+#[derive(Debug, Clone, Default)]
+pub struct RestoreOptions {
+    /// Instance paths to restore first, in this order (case-insensitive).
+    /// Devices not listed follow in their original order.
+    pub priority: Vec<String>,
+    /// Disable then re-enable each device instead of only enabling it.
+    pub power_cycle: bool,
+}
+
+impl RestoreOptions {
+    /// Options from settings. Power-cycling needs SetupDi, so it falls back to a
+    /// plain enable when not elevated.
+    pub fn from_settings(settings: &Settings) -> Self {
+        let power_cycle = settings.reset_power_cycle && is_elevated();
+        if settings.reset_power_cycle && !power_cycle {
+            log::warn!("Reset: power-cycle needs elevation, falling back to plain enable");
+        }
+        Self {
+            priority: settings.reset_priority.clone(),
+            power_cycle,
+        }
+    }
+
+    /// `instance_paths` reordered so prioritized devices come first.
+    fn order<'a>(&self, instance_paths: &'a [String]) -> Vec<&'a String> {
+        let rank = |path: &String| {
+            self.priority
+                .iter()
+                .position(|p| same_instance_path(p, path))
+                .unwrap_or(usize::MAX)
+        };
+        let mut ordered: Vec<&String> = instance_paths.iter().collect();
+        ordered.sort_by_key(|path| rank(path)); // stable: unlisted keep their order
+        ordered
+    }
+}
+
+/// Re-enable (or power-cycle) and unhide each device, then deactivate hiding
+/// globally. Used by the reset commands; every step is idempotent and failures are
+/// only logged.
+pub fn restore_devices(
+    manager: &dyn PlatformServices,
+    instance_paths: &[String],
+    options: &RestoreOptions,
+) {
+    for path in options.order(instance_paths) {
+        if options.power_cycle {
+            match manager.disable_device(path) {
+                Ok(()) => std::thread::sleep(POWER_CYCLE_PAUSE),
+                Err(e) => log::warn!("Reset: disable_device failed for {}: {}", path, e),
+            }
+        }
+        if let Err(e) = manager.enable_device(path) {
+            log::warn!("Reset: enable_device failed for {}: {}", path, e);
+        }
+        if let Err(e) = manager.unhide_device(path) {
+            log::warn!("Reset: unhide_device failed for {}: {}", path, e);
+        }
+    }
+    if let Err(e) = manager.deactivate_hiding() {
+        log::warn!("Reset: deactivate_hiding failed: {}", e);
+    }
+}
+
+/// Hide every persistently-hidden device and activate hiding if there are any.
+/// Idempotent; called at startup, after adding an entry and after forwarding stops.
+pub fn apply_persistent_hides(manager: &dyn PlatformServices, instance_paths: &[String]) {
+    if instance_paths.is_empty() {
+        return;
+    }
+    for path in instance_paths {
+        if let Err(e) = manager.hide_device(path) {
+            log::warn!("Persistent hide failed for {}: {}", path, e);
+        }
+    }
+    if let Err(e) = manager.activate_hiding() {
+        log::warn!("Failed to activate hiding for persistent hides: {}", e);
+    }
+}
+
 /// Check whether the current process is running with admin/elevated privileges.
 /// Minimal mode (SetupDi disable/enable) requires elevation on Windows.
 pub fn is_elevated() -> bool {
@@ -75,3 +399,252 @@ pub fn is_elevated() -> bool {
         true
     }
 }
+
+/// Environment details for the about box and bug reports.
+#[derive(Debug, Clone, Serialize)]
+pub struct AppInfo {
+    pub app_version: String,
+    /// e.g. "Windows", "Ubuntu 24.04 LTS", "macOS"
+    pub os_name: String,
+    /// e.g. "10.0.22631.4317", kernel release on Linux, "14.5" on macOS
+    pub os_version: String,
+    pub arch: String,
+    pub elevated: bool,
+}
+
+/// Collect `AppInfo` for the running process.
+pub fn app_info(app_version: String) -> AppInfo {
+    let (os_name, os_version) = os_version();
+    AppInfo {
+        app_version,
+        os_name,
+        os_version,
+        arch: std::env::consts::ARCH.into(),
+        elevated: is_elevated(),
+    }
+}
+
+/// OS name and version. Falls back to "unknown" when the version can't be read.
+fn os_version() -> (String, String) {
+    let unknown = || "unknown".to_string();
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+        // `ver` reports the real build; GetVersionEx is capped by the app manifest
+        let version = std::process::Command::new("cmd")
+            .args(["/C", "ver"])
+            .creation_flags(CREATE_NO_WINDOW)
+            .output()
+            .ok()
+            .and_then(|out| {
+                let text = String::from_utf8_lossy(&out.stdout).into_owned();
+                let start = text.find("Version ")? + "Version ".len();
+                let end = text[start..].find(']')? + start;
+                Some(text[start..end].trim().to_string())
+            })
+            .unwrap_or_else(unknown);
+        ("Windows".into(), version)
+    }
+    #[cfg(target_os = "linux")]
+    {
+        let name = std::fs::read_to_string("/etc/os-release")
+            .ok()
+            .and_then(|release| {
+                release.lines().find_map(|line| {
+                    let value = line.strip_prefix("PRETTY_NAME=")?;
+                    Some(value.trim_matches('"').to_string())
+                })
+            })
+            .unwrap_or_else(|| "Linux".into());
+        let kernel = std::fs::read_to_string("/proc/sys/kernel/osrelease")
+            .map(|s| s.trim().to_string())
+            .unwrap_or_else(|_| unknown());
+        (name, kernel)
+    }
+    #[cfg(target_os = "macos")]
+    {
+        let version = std::process::Command::new("sw_vers")
+            .arg("-productVersion")
+            .output()
+            .ok()
+            .map(|out| String::from_utf8_lossy(&out.stdout).trim().to_string())
+            .filter(|v| !v.is_empty())
+            .unwrap_or_else(unknown);
+        ("macOS".into(), version)
+    }
+}
+
+/// Whether running a routing mode with the given options needs admin privileges
+/// on this platform. Minimal mode and Force mode's hard hide both use SetupDi.
+pub fn requires_elevation(mode: &RoutingMode, options: &ForwardingOptions) -> bool {
+    #[cfg(target_os = "windows")]
+    {
+        match mode {
+            RoutingMode::Minimal => true,
+            RoutingMode::Force => options.hard_hide,
+        }
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        let _ = (mode, options);
+        false
+    }
+}
+
+/// Start a new elevated instance of the current executable (UAC prompt on Windows).
+/// The caller is responsible for exiting the current instance on success.
+pub fn relaunch_elevated() -> Result<()> {
+    #[cfg(target_os = "windows")]
+    {
+        use windows::core::PCWSTR;
+        use windows::Win32::Foundation::{GetLastError, ERROR_CANCELLED, HWND};
+        use windows::Win32::UI::Shell::ShellExecuteW;
+        use windows::Win32::UI::WindowsAndMessaging::SW_SHOWNORMAL;
+
+        let exe = std::env::current_exe().map_err(|e| {
+            crate::error::PadSwitchError::Platform(format!("Failed to get current exe: {}", e))
+        })?;
+        let to_wide =
+            |s: &str| -> Vec<u16> { s.encode_utf16().chain(std::iter::once(0)).collect() };
+        let verb = to_wide("runas");
+        let file = to_wide(&exe.to_string_lossy());
+
+        let result = unsafe {
+            ShellExecuteW(
+                HWND::default(),
+                PCWSTR(verb.as_ptr()),
+                PCWSTR(file.as_ptr()),
+                PCWSTR::null(),
+                PCWSTR::null(),
+                SW_SHOWNORMAL,
+            )
+        };
+        // ShellExecuteW returns a value > 32 on success. Declining the UAC prompt
+        // yields SE_ERR_ACCESSDENIED with ERROR_CANCELLED as the last error.
+        const SE_ERR_ACCESSDENIED: isize = 5;
+        let code = result.0 as isize;
+        if code <= 32 {
+            if code == SE_ERR_ACCESSDENIED || unsafe { GetLastError() } == ERROR_CANCELLED {
+                return Err(crate::error::PadSwitchError::ElevationCancelled(
+                    "The administrator prompt was declined. PadSwitch keeps running unelevated."
+                        .into(),
+                ));
+            }
+            return Err(crate::error::PadSwitchError::Platform(format!(
+                "Failed to relaunch as administrator (code {})",
+                code
+            )));
+        }
+        Ok(())
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        Err(crate::error::PadSwitchError::PlatformNotSupported(
+            "Relaunching elevated is only supported on Windows".into(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hid_pad(instance_path: &str, vendor_id: u16) -> PhysicalDevice {
+        PhysicalDevice {
+            id: instance_path.to_lowercase(),
+            name: "Xbox Wireless Controller".into(),
+            instance_path: instance_path.into(),
+            device_type: DeviceType::DirectInput,
+            hidden: false,
+            connected: true,
+            vendor_id,
+            product_id: 0x0B13,
+            xinput_slot: None,
+            xinput_overflow: false,
+        }
+    }
+
+    #[test]
+    fn xinput_fallback_folds_into_the_bluetooth_pad_holding_the_slot() {
+        // A Bluetooth Xbox pad enumerates as a HID game controller, so its XInput
+        // slot goes unmatched and gets a fallback entry for the same pad
+        let devices = vec![
+            hid_pad(
+                "HID\\{00001124-0000-1000-8000-00805F9B34FB}_VID&0002045E_PID&0B13\\1",
+                0x045E,
+            ),
+            PhysicalDevice::from_xinput_slot(0),
+        ];
+
+        let deduped = dedupe_devices(devices, |_| None);
+
+        assert_eq!(deduped.len(), 1);
+        assert_eq!(deduped[0].name, "Xbox Wireless Controller");
+        assert_eq!(deduped[0].xinput_slot, Some(0));
+        assert_eq!(deduped[0].device_type, DeviceType::XInput);
+    }
+
+    #[test]
+    fn fallback_without_a_matching_pad_is_kept() {
+        let devices = vec![
+            hid_pad("HID\\VID_054C&PID_0CE6\\1", 0x054C),
+            PhysicalDevice::from_xinput_slot(1),
+        ];
+
+        let deduped = dedupe_devices(devices, |_| None);
+
+        assert_eq!(deduped.len(), 2);
+        assert!(deduped[0].xinput_slot.is_none());
+    }
+
+    #[test]
+    fn entries_sharing_a_container_keep_the_richer_one() {
+        let mut parent = hid_pad("USB\\VID_045E&PID_0B12\\ABC", 0x045E);
+        parent.vendor_id = 0;
+        parent.product_id = 0;
+        let mut child = hid_pad("USB\\VID_045E&PID_0B12&MI_00\\7&1", 0x045E);
+        child.device_type = DeviceType::XInput;
+        child.xinput_slot = Some(2);
+        let other = hid_pad("USB\\VID_045E&PID_0B12\\DEF", 0x045E);
+
+        let container =
+            |d: &PhysicalDevice| (!d.instance_path.ends_with("DEF")).then(|| "{pad-1}".to_string());
+        let deduped = dedupe_devices(vec![parent, child.clone(), other], container);
+
+        assert_eq!(deduped.len(), 2);
+        assert_eq!(deduped[0].instance_path, child.instance_path);
+        assert_eq!(deduped[0].xinput_slot, Some(2));
+        assert!(deduped[1].instance_path.ends_with("DEF"));
+    }
+
+    #[test]
+    fn instance_path_variants_compare_equal() {
+        let canonical = r"HID\VID_045E&PID_028E&IG_00\7&2A1B3C4D&0&0000";
+        for variant in [
+            r"hid\vid_045e&pid_028e&ig_00\7&2a1b3c4d&0&0000",
+            "HID/VID_045E&PID_028E&IG_00/7&2A1B3C4D&0&0000",
+            r"HID\\VID_045E&PID_028E&IG_00\\7&2A1B3C4D&0&0000",
+            "HID\\VID_045E&PID_028E&IG_00\\7&2A1B3C4D&0&0000\\",
+            "HID\\VID_045E&PID_028E&IG_00\\7&2A1B3C4D&0&0000\0",
+            " HID\\VID_045E&PID_028E&IG_00\\7&2A1B3C4D&0&0000\r\n",
+        ] {
+            assert!(same_instance_path(canonical, variant), "{:?}", variant);
+        }
+        assert!(!same_instance_path(
+            canonical,
+            r"HID\VID_045E&PID_028E&IG_00\7&2A1B3C4D&0&0001"
+        ));
+    }
+
+    #[test]
+    fn interface_paths_stay_distinct_from_instance_paths() {
+        let interface =
+            r"\\?\hid#vid_054c&pid_0ce6#7&1&0&0000#{4d1e55b2-f16f-11cf-88cb-001111000030}";
+        assert!(normalize_instance_path(interface).starts_with(r"\\?\HID#"));
+        assert!(!same_instance_path(
+            interface,
+            r"HID\VID_054C&PID_0CE6\7&1&0&0000"
+        ));
+    }
+}
@@ -1,43 +1,142 @@
-use crate::device::{DeviceType, DriverStatus, GamepadState, PhysicalDevice};
+use crate::config::XInputVersion;
+use crate::device::{
+    DeviceCapabilities, DeviceType, DriverStatus, GamepadState, HideCandidate, HideCandidateKind,
+    PhysicalDevice,
+};
 use crate::error::{PadSwitchError, Result};
+use crate::hid::imp::HidDevice;
 use crate::hidhide::imp::HidHide;
-use crate::platform::{DeviceEnumerator, DeviceHider, VirtualControllerManager};
+use crate::platform::{
+    dedupe_devices, normalize_instance_path, to_hex, DeviceEnumerator, DeviceHider,
+    DeviceSelection, VirtualControllerManager, RAW_REPORT_TIMEOUT,
+};
 use crate::setupdi::imp as setupdi;
 use crate::vigem;
-use std::sync::Mutex;
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+/// XInput DLLs tried by `XInputVersion::Auto`, newest first.
+const XINPUT_DLLS: [&str; 3] = ["xinput1_4.dll", "xinput1_3.dll", "xinput9_1_0.dll"];
+
+/// XInput only has four user slots; further XInput devices get none.
+const XINPUT_MAX_SLOTS: usize = 4;
+
+/// DLLs that export `XInputGetStateEx` (ordinal 100), which reports the Guide button.
+const GUIDE_CAPABLE_DLLS: [&str; 2] = ["xinput1_4.dll", "xinput1_3.dll"];
+
+/// `XINPUT_CAPABILITIES::Flags` bit for force-feedback capable devices.
+const XINPUT_CAPS_FFB_SUPPORTED: u16 = 0x0001;
+
+/// How often a missing XInput DLL is looked for again (see `WindowsPlatform::xinput`).
+const XINPUT_RELOAD_INTERVAL: Duration = Duration::from_secs(2);
+
+/// The loaded XInput DLL, if any.
+struct XInput {
+    handle: Option<rusty_xinput::XInputHandle>,
+    /// DLL the handle was loaded from.
+    dll: Option<&'static str>,
+    /// Last load attempt, to throttle retries while no DLL loads.
+    last_attempt: Instant,
+}
 
 /// Windows implementation using SetupAPI + XInput + HidHide + ViGEmBus.
 pub struct WindowsPlatform {
-    xinput: Mutex<Option<rusty_xinput::XInputHandle>>,
+    xinput: Mutex<XInput>,
+    xinput_version: XInputVersion,
+    /// Read state via `XInputGetStateEx` (when the DLL has it) so the Guide button
+    /// comes through.
+    forward_guide_button: bool,
+    /// Also blacklist the HID interface paths of hidden devices and their children.
+    hide_interface_paths: bool,
+    /// Interface paths added per hidden instance path (uppercased), so unhide can
+    /// remove them even once the device is gone.
+    hidden_interfaces: Mutex<HashMap<String, Vec<String>>>,
+    /// Which devices `enumerate_devices` reports.
+    device_selection: Mutex<DeviceSelection>,
 }
 
 impl WindowsPlatform {
-    pub fn new() -> Self {
-        let handle = rusty_xinput::XInputHandle::load_default().ok();
-        Self {
-            xinput: Mutex::new(handle),
+    pub fn new(
+        version: XInputVersion,
+        forward_guide_button: bool,
+        hide_interface_paths: bool,
+    ) -> Self {
+        let (handle, dll) = match load_xinput(version) {
+            Some((handle, dll)) => (Some(handle), Some(dll)),
+            None => (None, None),
+        };
+        let platform = Self {
+            xinput: Mutex::new(XInput {
+                handle,
+                dll,
+                last_attempt: Instant::now(),
+            }),
+            xinput_version: version,
+            forward_guide_button,
+            hide_interface_paths,
+            hidden_interfaces: Mutex::new(HashMap::new()),
+            device_selection: Mutex::new(DeviceSelection::default()),
+        };
+        platform.warn_if_no_guide(dll);
+        platform
+    }
+
+    /// The XInput handle. If no DLL loaded yet (e.g. the runtime was still being
+    /// installed at startup), loading is retried here, at most every
+    /// `XINPUT_RELOAD_INTERVAL`, instead of failing until the app restarts.
+    fn xinput(&self) -> MutexGuard<'_, XInput> {
+        let mut xinput = self.xinput.lock().unwrap();
+        if xinput.handle.is_none() && xinput.last_attempt.elapsed() >= XINPUT_RELOAD_INTERVAL {
+            xinput.last_attempt = Instant::now();
+            if let Some((handle, dll)) = load_xinput(self.xinput_version) {
+                log::info!("XInput loaded on retry ({})", dll);
+                xinput.handle = Some(handle);
+                xinput.dll = Some(dll);
+                self.warn_if_no_guide(Some(dll));
+            }
+        }
+        xinput
+    }
+
+    /// Whether reads use `XInputGetStateEx`, given the DLL that's loaded.
+    fn guide_button(&self, dll: Option<&str>) -> bool {
+        self.forward_guide_button && dll.is_some_and(|d| GUIDE_CAPABLE_DLLS.contains(&d))
+    }
+
+    fn warn_if_no_guide(&self, dll: Option<&str>) {
+        if self.forward_guide_button && !self.guide_button(dll) {
+            log::warn!(
+                "Guide button forwarding needs xinput1_4 or xinput1_3 (loaded: {})",
+                dll.unwrap_or("none")
+            );
         }
     }
 
     /// Get connected XInput slot numbers (0-3).
     fn connected_xinput_slots(&self) -> Vec<u32> {
-        let guard = self.xinput.lock().unwrap();
-        let Some(handle) = guard.as_ref() else {
+        let xinput = self.xinput();
+        let Some(handle) = xinput.handle.as_ref() else {
             return vec![];
         };
         (0..4u32).filter(|&s| handle.get_state(s).is_ok()).collect()
     }
-}
 
-impl DeviceEnumerator for WindowsPlatform {
-    fn enumerate_devices(&self) -> Result<Vec<PhysicalDevice>> {
+    /// Devices from SetupAPI (game controllers, plus every HID device with
+    /// `include_all_hid`) with their XInput slots, or bare XInput slots if SetupAPI
+    /// finds nothing.
+    fn enumerate(&self, include_all_hid: bool) -> Result<Vec<PhysicalDevice>> {
         let connected_slots = self.connected_xinput_slots();
 
         // Try real device enumeration via SetupAPI
-        let real_devices = setupdi::enumerate_game_controllers().unwrap_or_else(|e| {
-            log::warn!("SetupAPI enumeration failed, falling back to XInput-only: {}", e);
-            vec![]
-        });
+        let real_devices =
+            setupdi::enumerate_game_controllers(include_all_hid).unwrap_or_else(|e| {
+                log::warn!(
+                    "SetupAPI enumeration failed, falling back to XInput-only: {}",
+                    e
+                );
+                vec![]
+            });
 
         if !real_devices.is_empty() {
             let mut devices = Vec::new();
@@ -46,6 +145,7 @@ impl DeviceEnumerator for WindowsPlatform {
             // Only XInput devices occupy XInput slots 0-3; DirectInput devices
             // don't get a slot number assigned.
             let mut slot_iter = connected_slots.iter().copied();
+            let slots_full = connected_slots.len() >= XINPUT_MAX_SLOTS;
 
             for dev in &real_devices {
                 let xinput_slot = if dev.is_xinput {
@@ -57,6 +157,13 @@ impl DeviceEnumerator for WindowsPlatform {
                             dev.name,
                             dev.instance_path
                         );
+                    } else if slots_full {
+                        log::info!(
+                            "XInput device without a free slot (all {} taken): {} ({})",
+                            XINPUT_MAX_SLOTS,
+                            dev.name,
+                            dev.instance_path
+                        );
                     }
                     slot
                 } else {
@@ -82,6 +189,7 @@ impl DeviceEnumerator for WindowsPlatform {
                     vendor_id: dev.vendor_id,
                     product_id: dev.product_id,
                     xinput_slot,
+                    xinput_overflow: dev.is_xinput && xinput_slot.is_none() && slots_full,
                 });
             }
 
@@ -92,7 +200,9 @@ impl DeviceEnumerator for WindowsPlatform {
                 devices.push(PhysicalDevice::from_xinput_slot(slot));
             }
 
-            return Ok(devices);
+            return Ok(dedupe_devices(devices, |d| {
+                setupdi::container_id(&d.instance_path)
+            }));
         }
 
         // Fallback: XInput-only enumeration (no SetupAPI devices found)
@@ -102,6 +212,25 @@ impl DeviceEnumerator for WindowsPlatform {
             .collect();
         Ok(devices)
     }
+}
+
+impl DeviceEnumerator for WindowsPlatform {
+    fn enumerate_devices(&self) -> Result<Vec<PhysicalDevice>> {
+        let selection = self.device_selection.lock().unwrap().clone();
+        Ok(selection.apply(self.enumerate(selection.is_manual())?))
+    }
+
+    fn enumerate_input_devices(&self) -> Result<Vec<PhysicalDevice>> {
+        self.enumerate(true)
+    }
+
+    fn set_device_selection(&self, selection: DeviceSelection) {
+        *self.device_selection.lock().unwrap() = selection;
+    }
+
+    fn backend_description(&self) -> String {
+        "Windows: SetupAPI + HidHide + ViGEmBus".into()
+    }
 
     fn check_drivers(&self) -> Result<DriverStatus> {
         Ok(DriverStatus {
@@ -109,19 +238,116 @@ impl DeviceEnumerator for WindowsPlatform {
             vigembus_installed: vigem::imp::is_installed(),
             hidhide_version: None,
             vigembus_version: None,
+            xinput_version: self.xinput().dll.map(String::from),
         })
     }
+
+    fn read_raw_report(&self, instance_path: &str, count: u32) -> Result<Vec<String>> {
+        if parse_xinput_slot(instance_path).is_ok() {
+            return Err(PadSwitchError::Platform(format!(
+                "{} is an XInput slot, not a HID device — raw reports are unavailable",
+                instance_path
+            )));
+        }
+
+        let device = HidDevice::open(instance_path)?;
+        let deadline = Instant::now() + RAW_REPORT_TIMEOUT;
+        let mut frames = Vec::new();
+        while frames.len() < count as usize && Instant::now() < deadline {
+            let report = device.read_input_report()?;
+            frames.push(to_hex(&report));
+            std::thread::sleep(Duration::from_millis(16));
+        }
+        Ok(frames)
+    }
+
+    fn is_device_present(&self, instance_path: &str) -> Result<bool> {
+        Ok(match parse_xinput_slot(instance_path) {
+            Ok(slot) => self
+                .xinput()
+                .handle
+                .as_ref()
+                .is_some_and(|handle| handle.get_state(slot).is_ok()),
+            // Real devnode: checked via SetupAPI so disabled (Minimal-hidden) devices count
+            Err(_) => setupdi::is_present(instance_path),
+        })
+    }
+
+    fn device_capabilities(&self, device: &PhysicalDevice) -> Result<DeviceCapabilities> {
+        // XInput devices (and bare XInput slots) always expose the standard layout
+        if device.device_type == DeviceType::XInput
+            || parse_xinput_slot(&device.instance_path).is_ok()
+        {
+            return Ok(DeviceCapabilities::xinput());
+        }
+        HidDevice::open(&device.instance_path)?.capabilities()
+    }
 }
 
 impl DeviceHider for WindowsPlatform {
     fn hide_device(&self, instance_path: &str) -> Result<()> {
+        let mut paths = vec![instance_path.to_string()];
+        if self.hide_interface_paths {
+            let interfaces = setupdi::hid_interface_paths(instance_path);
+            for interface in &interfaces {
+                log::debug!("Hiding interface {} of {}", interface, instance_path);
+            }
+            paths.extend(interfaces.iter().cloned());
+            self.hidden_interfaces
+                .lock()
+                .unwrap()
+                .insert(normalize_instance_path(instance_path), interfaces);
+        }
         let hh = HidHide::open()?;
-        hh.add_to_blacklist(instance_path)
+        hh.add_to_blacklist(&paths)
     }
 
     fn unhide_device(&self, instance_path: &str) -> Result<()> {
+        // Tracked interfaces, plus any present now (hidden by an earlier run or before
+        // the option was turned off)
+        let mut paths = vec![instance_path.to_string()];
+        if let Some(tracked) = self
+            .hidden_interfaces
+            .lock()
+            .unwrap()
+            .remove(&normalize_instance_path(instance_path))
+        {
+            paths.extend(tracked);
+        }
+        paths.extend(setupdi::hid_interface_paths(instance_path));
         let hh = HidHide::open()?;
-        hh.remove_from_blacklist(instance_path)
+        hh.remove_from_blacklist(&paths)
+    }
+
+    fn hide_candidates(&self, instance_path: &str) -> Result<Vec<HideCandidate>> {
+        let blacklist: Vec<String> = HidHide::open()?
+            .blacklist()?
+            .iter()
+            .map(|s| normalize_instance_path(s))
+            .collect();
+        let paths = std::iter::once((instance_path.to_string(), HideCandidateKind::Instance))
+            .chain(
+                setupdi::child_instance_ids(instance_path)
+                    .into_iter()
+                    .map(|p| (p, HideCandidateKind::Child)),
+            )
+            .chain(
+                setupdi::hid_interface_paths(instance_path)
+                    .into_iter()
+                    .map(|p| (p, HideCandidateKind::Interface)),
+            )
+            .chain(
+                setupdi::parent_instance_ids(instance_path)
+                    .into_iter()
+                    .map(|p| (p, HideCandidateKind::Parent)),
+            );
+        Ok(paths
+            .map(|(path, kind)| HideCandidate {
+                blacklisted: blacklist.contains(&normalize_instance_path(&path)),
+                path,
+                kind,
+            })
+            .collect())
     }
 
     fn whitelist_self(&self) -> Result<()> {
@@ -129,7 +355,17 @@ impl DeviceHider for WindowsPlatform {
             .map_err(|e| PadSwitchError::Platform(format!("Failed to get current exe: {}", e)))?;
         let exe_str = exe.to_string_lossy().to_string();
         let hh = HidHide::open()?;
-        hh.add_to_whitelist(&exe_str)
+        hh.add_to_whitelist(&exe_str).map(|_| ())
+    }
+
+    fn whitelist_app(&self, app_path: &str) -> Result<bool> {
+        let hh = HidHide::open()?;
+        hh.add_to_whitelist(app_path)
+    }
+
+    fn unwhitelist_app(&self, app_path: &str) -> Result<()> {
+        let hh = HidHide::open()?;
+        hh.remove_from_whitelist(app_path)
     }
 
     fn disable_device(&self, instance_path: &str) -> Result<()> {
@@ -140,10 +376,19 @@ impl DeviceHider for WindowsPlatform {
         setupdi::enable_device(instance_path)
     }
 
+    fn activate_hiding(&self) -> Result<()> {
+        let hh = HidHide::open()?;
+        hh.set_active(true)
+    }
+
     fn deactivate_hiding(&self) -> Result<()> {
         let hh = HidHide::open()?;
         hh.set_active(false)
     }
+
+    fn hiding_active(&self) -> Result<bool> {
+        HidHide::open()?.is_active()
+    }
 }
 
 impl VirtualControllerManager for WindowsPlatform {
@@ -157,14 +402,18 @@ impl VirtualControllerManager for WindowsPlatform {
 
     fn read_gamepad_state(&self, instance_path: &str) -> Result<GamepadState> {
         let slot = parse_xinput_slot(instance_path)?;
-        let guard = self.xinput.lock().unwrap();
-        let handle = guard
+        let xinput = self.xinput();
+        let handle = xinput
+            .handle
             .as_ref()
-            .ok_or_else(|| PadSwitchError::Platform("XInput not loaded".into()))?;
+            .ok_or_else(|| PadSwitchError::XInputUnavailable(XINPUT_UNAVAILABLE.into()))?;
 
-        let state = handle.get_state(slot).map_err(|_| {
-            PadSwitchError::Platform(format!("Failed to read XInput slot {}", slot))
-        })?;
+        let state = if self.guide_button(xinput.dll) {
+            handle.get_state_ex(slot)
+        } else {
+            handle.get_state(slot)
+        }
+        .map_err(|_| PadSwitchError::Platform(format!("Failed to read XInput slot {}", slot)))?;
 
         Ok(GamepadState {
             buttons: state.raw.Gamepad.wButtons,
@@ -180,8 +429,68 @@ impl VirtualControllerManager for WindowsPlatform {
     fn write_virtual_state(&self, _index: u32, _state: &GamepadState) -> Result<()> {
         Ok(())
     }
+
+    fn set_rumble(&self, instance_path: &str, left: u16, right: u16) -> Result<()> {
+        let slot = parse_xinput_slot(instance_path)?;
+        let xinput = self.xinput();
+        let handle = xinput
+            .handle
+            .as_ref()
+            .ok_or_else(|| PadSwitchError::XInputUnavailable(XINPUT_UNAVAILABLE.into()))?;
+
+        handle.set_state(slot, left, right).map_err(|_| {
+            PadSwitchError::Platform(format!("Failed to set rumble on XInput slot {}", slot))
+        })
+    }
+
+    fn supports_rumble(&self, instance_path: &str) -> Result<bool> {
+        // Only XInput slots can be rumbled; DirectInput devices have none
+        let Ok(slot) = parse_xinput_slot(instance_path) else {
+            return Ok(false);
+        };
+        let xinput = self.xinput();
+        let handle = xinput
+            .handle
+            .as_ref()
+            .ok_or_else(|| PadSwitchError::XInputUnavailable(XINPUT_UNAVAILABLE.into()))?;
+
+        let caps = handle.get_capabilities(slot).map_err(|_| {
+            PadSwitchError::Platform(format!(
+                "Failed to read capabilities of XInput slot {}",
+                slot
+            ))
+        })?;
+        // A nonzero motor resolution means the motor exists
+        Ok(caps.Vibration.wLeftMotorSpeed != 0
+            || caps.Vibration.wRightMotorSpeed != 0
+            || caps.Flags & XINPUT_CAPS_FFB_SUPPORTED != 0)
+    }
 }
 
+/// Load the requested XInput DLL, falling back to the newest available one.
+fn load_xinput(version: XInputVersion) -> Option<(rusty_xinput::XInputHandle, &'static str)> {
+    let requested = match version {
+        XInputVersion::Auto => None,
+        XInputVersion::XInput1_4 => Some(XINPUT_DLLS[0]),
+        XInputVersion::XInput1_3 => Some(XINPUT_DLLS[1]),
+        XInputVersion::XInput9_1_0 => Some(XINPUT_DLLS[2]),
+    };
+    if let Some(dll) = requested {
+        match rusty_xinput::XInputHandle::load(dll) {
+            Ok(handle) => return Some((handle, dll)),
+            Err(e) => log::warn!("Failed to load {} ({:?}) — falling back to auto", dll, e),
+        }
+    }
+    XINPUT_DLLS.iter().find_map(|&dll| {
+        rusty_xinput::XInputHandle::load(dll)
+            .ok()
+            .map(|handle| (handle, dll))
+    })
+}
+
+/// Guidance returned when no XInput DLL could be loaded.
+const XINPUT_UNAVAILABLE: &str = "xinput1_4.dll / xinput1_3.dll could not be loaded. Install the DirectX End-User Runtime or repair Windows system files (sfc /scannow).";
+
 /// Try to extract an XInput slot from a device identifier.
 /// Supports both legacy "XINPUT\SLOT{n}" paths and numeric slot strings.
 fn parse_xinput_slot(instance_path: &str) -> Result<u32> {
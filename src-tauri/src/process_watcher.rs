@@ -1,13 +1,18 @@
+use crate::config::{GameExitBehavior, GameRule};
 use crate::state::AppState;
+use std::collections::HashSet;
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
-use std::time::Duration;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use tauri::{AppHandle, Emitter, Manager};
 
 /// Watches for game processes and auto-activates matching presets.
 pub struct ProcessWatcher {
     running: Arc<AtomicBool>,
     thread_handle: Option<std::thread::JoinHandle<()>>,
+    /// IDs of rules the loop ignores until their game is no longer running
+    /// (or the rule is re-enabled); see `pause_for_reset`.
+    suppressed: Arc<Mutex<HashSet<String>>>,
 }
 
 impl ProcessWatcher {
@@ -15,9 +20,20 @@ impl ProcessWatcher {
         Self {
             running: Arc::new(AtomicBool::new(false)),
             thread_handle: None,
+            suppressed: Arc::new(Mutex::new(HashSet::new())),
         }
     }
 
+    /// Ignore these rules until their game exits.
+    pub fn suppress(&self, rule_ids: impl IntoIterator<Item = String>) {
+        self.suppressed.lock().unwrap().extend(rule_ids);
+    }
+
+    /// Lift a rule's suppression (e.g. the user re-enabled it).
+    pub fn unsuppress(&self, rule_id: &str) {
+        self.suppressed.lock().unwrap().remove(rule_id);
+    }
+
     pub fn start(&mut self, app: AppHandle) {
         if self.running.load(Ordering::SeqCst) {
             return;
@@ -25,10 +41,11 @@ impl ProcessWatcher {
 
         let running = self.running.clone();
         running.store(true, Ordering::SeqCst);
+        let suppressed = self.suppressed.clone();
 
         let handle = std::thread::Builder::new()
             .name("padswitch-process-watcher".into())
-            .spawn(move || watcher_loop(running, app))
+            .spawn(move || watcher_loop(running, suppressed, app))
             .expect("Failed to spawn process watcher thread");
 
         self.thread_handle = Some(handle);
@@ -61,53 +78,146 @@ impl Drop for ProcessWatcher {
 // Watcher loop
 // ---------------------------------------------------------------------------
 
-fn watcher_loop(running: Arc<AtomicBool>, app: AppHandle) {
-    // Track which game rule is currently active (to avoid re-triggering)
-    let mut active_rule_id: Option<String> = None;
+/// Reset's watcher step. Stops the watcher, or with `reset_suppresses_running_games`
+/// keeps it running but suppresses the rules whose game is running now, so the
+/// reset isn't undone on the next poll.
+pub fn pause_for_reset(state: &AppState) {
+    let (suppress, rules) = {
+        let inner = state.lock_inner();
+        (
+            inner.config.settings.reset_suppresses_running_games,
+            inner.config.game_rules.clone(),
+        )
+    };
+    let mut watcher = state.lock_watcher();
+    if !suppress || !watcher.is_running() {
+        watcher.stop();
+        return;
+    }
+    let processes = list_running_processes();
+    let running_rules: Vec<String> = rules
+        .iter()
+        .filter(|r| r.enabled && r.matching_exe(&processes).is_some())
+        .map(|r| r.id.clone())
+        .collect();
+    log::info!(
+        "Reset: suppressing {} running game rule(s)",
+        running_rules.len()
+    );
+    watcher.suppress(running_rules);
+}
+
+fn watcher_loop(running: Arc<AtomicBool>, suppressed: Arc<Mutex<HashSet<String>>>, app: AppHandle) {
+    // Track which game rule is currently active (to avoid re-triggering),
+    // along with the executable that matched it
+    let mut active_rule: Option<(GameRule, String)> = None;
     // Profile that was active before the game launched (for reverting)
     let mut pre_game_profile_id: Option<String> = None;
+    // When the watcher last switched profiles, for `watcher_switch_cooldown_ms`
+    let mut last_switch: Option<Instant> = None;
 
     while running.load(Ordering::SeqCst) {
         let state = app.state::<AppState>();
 
         // Read game rules and current profile (brief lock)
-        let (rules, current_profile_id) = {
+        let (rules, current_profile_id, on_game_exit, cooldown) = {
             let inner = state.lock_inner();
             (
                 inner.config.game_rules.clone(),
                 inner.config.settings.active_profile_id.clone(),
+                inner.config.settings.on_game_exit,
+                Duration::from_millis(inner.config.settings.watcher_switch_cooldown_ms.into()),
             )
         };
 
         let processes = list_running_processes();
 
-        // Find the first enabled rule that matches a running process
-        let matched_rule = rules
-            .iter()
-            .filter(|r| r.enabled)
-            .find(|r| {
-                processes
+        // Suppressed rules stay ignored until their game exits. If the active rule
+        // was suppressed (by a reset), forget it without reverting anything.
+        let suppressed_now: HashSet<String> = {
+            let mut suppressed = suppressed.lock().unwrap();
+            suppressed.retain(|id| {
+                rules
                     .iter()
-                    .any(|p| p.eq_ignore_ascii_case(&r.exe_name))
+                    .any(|r| &r.id == id && r.matching_exe(&processes).is_some())
             });
+            suppressed.clone()
+        };
+        if active_rule
+            .as_ref()
+            .is_some_and(|(rule, _)| suppressed_now.contains(&rule.id))
+        {
+            active_rule = None;
+            pre_game_profile_id = None;
+        }
+
+        // Find the first enabled, unsuppressed rule with any of its executables running
+        let matched_rule = rules
+            .iter()
+            .filter(|r| r.enabled && !suppressed_now.contains(&r.id))
+            .find_map(|r| r.matching_exe(&processes).map(|exe| (r, exe)));
+
+        // A flapping launcher would otherwise restart forwarding on every poll. The
+        // transition is only deferred: it's re-evaluated once the cooldown is over.
+        let transition = match (&active_rule, matched_rule) {
+            (Some((current, _)), Some((rule, _))) => current.id != rule.id,
+            (current, matched) => current.is_some() != matched.is_some(),
+        };
+        if transition && last_switch.is_some_and(|at| at.elapsed() < cooldown) {
+            log::info!(
+                "Profile switch suppressed by cooldown ({}ms since the last one)",
+                last_switch.map_or(0, |at| at.elapsed().as_millis())
+            );
+            if !sleep_while_running(&running, Duration::from_secs(3)) {
+                return;
+            }
+            continue;
+        }
+        if transition {
+            last_switch = Some(Instant::now());
+        }
 
-        match (&active_rule_id, matched_rule) {
-            (None, Some(rule)) => {
+        match (&active_rule, matched_rule) {
+            (None, Some((rule, exe))) if !confirm_after_delay(&running, rule) => {
+                log::info!("Game {} not confirmed after its activation delay", exe);
+            }
+            (None, Some((rule, exe))) => {
                 // Game just launched — activate its profile
                 log::info!(
                     "Game detected: {} — activating profile {}",
-                    rule.exe_name,
+                    exe,
                     rule.profile_id
                 );
                 if activate_profile_internal(&app, &state, &rule.profile_id) {
                     pre_game_profile_id = current_profile_id;
-                    active_rule_id = Some(rule.id.clone());
+                    active_rule = Some((rule.clone(), exe.to_string()));
+                    emit_rule_fired(&app, rule, exe, Some(&rule.profile_id), "activated");
                 }
             }
-            (Some(_), None) => {
+            (Some((exited, exe)), None) if on_game_exit != GameExitBehavior::RevertToPrevious => {
+                // Game exited — keep its profile, optionally stop forwarding
+                log::info!("Game exited — keeping profile ({:?})", on_game_exit);
+                let action = if on_game_exit == GameExitBehavior::StopForwarding {
+                    stop_forwarding_internal(&app, &state);
+                    "stopped"
+                } else {
+                    "kept"
+                };
+                emit_rule_fired(&app, exited, exe, Some(&exited.profile_id), action);
+                active_rule = None;
+                pre_game_profile_id = None;
+            }
+            (Some((exited, exe)), None) => {
                 // Game exited — revert to previous profile
                 log::info!("Game exited — reverting to previous profile");
-                active_rule_id = None;
+                emit_rule_fired(
+                    &app,
+                    exited,
+                    exe,
+                    pre_game_profile_id.as_deref(),
+                    "reverted",
+                );
+                active_rule = None;
 
                 if let Some(ref prev_id) = pre_game_profile_id {
                     activate_profile_internal(&app, &state, prev_id);
@@ -129,15 +239,21 @@ fn watcher_loop(running: Arc<AtomicBool>, app: AppHandle) {
                 }
                 pre_game_profile_id = None;
             }
-            (Some(current_id), Some(rule)) if *current_id != rule.id => {
+            (Some((current, _)), Some((rule, exe)))
+                if current.id != rule.id && !confirm_after_delay(&running, rule) =>
+            {
+                log::info!("Game {} not confirmed after its activation delay", exe);
+            }
+            (Some((current, _)), Some((rule, exe))) if current.id != rule.id => {
                 // Different game matched — switch to new game's profile
                 log::info!(
                     "Game switch: {} — activating profile {}",
-                    rule.exe_name,
+                    exe,
                     rule.profile_id
                 );
                 if activate_profile_internal(&app, &state, &rule.profile_id) {
-                    active_rule_id = Some(rule.id.clone());
+                    active_rule = Some((rule.clone(), exe.to_string()));
+                    emit_rule_fired(&app, rule, exe, Some(&rule.profile_id), "switched");
                 }
             }
             _ => {
@@ -146,13 +262,89 @@ fn watcher_loop(running: Arc<AtomicBool>, app: AppHandle) {
         }
 
         // Poll every 3 seconds
-        for _ in 0..30 {
-            if !running.load(Ordering::SeqCst) {
-                return;
-            }
-            std::thread::sleep(Duration::from_millis(100));
+        if !sleep_while_running(&running, Duration::from_secs(3)) {
+            return;
+        }
+    }
+}
+
+/// Sleep in short steps so a stop request is noticed quickly. Returns `false` if
+/// the watcher was stopped before `duration` elapsed.
+fn sleep_while_running(running: &AtomicBool, duration: Duration) -> bool {
+    let step = Duration::from_millis(100);
+    let mut remaining = duration;
+    while !remaining.is_zero() {
+        if !running.load(Ordering::SeqCst) {
+            return false;
         }
+        let nap = remaining.min(step);
+        std::thread::sleep(nap);
+        remaining -= nap;
     }
+    running.load(Ordering::SeqCst)
+}
+
+/// Wait out a rule's `activation_delay_ms`, then confirm one of its executables is
+/// still running. `false` if it exited meanwhile or the watcher was stopped.
+fn confirm_after_delay(running: &AtomicBool, rule: &GameRule) -> bool {
+    if rule.activation_delay_ms == 0 {
+        return true;
+    }
+    log::info!(
+        "Rule {} matched — waiting {}ms before activating",
+        rule.id,
+        rule.activation_delay_ms
+    );
+    sleep_while_running(
+        running,
+        Duration::from_millis(rule.activation_delay_ms as u64),
+    ) && rule.matching_exe(&list_running_processes()).is_some()
+}
+
+/// Tell the frontend which rule caused a profile change, so it can explain auto-switches.
+/// `exe_name` is the rule executable that matched; `profile_id` is the profile now
+/// active (`None` when reverting to no profile).
+fn emit_rule_fired(
+    app: &AppHandle,
+    rule: &GameRule,
+    exe_name: &str,
+    profile_id: Option<&str>,
+    action: &str,
+) {
+    let _ = app.emit(
+        "game-rule-fired",
+        serde_json::json!({
+            "rule_id": rule.id,
+            "exe_name": exe_name,
+            "profile_id": profile_id,
+            "action": action,
+        }),
+    );
+}
+
+/// Stop forwarding from the watcher thread (no-op if it isn't running).
+fn stop_forwarding_internal(app: &AppHandle, state: &AppState) {
+    let stopped = state.stop_forwarding_with(
+        |inner| {
+            inner
+                .forwarding_active()
+                .then(|| inner.config.persistent_hidden.clone())
+        },
+        |persistent_hidden| {
+            let Some(persistent_hidden) = persistent_hidden else {
+                return false;
+            };
+            crate::platform::apply_persistent_hides(&**state.manager(), &persistent_hidden);
+            true
+        },
+    );
+    if !stopped {
+        return;
+    }
+
+    crate::tray::rebuild_tray_menu(app);
+    let _ = app.emit("forwarding-status", serde_json::json!({ "active": false }));
+    crate::device_refresh::schedule(app);
 }
 
 /// Activate a profile by ID from the watcher thread.
@@ -175,10 +367,11 @@ fn activate_profile_internal(app: &AppHandle, state: &AppState, profile_id: &str
     let _ = inner.config.save();
 
     // If forwarding is active, restart the loop with the new profile's assignments
-    if inner.forwarding_active {
+    if inner.forwarding_active() {
         log::info!("Forwarding active — restarting with new profile");
-        if let Err(e) = inner.restart_forwarding(manager) {
+        if let Err(e) = inner.restart_forwarding(manager, app) {
             log::error!("Failed to restart forwarding: {}", e);
+            state.record_background_error("watcher", &e.to_string());
             let _ = app.emit(
                 "forwarding-status",
                 serde_json::json!({ "active": false, "error": e.to_string() }),
@@ -207,7 +400,7 @@ fn activate_profile_internal(app: &AppHandle, state: &AppState, profile_id: &str
 // ---------------------------------------------------------------------------
 
 #[cfg(target_os = "windows")]
-fn list_running_processes() -> Vec<String> {
+pub(crate) fn list_running_processes() -> Vec<String> {
     use windows::Win32::Foundation::CloseHandle;
     use windows::Win32::System::Diagnostics::ToolHelp::{
         CreateToolhelp32Snapshot, Process32FirstW, Process32NextW, PROCESSENTRY32W,
@@ -254,19 +447,238 @@ fn list_running_processes() -> Vec<String> {
     }
 }
 
+/// Full image paths of running processes whose executable name matches `exe_name`
+/// (case-insensitive). Used to resolve whitelist entries given as bare names.
+#[cfg(target_os = "windows")]
+pub fn find_process_paths(exe_name: &str) -> Vec<String> {
+    use windows::core::PWSTR;
+    use windows::Win32::Foundation::CloseHandle;
+    use windows::Win32::System::Diagnostics::ToolHelp::{
+        CreateToolhelp32Snapshot, Process32FirstW, Process32NextW, PROCESSENTRY32W,
+        TH32CS_SNAPPROCESS,
+    };
+    use windows::Win32::System::Threading::{
+        OpenProcess, QueryFullProcessImageNameW, PROCESS_NAME_WIN32,
+        PROCESS_QUERY_LIMITED_INFORMATION,
+    };
+
+    unsafe {
+        let snapshot = match CreateToolhelp32Snapshot(TH32CS_SNAPPROCESS, 0) {
+            Ok(h) => h,
+            Err(e) => {
+                log::warn!("CreateToolhelp32Snapshot failed: {}", e);
+                return vec![];
+            }
+        };
+
+        let mut entry = PROCESSENTRY32W {
+            dwSize: std::mem::size_of::<PROCESSENTRY32W>() as u32,
+            ..Default::default()
+        };
+
+        let mut paths: Vec<String> = Vec::new();
+
+        if Process32FirstW(snapshot, &mut entry).is_ok() {
+            loop {
+                let end = entry
+                    .szExeFile
+                    .iter()
+                    .position(|&c| c == 0)
+                    .unwrap_or(entry.szExeFile.len());
+                let name = String::from_utf16_lossy(&entry.szExeFile[..end]);
+                if name.eq_ignore_ascii_case(exe_name) {
+                    if let Ok(process) = OpenProcess(
+                        PROCESS_QUERY_LIMITED_INFORMATION,
+                        false,
+                        entry.th32ProcessID,
+                    ) {
+                        let mut buf = vec![0u16; 1024];
+                        let mut len = buf.len() as u32;
+                        if QueryFullProcessImageNameW(
+                            process,
+                            PROCESS_NAME_WIN32,
+                            PWSTR(buf.as_mut_ptr()),
+                            &mut len,
+                        )
+                        .is_ok()
+                        {
+                            let path = String::from_utf16_lossy(&buf[..len as usize]);
+                            if !paths.iter().any(|p| p.eq_ignore_ascii_case(&path)) {
+                                paths.push(path);
+                            }
+                        }
+                        let _ = CloseHandle(process);
+                    }
+                }
+
+                entry.dwSize = std::mem::size_of::<PROCESSENTRY32W>() as u32;
+                if Process32NextW(snapshot, &mut entry).is_err() {
+                    break;
+                }
+            }
+        }
+
+        let _ = CloseHandle(snapshot);
+        paths
+    }
+}
+
+/// Samples the foreground process on its own thread, so the forwarding loop only
+/// reads a flag instead of querying the window system (Linux spawns `xdotool`)
+/// every few frames. Reads as focused whenever the foreground process can't be
+/// determined, so gating fails open. Stops when dropped.
+#[cfg(not(target_os = "macos"))]
+pub(crate) struct FocusSampler {
+    focused: Arc<AtomicBool>,
+    running: Arc<AtomicBool>,
+    handle: Option<std::thread::JoinHandle<()>>,
+}
+
+#[cfg(not(target_os = "macos"))]
+impl FocusSampler {
+    /// Start sampling every `interval`. `None` (forward regardless of focus) if the
+    /// thread can't be spawned.
+    pub fn start(exe_names: Vec<String>, interval: Duration) -> Option<Self> {
+        let focused = Arc::new(AtomicBool::new(true));
+        let running = Arc::new(AtomicBool::new(true));
+        let (flag, alive) = (focused.clone(), running.clone());
+        let spawned = std::thread::Builder::new()
+            .name("padswitch-focus-sampler".into())
+            .spawn(move || {
+                while alive.load(Ordering::SeqCst) {
+                    let focused = foreground_process_name()
+                        .is_none_or(|name| exe_names.iter().any(|n| n.eq_ignore_ascii_case(&name)));
+                    flag.store(focused, Ordering::SeqCst);
+                    std::thread::sleep(interval);
+                }
+            });
+        match spawned {
+            Ok(handle) => Some(Self {
+                focused,
+                running,
+                handle: Some(handle),
+            }),
+            Err(e) => {
+                log::warn!(
+                    "Failed to spawn focus sampler, forwarding regardless of focus: {}",
+                    e
+                );
+                None
+            }
+        }
+    }
+
+    pub fn focused(&self) -> bool {
+        self.focused.load(Ordering::SeqCst)
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+impl Drop for FocusSampler {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::SeqCst);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+/// Executable name of the process owning the foreground window, if it can be determined.
+#[cfg(target_os = "windows")]
+pub(crate) fn foreground_process_name() -> Option<String> {
+    use windows::core::PWSTR;
+    use windows::Win32::Foundation::CloseHandle;
+    use windows::Win32::System::Threading::{
+        OpenProcess, QueryFullProcessImageNameW, PROCESS_NAME_WIN32,
+        PROCESS_QUERY_LIMITED_INFORMATION,
+    };
+    use windows::Win32::UI::WindowsAndMessaging::{GetForegroundWindow, GetWindowThreadProcessId};
+
+    unsafe {
+        let hwnd = GetForegroundWindow();
+        if hwnd.0.is_null() {
+            return None;
+        }
+        let mut pid: u32 = 0;
+        GetWindowThreadProcessId(hwnd, Some(&mut pid));
+        if pid == 0 {
+            return None;
+        }
+
+        let process = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid).ok()?;
+        let mut buf = vec![0u16; 1024];
+        let mut len = buf.len() as u32;
+        let queried = QueryFullProcessImageNameW(
+            process,
+            PROCESS_NAME_WIN32,
+            PWSTR(buf.as_mut_ptr()),
+            &mut len,
+        );
+        let _ = CloseHandle(process);
+        queried.ok()?;
+
+        let path = String::from_utf16_lossy(&buf[..len as usize]);
+        path.rsplit('\\').next().map(String::from)
+    }
+}
+
+/// Executable name of the process owning the active X11 window, via `xdotool`.
+/// `None` on Wayland or when xdotool isn't installed — there is no portable way to
+/// ask the WM. Spawns a process, so keep it off the forwarding loop.
+#[cfg(target_os = "linux")]
+pub(crate) fn foreground_process_name() -> Option<String> {
+    let output = std::process::Command::new("xdotool")
+        .args(["getactivewindow", "getwindowpid"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let pid = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    process_name(std::path::Path::new("/proc").join(pid).as_path())
+}
+
+/// Executable name for a `/proc/<pid>` entry. `comm` is cut to 15 characters
+/// ("RocketLeague.exe" reads "RocketLeague.ex"), so use the program from `cmdline`
+/// (which also holds the Windows name under Wine/Proton), then the `exe` link.
+#[cfg(target_os = "linux")]
+fn process_name(proc_dir: &std::path::Path) -> Option<String> {
+    let base = |path: &str| {
+        path.rsplit(['/', '\\'])
+            .next()
+            .filter(|name| !name.is_empty())
+            .map(String::from)
+    };
+    let cmdline = std::fs::read(proc_dir.join("cmdline")).unwrap_or_default();
+    let program = cmdline.split(|&b| b == 0).next().unwrap_or_default();
+    base(&String::from_utf8_lossy(program))
+        .or_else(|| {
+            base(
+                &std::fs::read_link(proc_dir.join("exe"))
+                    .ok()?
+                    .to_string_lossy(),
+            )
+        })
+        .or_else(|| {
+            let comm = std::fs::read_to_string(proc_dir.join("comm")).ok()?;
+            Some(comm.trim().to_string()).filter(|name| !name.is_empty())
+        })
+}
+
+#[cfg(target_os = "macos")]
+pub(crate) fn foreground_process_name() -> Option<String> {
+    None
+}
+
 #[cfg(target_os = "linux")]
-fn list_running_processes() -> Vec<String> {
+pub(crate) fn list_running_processes() -> Vec<String> {
     let mut names = Vec::new();
     if let Ok(entries) = std::fs::read_dir("/proc") {
         for entry in entries.flatten() {
             let name = entry.file_name();
             if name.to_string_lossy().chars().all(|c| c.is_ascii_digit()) {
-                let comm_path = entry.path().join("comm");
-                if let Ok(comm) = std::fs::read_to_string(&comm_path) {
-                    let trimmed = comm.trim().to_string();
-                    if !trimmed.is_empty() {
-                        names.push(trimmed);
-                    }
+                if let Some(name) = process_name(&entry.path()) {
+                    names.push(name);
                 }
             }
         }
@@ -275,6 +687,6 @@ fn list_running_processes() -> Vec<String> {
 }
 
 #[cfg(target_os = "macos")]
-fn list_running_processes() -> Vec<String> {
+pub(crate) fn list_running_processes() -> Vec<String> {
     vec![]
 }
@@ -0,0 +1,132 @@
+use crate::device::GamepadState;
+use crate::error::{PadSwitchError, Result};
+use crate::mapping::InputMapping;
+use crate::platform::PlatformServices;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// Bump when the file layout changes incompatibly.
+const FORMAT_VERSION: u32 = 1;
+
+/// Longest recording we accept, to keep files and memory bounded.
+pub const MAX_DURATION: Duration = Duration::from_secs(300);
+
+/// One captured controller state, `t_ms` after the recording started.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedFrame {
+    pub t_ms: u64,
+    pub state: GamepadState,
+}
+
+/// A recorded input sequence, stored as JSON.
+///
+/// Frames are only stored when the state changes, so an idle controller
+/// produces a tiny file; `duration_ms` preserves the trailing idle time.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Recording {
+    pub version: u32,
+    /// XInput slot the frames were captured from.
+    pub source_slot: u32,
+    pub duration_ms: u64,
+    pub frames: Vec<RecordedFrame>,
+}
+
+impl Recording {
+    pub fn load(path: &Path) -> Result<Self> {
+        let data = std::fs::read_to_string(path)?;
+        let recording: Self = serde_json::from_str(&data)?;
+        if recording.version != FORMAT_VERSION {
+            return Err(PadSwitchError::Config(format!(
+                "Unsupported recording version {} (expected {})",
+                recording.version, FORMAT_VERSION
+            )));
+        }
+        Ok(recording)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        std::fs::write(path, serde_json::to_string(self)?)?;
+        Ok(())
+    }
+}
+
+/// Capture raw (unmapped) state from an XInput slot for `duration`, polling at ~1000Hz
+/// like the forwarding loop. Blocks the calling thread for the whole duration.
+pub fn record(manager: &dyn PlatformServices, slot: u32, duration: Duration) -> Result<Recording> {
+    let duration = duration.min(MAX_DURATION);
+    let source = slot.to_string();
+    // Fail fast if the slot can't be read at all
+    manager.read_gamepad_state(&source)?;
+
+    let start = Instant::now();
+    let mut frames: Vec<RecordedFrame> = Vec::new();
+    while start.elapsed() < duration {
+        if let Ok(state) = manager.read_gamepad_state(&source) {
+            if frames.last().is_none_or(|f| f.state != state) {
+                frames.push(RecordedFrame {
+                    t_ms: start.elapsed().as_millis() as u64,
+                    state,
+                });
+            }
+        }
+        std::thread::sleep(Duration::from_millis(1));
+    }
+
+    log::info!(
+        "Recorded {} frames from XInput slot {} over {}ms",
+        frames.len(),
+        slot,
+        duration.as_millis()
+    );
+    Ok(Recording {
+        version: FORMAT_VERSION,
+        source_slot: slot,
+        duration_ms: duration.as_millis() as u64,
+        frames,
+    })
+}
+
+/// Play a recording into a temporary virtual controller, applying `mapping` to each
+/// frame exactly as the forwarding loop would. Blocks until playback finishes; the
+/// virtual controller is unplugged afterwards.
+#[cfg(target_os = "windows")]
+pub fn replay(recording: &Recording, mapping: &InputMapping) -> Result<()> {
+    use crate::vigem::imp::to_xgamepad;
+
+    let client = vigem_client::Client::connect().map_err(|e| {
+        PadSwitchError::Forwarding(format!("Failed to connect to ViGEmBus: {:?}", e))
+    })?;
+    let mut target =
+        vigem_client::Xbox360Wired::new(&client, vigem_client::TargetId::XBOX360_WIRED);
+    target.plugin_wait().map_err(|e| {
+        PadSwitchError::Forwarding(format!("Failed to plug in virtual controller: {:?}", e))
+    })?;
+
+    log::info!("Replaying {} frames", recording.frames.len());
+    let start = Instant::now();
+    for frame in &recording.frames {
+        let due = Duration::from_millis(frame.t_ms);
+        if let Some(wait) = due.checked_sub(start.elapsed()) {
+            std::thread::sleep(wait);
+        }
+        let mut state = frame.state.clone();
+        mapping.apply(&mut state);
+        let _ = target.update(&to_xgamepad(&state));
+    }
+
+    // Hold the last frame for the recorded tail, then release everything
+    let end = Duration::from_millis(recording.duration_ms);
+    if let Some(wait) = end.checked_sub(start.elapsed()) {
+        std::thread::sleep(wait);
+    }
+    let _ = target.update(&to_xgamepad(&GamepadState::default()));
+    Ok(())
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn replay(_recording: &Recording, _mapping: &InputMapping) -> Result<()> {
+    Err(PadSwitchError::PlatformNotSupported(
+        "Input replay requires ViGEmBus (Windows)".into(),
+    ))
+}
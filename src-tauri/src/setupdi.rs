@@ -12,15 +12,22 @@ pub mod imp {
     use crate::error::{PadSwitchError, Result};
     use std::collections::hash_map::DefaultHasher;
     use std::hash::{Hash, Hasher};
-    use windows::core::PCWSTR;
+    use windows::core::{GUID, PCWSTR};
     use windows::Win32::Devices::DeviceAndDriverInstallation::{
-        SetupDiCallClassInstaller, SetupDiDestroyDeviceInfoList, SetupDiEnumDeviceInfo,
-        SetupDiGetClassDevsW, SetupDiGetDeviceInstanceIdW, SetupDiGetDeviceRegistryPropertyW,
-        SetupDiSetClassInstallParamsW, DIF_PROPERTYCHANGE, DIGCF_ALLCLASSES, DIGCF_PRESENT,
-        DICS_DISABLE, DICS_ENABLE, DICS_FLAG_GLOBAL, DI_FUNCTION, SETUP_DI_REGISTRY_PROPERTY,
-        SP_CLASSINSTALL_HEADER, SP_DEVINFO_DATA, SP_PROPCHANGE_PARAMS, SPDRP_CLASS,
-        SPDRP_DEVICEDESC, SPDRP_FRIENDLYNAME, SPDRP_HARDWAREID, SPDRP_SERVICE,
+        CM_Get_Child, CM_Get_DevNode_PropertyW, CM_Get_DevNode_Status, CM_Get_Device_IDW,
+        CM_Get_Device_Interface_ListW, CM_Get_Device_Interface_List_SizeW, CM_Get_Parent,
+        CM_Get_Sibling, CM_Locate_DevNodeW, SetupDiCallClassInstaller,
+        SetupDiDestroyDeviceInfoList, SetupDiEnumDeviceInfo, SetupDiGetClassDevsW,
+        SetupDiGetDeviceInstanceIdW, SetupDiGetDeviceRegistryPropertyW,
+        SetupDiSetClassInstallParamsW, CM_GET_DEVICE_INTERFACE_LIST_PRESENT,
+        CM_LOCATE_DEVNODE_NORMAL, CM_PROB_DISABLED, CR_SUCCESS, DICS_DISABLE, DICS_ENABLE,
+        DICS_FLAG_GLOBAL, DIF_PROPERTYCHANGE, DIGCF_ALLCLASSES, DIGCF_PRESENT, DI_FUNCTION,
+        SETUP_DI_REGISTRY_PROPERTY, SPDRP_CLASS, SPDRP_DEVICEDESC, SPDRP_FRIENDLYNAME,
+        SPDRP_HARDWAREID, SPDRP_SERVICE, SP_CLASSINSTALL_HEADER, SP_DEVINFO_DATA,
+        SP_PROPCHANGE_PARAMS,
     };
+    use windows::Win32::Devices::HumanInterfaceDevice::GUID_DEVINTERFACE_HID;
+    use windows::Win32::Devices::Properties::{DEVPKEY_Device_ContainerId, DEVPROPTYPE};
 
     /// Info about a game controller discovered via SetupAPI.
     pub struct GameControllerInfo {
@@ -36,14 +43,15 @@ pub mod imp {
     /// Generate a stable device ID from the instance path (deterministic across sessions).
     pub fn stable_device_id(instance_path: &str) -> String {
         let mut hasher = DefaultHasher::new();
-        instance_path.to_uppercase().hash(&mut hasher);
+        crate::platform::normalize_instance_path(instance_path).hash(&mut hasher);
         format!("dev-{:016x}", hasher.finish())
     }
 
     /// Enumerate real game controller devices via SetupAPI.
     /// Finds XInput-compatible controllers by checking driver service names,
-    /// device class names, and device descriptions.
-    pub fn enumerate_game_controllers() -> Result<Vec<GameControllerInfo>> {
+    /// device class names, and device descriptions. With `include_all_hid`, every
+    /// HIDClass device is listed too (manual device mode).
+    pub fn enumerate_game_controllers(include_all_hid: bool) -> Result<Vec<GameControllerInfo>> {
         unsafe {
             let dev_info = SetupDiGetClassDevsW(
                 None,
@@ -76,7 +84,8 @@ pub mod imp {
                     get_device_string_property(dev_info, &dev_data, SPDRP_DEVICEDESC);
 
                 // Filter: is this a game controller?
-                if !is_game_controller(&service, &description, &class) {
+                let any_hid = include_all_hid && class.eq_ignore_ascii_case("HIDClass");
+                if !is_game_controller(&service, &description, &class) && !any_hid {
                     continue;
                 }
 
@@ -137,6 +146,185 @@ pub mod imp {
         change_device_state(instance_path, DICS_ENABLE)
     }
 
+    /// Whether a device node with this instance path is currently present.
+    /// Disabled devices still count as present; unplugged ones don't.
+    pub fn is_present(instance_path: &str) -> bool {
+        locate_devnode(instance_path).is_some()
+    }
+
+    /// Whether a present device is disabled (e.g. by the user in Device Manager).
+    pub fn is_disabled(instance_path: &str) -> bool {
+        let Some(dev_inst) = locate_devnode(instance_path) else {
+            return false;
+        };
+        let mut status = Default::default();
+        let mut problem = Default::default();
+        let result = unsafe { CM_Get_DevNode_Status(&mut status, &mut problem, dev_inst, 0) };
+        result == CR_SUCCESS && problem == CM_PROB_DISABLED
+    }
+
+    /// HID interface paths (`\\?\HID#...`) of a present device and all its child
+    /// devnodes. Composite and wireless controllers expose their HID collections on
+    /// children, which HidHide only hides reliably when those paths are blacklisted.
+    pub fn hid_interface_paths(instance_path: &str) -> Vec<String> {
+        let Some(root) = locate_devnode(instance_path) else {
+            return vec![];
+        };
+        devnode_tree(root)
+            .into_iter()
+            .filter_map(device_id)
+            .flat_map(|id| hid_interfaces_of(&id))
+            .collect()
+    }
+
+    /// Container the computer's built-in devices share; not a per-device grouping.
+    const COMPUTER_CONTAINER: GUID = GUID::from_u128(0x00000000_0000_0000_ffff_ffffffffffff);
+
+    /// Container ID grouping every devnode of one physical device (e.g. the USB
+    /// parent and HID child of a composite pad). `None` if unknown or if the device
+    /// sits in the computer's own container.
+    pub fn container_id(instance_path: &str) -> Option<String> {
+        let dev_inst = locate_devnode(instance_path)?;
+        let mut guid = GUID::zeroed();
+        let mut prop_type = DEVPROPTYPE::default();
+        let mut size = std::mem::size_of::<GUID>() as u32;
+        let status = unsafe {
+            CM_Get_DevNode_PropertyW(
+                dev_inst,
+                &DEVPKEY_Device_ContainerId,
+                &mut prop_type,
+                Some(&mut guid as *mut GUID as *mut u8),
+                &mut size,
+                0,
+            )
+        };
+        if status != CR_SUCCESS || guid == GUID::zeroed() || guid == COMPUTER_CONTAINER {
+            return None;
+        }
+        Some(format!("{:?}", guid))
+    }
+
+    /// Instance IDs of a present device's child devnodes (all depths), without the
+    /// device itself.
+    pub fn child_instance_ids(instance_path: &str) -> Vec<String> {
+        let Some(root) = locate_devnode(instance_path) else {
+            return vec![];
+        };
+        devnode_tree(root)
+            .into_iter()
+            .skip(1)
+            .filter_map(device_id)
+            .collect()
+    }
+
+    /// Instance IDs of a present device's ancestors, nearest first, stopping below
+    /// the bus controllers (root hubs, PCI, ACPI), which are never worth hiding.
+    pub fn parent_instance_ids(instance_path: &str) -> Vec<String> {
+        let Some(mut current) = locate_devnode(instance_path) else {
+            return vec![];
+        };
+        let mut parents = Vec::new();
+        loop {
+            let mut parent: u32 = 0;
+            if unsafe { CM_Get_Parent(&mut parent, current, 0) } != CR_SUCCESS {
+                break;
+            }
+            let Some(id) = device_id(parent) else { break };
+            let upper = id.to_uppercase();
+            let is_bus = ["PCI\\", "ACPI\\", "HTREE\\", "ROOT\\", "USB\\ROOT_HUB"]
+                .iter()
+                .any(|prefix| upper.starts_with(prefix));
+            if is_bus {
+                break;
+            }
+            parents.push(id);
+            current = parent;
+        }
+        parents
+    }
+
+    /// Device instance handle of a present device node.
+    fn locate_devnode(instance_path: &str) -> Option<u32> {
+        let wide: Vec<u16> = instance_path
+            .encode_utf16()
+            .chain(std::iter::once(0))
+            .collect();
+        let mut dev_inst: u32 = 0;
+        let status = unsafe {
+            CM_Locate_DevNodeW(
+                &mut dev_inst,
+                PCWSTR(wide.as_ptr()),
+                CM_LOCATE_DEVNODE_NORMAL,
+            )
+        };
+        (status == CR_SUCCESS).then_some(dev_inst)
+    }
+
+    /// A devnode followed by all of its descendants, breadth-first.
+    fn devnode_tree(root: u32) -> Vec<u32> {
+        let mut nodes = vec![root];
+        let mut next = 0;
+        while next < nodes.len() {
+            let mut child: u32 = 0;
+            if unsafe { CM_Get_Child(&mut child, nodes[next], 0) } == CR_SUCCESS {
+                nodes.push(child);
+                let mut current = child;
+                let mut sibling: u32 = 0;
+                while unsafe { CM_Get_Sibling(&mut sibling, current, 0) } == CR_SUCCESS {
+                    nodes.push(sibling);
+                    current = sibling;
+                }
+            }
+            next += 1;
+        }
+        nodes
+    }
+
+    /// Instance ID of a devnode.
+    fn device_id(dev_inst: u32) -> Option<String> {
+        let mut buffer = [0u16; 512];
+        if unsafe { CM_Get_Device_IDW(dev_inst, &mut buffer, 0) } != CR_SUCCESS {
+            return None;
+        }
+        let end = buffer.iter().position(|&c| c == 0).unwrap_or(buffer.len());
+        Some(String::from_utf16_lossy(&buffer[..end]))
+    }
+
+    /// Present HID interfaces registered directly on one device instance.
+    fn hid_interfaces_of(device_id: &str) -> Vec<String> {
+        let wide: Vec<u16> = device_id.encode_utf16().chain(std::iter::once(0)).collect();
+        let mut len: u32 = 0;
+        let mut buffer = unsafe {
+            let sized = CM_Get_Device_Interface_List_SizeW(
+                &mut len,
+                &GUID_DEVINTERFACE_HID,
+                PCWSTR(wide.as_ptr()),
+                CM_GET_DEVICE_INTERFACE_LIST_PRESENT,
+            );
+            if sized != CR_SUCCESS || len <= 1 {
+                return vec![];
+            }
+            vec![0u16; len as usize]
+        };
+        let listed = unsafe {
+            CM_Get_Device_Interface_ListW(
+                &GUID_DEVINTERFACE_HID,
+                PCWSTR(wide.as_ptr()),
+                &mut buffer,
+                CM_GET_DEVICE_INTERFACE_LIST_PRESENT,
+            )
+        };
+        if listed != CR_SUCCESS {
+            return vec![];
+        }
+        // Multi-string: entries separated by NUL, terminated by an empty one
+        buffer
+            .split(|&c| c == 0)
+            .take_while(|s| !s.is_empty())
+            .map(String::from_utf16_lossy)
+            .collect()
+    }
+
     // -----------------------------------------------------------------------
     // Private helpers
     // -----------------------------------------------------------------------
@@ -346,7 +534,7 @@ pub mod imp {
         instance_path: &str,
         state_change: u32,
     ) -> Result<()> {
-        let target_upper = instance_path.to_uppercase();
+        let target = crate::platform::normalize_instance_path(instance_path);
         let mut index: u32 = 0;
 
         loop {
@@ -378,7 +566,7 @@ pub mod imp {
                 &id_buf[..required_size.saturating_sub(1) as usize],
             );
 
-            if device_id.to_uppercase() != target_upper {
+            if crate::platform::normalize_instance_path(&device_id) != target {
                 continue;
             }
 
@@ -401,8 +589,8 @@ pub mod imp {
             )
             .map_err(|e| {
                 if e.code().0 as u32 == 0x80070005 {
-                    PadSwitchError::Platform(
-                        "Access denied. Run PadSwitch as Administrator to change device state."
+                    PadSwitchError::ElevationRequired(
+                        "Access denied changing device state. Relaunch PadSwitch as Administrator."
                             .into(),
                     )
                 } else {
@@ -416,8 +604,8 @@ pub mod imp {
             SetupDiCallClassInstaller(DIF_PROPERTYCHANGE, dev_info, Some(&dev_info_data)).map_err(
                 |e| {
                     if e.code().0 as u32 == 0x80070005 {
-                        PadSwitchError::Platform(
-                            "Access denied. Run PadSwitch as Administrator to change device state."
+                        PadSwitchError::ElevationRequired(
+                            "Access denied changing device state. Relaunch PadSwitch as Administrator."
                                 .into(),
                         )
                     } else {
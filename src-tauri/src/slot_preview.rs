@@ -0,0 +1,66 @@
+use crate::config::RoutingMode;
+use crate::device::GamepadState;
+use crate::error::{PadSwitchError, Result};
+use crate::input_loop::session_modes;
+use crate::state::AppState;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+/// Interval between `slots-preview` events (~30Hz).
+const INTERVAL: Duration = Duration::from_millis(33);
+
+/// Whether a session with Force-mode assignments is reading the XInput slots right now.
+fn force_session_active(state: &AppState) -> bool {
+    let inner = state.lock_inner();
+    inner.is_forwarding()
+        && session_modes(&inner.resolve_assignments(), &inner.active_routing_mode())
+            .contains(&RoutingMode::Force)
+}
+
+/// Stream the state of XInput slots 0-3 on a background thread.
+///
+/// Emits `slots-preview { slots }` with one entry per slot (`null` when nothing is
+/// connected). Starting again or calling `stop` bumps the preview generation,
+/// which ends the current stream. Refused while a Force session is running, and
+/// ends with `slots-preview-stopped` if one starts, so the preview never competes
+/// with the input loop for the XInput handle.
+pub fn start(app: &AppHandle) -> Result<()> {
+    let state = app.state::<AppState>();
+    if force_session_active(&state) {
+        return Err(PadSwitchError::InvalidState(
+            "Slot preview is unavailable while Force mode is forwarding".into(),
+        ));
+    }
+    let generation = state.preview_generation().fetch_add(1, Ordering::SeqCst) + 1;
+    let app = app.clone();
+
+    std::thread::Builder::new()
+        .name("padswitch-slot-preview".into())
+        .spawn(move || {
+            let state = app.state::<AppState>();
+            let manager = state.manager().clone();
+            while state.preview_generation().load(Ordering::SeqCst) == generation {
+                if force_session_active(&state) {
+                    log::info!("Slot preview stopped: Force mode started");
+                    let _ = app.emit(
+                        "slots-preview-stopped",
+                        serde_json::json!({ "reason": "Force mode started" }),
+                    );
+                    return;
+                }
+                let slots: Vec<Option<GamepadState>> = (0..4u32)
+                    .map(|slot| manager.read_gamepad_state(&slot.to_string()).ok())
+                    .collect();
+                let _ = app.emit("slots-preview", serde_json::json!({ "slots": slots }));
+                std::thread::sleep(INTERVAL);
+            }
+        })
+        .map_err(|e| PadSwitchError::Platform(format!("Failed to spawn slot preview: {}", e)))?;
+    Ok(())
+}
+
+/// End the running slot preview, if any.
+pub fn stop(state: &AppState) {
+    state.preview_generation().fetch_add(1, Ordering::SeqCst);
+}
@@ -1,20 +1,115 @@
-use crate::config::{AppConfig, Profile, RoutingMode};
-use crate::device::{DriverStatus, PhysicalDevice, SlotAssignment};
-use crate::input_loop::{InputLoop, ResolvedAssignment};
+use crate::config::{AppConfig, Profile, RoutingMode, Settings};
+use crate::control_server::ControlServer;
+use crate::device::{DeviceType, DriverStatus, PhysicalDevice, SlotAssignment};
+use crate::input_loop::{
+    session_modes, AssignmentResolution, ForwardingOptions, InputLoop, LatencyProbe, ResetChord,
+    ResolvedAssignment, StartCancel, UnresolvedAssignment, UnresolvedReason,
+};
 use crate::platform::PlatformServices;
 use crate::process_watcher::ProcessWatcher;
-use std::sync::{Arc, Mutex, MutexGuard};
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError};
+use tauri::{AppHandle, Emitter, Manager};
+
+/// The routing mode forwarding would use right now, and where it comes from.
+#[derive(Debug, Clone, Serialize)]
+pub struct EffectiveRoutingMode {
+    pub mode: RoutingMode,
+    /// Human-readable source, e.g. "from profile Co-op" or "default (no active profile)".
+    pub reason: String,
+    /// The profile the mode was taken from, if any.
+    pub profile_id: Option<String>,
+    /// Whether starting in this mode needs admin on this platform.
+    pub requires_elevation: bool,
+}
+
+/// Whether pending config edits need forwarding (or the app) restarted to apply.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RestartCheck {
+    /// Forwarding is running and the running session would not pick the change up.
+    pub required: bool,
+    /// The changes that only apply on the next app launch; restarting forwarding
+    /// doesn't help.
+    pub app_restart: bool,
+    /// One human-readable line per change that isn't hot-applied.
+    pub reasons: Vec<String>,
+}
+
+/// Whether a saved profile could start right now, and what stops it if not.
+#[derive(Debug, Clone, Serialize)]
+pub struct ProfileRunStatus {
+    pub can_run: bool,
+    /// One human-readable line per problem (preflight failures, no connected devices).
+    pub blockers: Vec<String>,
+}
+
+/// A saved profile with its run status, for the profile list.
+#[derive(Debug, Clone, Serialize)]
+pub struct ProfileWithStatus {
+    pub profile: Profile,
+    pub status: ProfileRunStatus,
+}
+
+/// A newly saved profile, with warnings about assignments that won't forward.
+#[derive(Debug, Clone, Serialize)]
+pub struct SavedProfile {
+    pub profile: Profile,
+    pub warnings: Vec<String>,
+}
+
+/// Where forwarding is in its lifecycle. `Starting` and `Stopping` mark a transition
+/// that may still be hiding or unhiding devices; `AppState::lock_inner` waits them out
+/// so overlapping start/stop/restart requests run one after another.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ForwardingPhase {
+    #[default]
+    Idle,
+    Starting,
+    Active,
+    Stopping,
+}
+
+impl ForwardingPhase {
+    pub fn is_transitioning(self) -> bool {
+        matches!(self, Self::Starting | Self::Stopping)
+    }
+}
+
+/// A physical device Force mode would hide from games.
+#[derive(Debug, Clone, Serialize)]
+pub struct HiddenDevicePreview {
+    pub device_id: String,
+    pub name: String,
+    pub instance_path: String,
+}
 
 pub struct Inner {
     pub devices: Vec<PhysicalDevice>,
     pub assignments: Vec<SlotAssignment>,
     pub driver_status: DriverStatus,
-    pub forwarding_active: bool,
+    pub forwarding_phase: ForwardingPhase,
     pub config: AppConfig,
     pub input_loop: InputLoop,
+    /// What the running session was started with; `None` when not forwarding.
+    pub session: Option<ForwardingSession>,
+}
+
+/// The inputs a forwarding session was started from, for `Inner::restart_check`.
+#[derive(Debug, Clone)]
+pub struct ForwardingSession {
+    pub profile_id: Option<String>,
+    pub mode: RoutingMode,
+    pub options: ForwardingOptions,
+    pub assignments: Vec<SlotAssignment>,
 }
 
 impl Inner {
+    /// Whether a forwarding session was started and hasn't been stopped.
+    pub fn forwarding_active(&self) -> bool {
+        self.forwarding_phase == ForwardingPhase::Active
+    }
+
     /// Get the currently active profile, if any.
     pub fn active_profile(&self) -> Option<&Profile> {
         let active_id = self.config.settings.active_profile_id.as_deref()?;
@@ -28,62 +123,481 @@ impl Inner {
             .unwrap_or_default()
     }
 
+    /// Explain `active_routing_mode()`: the mode plus why it was chosen.
+    pub fn effective_routing_mode(&self) -> EffectiveRoutingMode {
+        let mode = self.active_routing_mode();
+        let options = self.active_forwarding_options();
+        // Per-assignment overrides can add a mode that needs elevation
+        let requires_elevation = session_modes(&self.resolve_assignments(), &mode)
+            .iter()
+            .chain(std::iter::once(&mode))
+            .any(|m| crate::platform::requires_elevation(m, &options));
+        let (reason, profile_id) = match (
+            self.config.settings.active_profile_id.as_deref(),
+            self.active_profile(),
+        ) {
+            (_, Some(profile)) => (
+                format!("from profile {}", profile.name),
+                Some(profile.id.clone()),
+            ),
+            (Some(missing), None) => (
+                format!("default (active profile '{}' not found)", missing),
+                None,
+            ),
+            (None, None) => ("default (no active profile)".to_string(), None),
+        };
+        EffectiveRoutingMode {
+            mode,
+            reason,
+            profile_id,
+            requires_elevation,
+        }
+    }
+
+    /// Build forwarding options from the active profile (defaults when none is active).
+    pub fn active_forwarding_options(&self) -> ForwardingOptions {
+        forwarding_options(&self.config)
+    }
+
+    /// Which of the given edits would not reach a running session. `settings`
+    /// replaces the current settings and `profile` replaces the saved profile with
+    /// the same id; either may be omitted. This is the one place that knows which
+    /// fields are hot-applied: anything read into `ForwardingOptions`, the routing
+    /// mode or the session's assignments is fixed when forwarding starts (and is
+    /// compared against what the running session started with), and the platform
+    /// options are read once at launch. Everything else (auto-switch, the control
+    /// server, reset behavior, ...) applies immediately.
+    pub fn restart_check(
+        &self,
+        settings: Option<&Settings>,
+        profile: Option<&Profile>,
+    ) -> RestartCheck {
+        let old = &self.config.settings;
+        let new = settings.unwrap_or(old);
+        let mut check = RestartCheck::default();
+
+        let launch_only = [
+            ("XInput version", new.xinput_version != old.xinput_version),
+            (
+                "Guide button forwarding",
+                new.forward_guide_button != old.forward_guide_button,
+            ),
+            (
+                "HID interface path hiding",
+                new.hide_interface_paths != old.hide_interface_paths,
+            ),
+        ];
+        for (name, _) in launch_only.iter().filter(|(_, changed)| *changed) {
+            check.app_restart = true;
+            check
+                .reasons
+                .push(format!("{} takes effect after restarting PadSwitch", name));
+        }
+
+        let Some(session) = self.session.as_ref().filter(|_| self.forwarding_active()) else {
+            return check;
+        };
+        let mut restart = |reason: &str| {
+            check.required = true;
+            check.reasons.push(reason.to_string());
+        };
+
+        // The config a start would read with the pending edits applied
+        let mut config = self.config.clone();
+        config.settings = new.clone();
+        if let Some(profile) = profile {
+            match config.profiles.iter_mut().find(|p| p.id == profile.id) {
+                Some(existing) => *existing = profile.clone(),
+                None => config.profiles.push(profile.clone()),
+            }
+        }
+        let next_profile = new
+            .active_profile_id
+            .as_deref()
+            .and_then(|id| config.profiles.iter().find(|p| p.id == id));
+
+        if new.active_profile_id != session.profile_id {
+            restart("The active profile changed");
+        }
+        if next_profile
+            .map(|p| p.routing_mode.clone())
+            .unwrap_or_default()
+            != session.mode
+        {
+            restart("The routing mode changed");
+        }
+        // The session's assignments come from the profile when it's switched to or
+        // saved, and from the live edits otherwise
+        let next_assignments = next_profile
+            .filter(|p| {
+                profile.is_some_and(|edited| edited.id == p.id)
+                    || session.profile_id.as_deref() != Some(p.id.as_str())
+            })
+            .map_or(&self.assignments, |p| &p.assignments);
+        if *next_assignments != session.assignments {
+            restart("Assignments or their mappings changed");
+        }
+
+        let next = forwarding_options(&config);
+        let started = &session.options;
+        let option_changes = [
+            (
+                "Hard hide or the virtual controller model",
+                (next.hard_hide, next.virtual_subtype)
+                    != (started.hard_hide, started.virtual_subtype),
+            ),
+            (
+                "The virtual layout",
+                next.virtual_layout != started.virtual_layout,
+            ),
+            (
+                "Overlay whitelist",
+                next.whitelist_apps != started.whitelist_apps,
+            ),
+            (
+                "Persistently hidden devices",
+                next.persistent_hidden != started.persistent_hidden,
+            ),
+            (
+                "Minimal mode watchdog",
+                next.minimal_watchdog_secs != started.minimal_watchdog_secs,
+            ),
+            (
+                "Minimal mode cleanup",
+                next.minimal_cleanup_only_own != started.minimal_cleanup_only_own,
+            ),
+            // Covers the game rules too, which feed the focus list when it's empty
+            (
+                "Focus-only forwarding",
+                next.focus_exe_names != started.focus_exe_names,
+            ),
+            (
+                "Rumble forwarding",
+                next.forward_rumble != started.forward_rumble,
+            ),
+            (
+                "Neutralize on stop",
+                next.stop_settle != started.stop_settle,
+            ),
+            ("Reset chord", next.reset_chord != started.reset_chord),
+            // Only the Linux loop reads them at start; elsewhere the platform applies them live
+            (
+                "Axis overrides",
+                cfg!(target_os = "linux") && next.axis_overrides != started.axis_overrides,
+            ),
+        ];
+        for (name, _) in option_changes.iter().filter(|(_, changed)| *changed) {
+            restart(&format!("{} changed", name));
+        }
+        check
+    }
+
+    /// Re-check each cached device's presence and update `connected`.
+    /// Returns whether any device changed.
+    pub fn refresh_connection_status(&mut self, manager: &dyn PlatformServices) -> bool {
+        let mut changed = false;
+        for device in &mut self.devices {
+            let present = match manager.is_device_present(&device.instance_path) {
+                Ok(present) => present,
+                Err(e) => {
+                    log::warn!("Presence check failed for {}: {}", device.instance_path, e);
+                    continue;
+                }
+            };
+            if device.connected != present {
+                log::info!(
+                    "{} is now {}",
+                    device.instance_path,
+                    if present { "connected" } else { "disconnected" }
+                );
+                device.connected = present;
+                changed = true;
+            }
+        }
+        changed
+    }
+
     /// Resolve enabled assignments to ResolvedAssignments by looking up real device data.
-    /// Returns only assignments whose device_id matches a known device.
+    /// Returns only the assignments the loop would forward (see `explain_assignments`).
     pub fn resolve_assignments(&self) -> Vec<ResolvedAssignment> {
-        self.assignments
+        self.resolve(&self.assignments, &self.active_routing_mode())
+            .0
+    }
+
+    /// Resolve an assignment list (the active one or another profile's) under `mode`:
+    /// the assignments the loop would forward, and each dropped one with the reason.
+    fn resolve(
+        &self,
+        assignments: &[SlotAssignment],
+        mode: &RoutingMode,
+    ) -> (Vec<ResolvedAssignment>, Vec<UnresolvedAssignment>) {
+        let reserved = self.passthrough_reservations(assignments);
+        let mut resolved = Vec::new();
+        let mut unresolved = Vec::new();
+
+        for a in assignments {
+            let needs_xinput_slot = cfg!(target_os = "windows")
+                && a.routing_mode.as_ref().unwrap_or(mode) == &RoutingMode::Force;
+            let outcome = match self.devices.iter().find(|d| d.id == a.device_id) {
+                _ if !a.enabled => Err(UnresolvedReason::Disabled),
+                None => Err(UnresolvedReason::DeviceNotFound),
+                Some(d) if !d.connected => Err(UnresolvedReason::Disconnected),
+                Some(d) if needs_xinput_slot && d.xinput_slot.is_none() => {
+                    Err(if d.xinput_overflow {
+                        UnresolvedReason::XInputSlotsFull
+                    } else {
+                        UnresolvedReason::NoXInputSlot
+                    })
+                }
+                Some(d) => resolve_one(a, d, &reserved),
+            };
+            match outcome {
+                Ok(ra) => resolved.push(ra),
+                Err(reason) => unresolved.push(UnresolvedAssignment {
+                    device_id: a.device_id.clone(),
+                    target_slot: a.slot,
+                    reason,
+                }),
+            }
+        }
+        (resolved, unresolved)
+    }
+
+    /// XInput indices that passthrough assignments keep, with the device keeping each.
+    fn passthrough_reservations(&self, assignments: &[SlotAssignment]) -> Vec<(u8, String)> {
+        assignments
+            .iter()
+            .filter(|a| a.enabled && a.passthrough)
+            .filter_map(|a| {
+                let device = self
+                    .devices
+                    .iter()
+                    .find(|d| d.id == a.device_id && d.connected)?;
+                Some((device.xinput_slot? as u8, device.id.clone()))
+            })
+            .collect()
+    }
+
+    /// Devices that Force mode would hide if `profile_id` were started now: the
+    /// profile's assignments resolved against the current device list, one entry per
+    /// device. Returns `None` if the profile doesn't exist.
+    pub fn preview_hidden_devices(&self, profile_id: &str) -> Option<Vec<HiddenDevicePreview>> {
+        let profile = self.config.profiles.iter().find(|p| p.id == profile_id)?;
+        let mut preview: Vec<HiddenDevicePreview> = Vec::new();
+        for resolved in self.resolve(&profile.assignments, &profile.routing_mode).0 {
+            if preview.iter().any(|p| p.device_id == resolved.device_id) {
+                continue;
+            }
+            let Some(device) = self.devices.iter().find(|d| d.id == resolved.device_id) else {
+                continue;
+            };
+            preview.push(HiddenDevicePreview {
+                device_id: device.id.clone(),
+                name: device.name.clone(),
+                instance_path: resolved.instance_path,
+            });
+        }
+        Some(preview)
+    }
+
+    /// Enabled assignments that forwarding would skip without an error: Force mode
+    /// on Windows reads XInput slots, which DirectInput devices never get. Checked
+    /// against the cached device list.
+    pub fn assignment_warnings(
+        &self,
+        assignments: &[SlotAssignment],
+        mode: &RoutingMode,
+    ) -> Vec<String> {
+        if !cfg!(target_os = "windows") {
+            return vec![];
+        }
+        assignments
             .iter()
             .filter(|a| a.enabled)
+            .filter(|a| a.routing_mode.as_ref().unwrap_or(mode) == &RoutingMode::Force)
             .filter_map(|a| {
                 let device = self.devices.iter().find(|d| d.id == a.device_id)?;
-                Some(ResolvedAssignment {
-                    instance_path: device.instance_path.clone(),
-                    xinput_slot: device.xinput_slot,
-                    target_slot: a.slot,
+                (device.device_type == DeviceType::DirectInput).then(|| {
+                    format!(
+                        "{} is a DirectInput device: Force mode only forwards XInput \
+                         controllers, so slot {} won't receive its input",
+                        device.name, a.slot
+                    )
                 })
             })
             .collect()
     }
 
+    /// Whether `profile` could start now: its assignments resolved against the cached
+    /// device list, then the preflight for every mode they'd run. Doesn't touch the
+    /// device list or forwarding.
+    pub fn profile_status(
+        &self,
+        profile: &Profile,
+        manager: &dyn PlatformServices,
+    ) -> ProfileRunStatus {
+        let mut blockers = Vec::new();
+        let (resolved, _) = self.resolve(&profile.assignments, &profile.routing_mode);
+        let modes = if resolved.is_empty() {
+            blockers.push("No assigned device is connected".to_string());
+            vec![profile.routing_mode.clone()]
+        } else {
+            session_modes(&resolved, &profile.routing_mode)
+        };
+        let options = ForwardingOptions::from_profile(profile);
+        for mode in &modes {
+            if let Err(e) = self.preflight_check(mode, &options, manager) {
+                blockers.push(e.to_string());
+            }
+        }
+        ProfileRunStatus {
+            can_run: blockers.is_empty(),
+            blockers,
+        }
+    }
+
+    /// Explain resolution: the assignments the loop would actually forward, plus
+    /// each dropped assignment and why (for the "why isn't this forwarding" view).
+    pub fn explain_assignments(&self) -> AssignmentResolution {
+        let (resolved, unresolved) = self.resolve(&self.assignments, &self.active_routing_mode());
+
+        let mut shared_devices: Vec<String> = Vec::new();
+        for (i, ra) in resolved.iter().enumerate() {
+            let repeated = resolved[..i].iter().any(|o| o.device_id == ra.device_id);
+            if repeated && !shared_devices.contains(&ra.device_id) {
+                shared_devices.push(ra.device_id.clone());
+            }
+        }
+
+        AssignmentResolution {
+            resolved,
+            unresolved,
+            shared_devices,
+        }
+    }
+
     /// Start forwarding with current assignments and active routing mode.
     /// Runs preflight checks (elevation for Minimal, drivers for Force),
     /// resolves assignments, and starts the input loop.
     pub fn start_forwarding(
         &mut self,
         manager: Arc<dyn PlatformServices>,
+        app: &AppHandle,
     ) -> crate::error::Result<()> {
-        if self.forwarding_active {
-            return Ok(());
+        if self.forwarding_active() && !self.input_loop.is_running() {
+            // The loop bailed on its own (already reported) — reap it and start fresh
+            self.stop_forwarding();
         }
 
-        let mode = self.active_routing_mode();
-        self.preflight_check(&mode, &*manager)?;
+        let started = self.transition_start(|inner| inner.start_session(manager, app))?;
+        if started {
+            if let Some(state) = app.try_state::<AppState>() {
+                state.clear_background_error();
+            }
+        }
+        Ok(())
+    }
 
-        let resolved = self.resolve_assignments();
+    /// The phase transitions of a start around `start`, which sets the session up:
+    /// `Starting` while it runs, then `Active`, or `Idle` if it failed. Returns
+    /// `Ok(false)` without calling it while a session is active.
+    fn transition_start(
+        &mut self,
+        start: impl FnOnce(&mut Self) -> crate::error::Result<()>,
+    ) -> crate::error::Result<bool> {
+        if self.forwarding_active() {
+            return Ok(false);
+        }
+        self.forwarding_phase = ForwardingPhase::Starting;
+        let result = start(self);
+        self.forwarding_phase = match result {
+            Ok(()) => ForwardingPhase::Active,
+            Err(_) => ForwardingPhase::Idle,
+        };
+        result.map(|()| true)
+    }
+
+    /// The body of `start_forwarding`, run while the phase is `Starting`.
+    fn start_session(
+        &mut self,
+        manager: Arc<dyn PlatformServices>,
+        app: &AppHandle,
+    ) -> crate::error::Result<()> {
+        // Don't resolve assignments against devices unplugged since the last enumeration
+        let previous = self.devices.clone();
+        if self.refresh_connection_status(&*manager) {
+            crate::device_refresh::emit_updated(app, &previous, &self.devices);
+        }
+
+        let session = self.session_snapshot();
+        let (mode, options) = (session.mode.clone(), session.options.clone());
+        let explained = self.explain_assignments();
+        if let Some(conflict) = explained
+            .unresolved
+            .iter()
+            .find(|u| matches!(u.reason, UnresolvedReason::SlotReserved))
+        {
+            let name = self
+                .devices
+                .iter()
+                .find(|d| d.id == conflict.device_id)
+                .map_or(conflict.device_id.as_str(), |d| d.name.as_str());
+            return Err(crate::error::PadSwitchError::Forwarding(format!(
+                "{} is assigned to slot {}, which a passthrough device keeps for itself",
+                name, conflict.target_slot
+            )));
+        }
+        let resolved = explained.resolved;
         if resolved.is_empty() {
             return Err(crate::error::PadSwitchError::Forwarding(
                 "No valid device assignments to forward".into(),
             ));
         }
 
+        // Assignments may override the profile's mode; check every mode in use
+        let modes = session_modes(&resolved, &mode);
+        for session_mode in &modes {
+            self.preflight_check(session_mode, &options, &*manager)?;
+        }
+        if modes.contains(&RoutingMode::Force) {
+            warn_steam_input(app);
+        }
+
+        for device_id in &explained.shared_devices {
+            log::warn!("Device {} feeds more than one virtual slot", device_id);
+        }
+
         log::info!(
-            "Starting forwarding ({:?} mode) with {} resolved assignments",
-            mode,
+            "Starting forwarding ({:?}) with {} resolved assignments",
+            modes,
             resolved.len()
         );
 
-        self.input_loop.start(manager, resolved, mode)?;
-        self.forwarding_active = true;
+        self.input_loop
+            .start(app.clone(), manager, resolved, mode, options)?;
+        self.session = Some(session);
         Ok(())
     }
 
+    /// The profile, mode, options and assignments a start would use right now.
+    pub fn session_snapshot(&self) -> ForwardingSession {
+        ForwardingSession {
+            profile_id: self.config.settings.active_profile_id.clone(),
+            mode: self.active_routing_mode(),
+            options: self.active_forwarding_options(),
+            assignments: self.assignments.clone(),
+        }
+    }
+
     /// Stop forwarding and clean up.
     pub fn stop_forwarding(&mut self) {
-        if !self.forwarding_active {
+        if !self.forwarding_active() {
             return;
         }
+        self.forwarding_phase = ForwardingPhase::Stopping;
         self.input_loop.stop();
-        self.forwarding_active = false;
+        self.session = None;
+        self.forwarding_phase = ForwardingPhase::Idle;
     }
 
     /// Restart forwarding (stop + start). Used when switching profiles while active.
@@ -91,15 +605,22 @@ impl Inner {
     pub fn restart_forwarding(
         &mut self,
         manager: Arc<dyn PlatformServices>,
+        app: &AppHandle,
     ) -> crate::error::Result<()> {
         self.stop_forwarding();
-        self.start_forwarding(manager)
+        self.start_forwarding(manager, app)
+    }
+
+    /// Whether forwarding is active and the loop hasn't bailed out on its own.
+    pub fn is_forwarding(&self) -> bool {
+        self.forwarding_active() && self.input_loop.is_running()
     }
 
     /// Run preflight checks for a given routing mode.
-    fn preflight_check(
+    pub fn preflight_check(
         &self,
         mode: &RoutingMode,
+        options: &ForwardingOptions,
         manager: &dyn PlatformServices,
     ) -> crate::error::Result<()> {
         match mode {
@@ -111,8 +632,8 @@ impl Inner {
 
                 #[cfg(not(target_os = "linux"))]
                 if !crate::platform::is_elevated() {
-                    return Err(crate::error::PadSwitchError::Platform(
-                        "Minimal mode requires administrator privileges. Restart PadSwitch as Administrator.".into(),
+                    return Err(crate::error::PadSwitchError::ElevationRequired(
+                        "Minimal mode reorders devices via SetupDi. Relaunch PadSwitch as Administrator.".into(),
                     ));
                 }
             }
@@ -128,38 +649,220 @@ impl Inner {
                         "ViGEmBus is required for Force mode. Install it from github.com/nefarius/ViGEmBus/releases".into(),
                     ));
                 }
+                // The Windows loop reads physical state through the platform's XInput
+                // handle — fail early with guidance if it never loaded.
+                #[cfg(target_os = "windows")]
+                if let Err(e @ crate::error::PadSwitchError::XInputUnavailable(_)) =
+                    manager.read_gamepad_state("0")
+                {
+                    return Err(e);
+                }
+                if options.hard_hide && !crate::platform::is_elevated() {
+                    return Err(crate::error::PadSwitchError::ElevationRequired(
+                        "Hard hide disables devices via SetupDi. Relaunch PadSwitch as Administrator or turn off hard hide.".into(),
+                    ));
+                }
             }
         }
         Ok(())
     }
 }
 
+/// `Inner::active_forwarding_options` for an arbitrary config (e.g. one with
+/// pending edits applied).
+fn forwarding_options(config: &AppConfig) -> ForwardingOptions {
+    let profile = config
+        .settings
+        .active_profile_id
+        .as_deref()
+        .and_then(|id| config.profiles.iter().find(|p| p.id == id));
+    let mut options = profile
+        .map(ForwardingOptions::from_profile)
+        .unwrap_or_default();
+    let settings = &config.settings;
+    if settings.overlay_whitelist_enabled {
+        options.whitelist_apps = settings.overlay_whitelist_apps.clone();
+    }
+    options.persistent_hidden = config.persistent_hidden.clone();
+    options.axis_overrides = config.axis_overrides.clone();
+    if let Some(layout) = profile.and_then(|p| config.virtual_layout_of(p)) {
+        options.virtual_layout = layout.targets.clone();
+    }
+    options.minimal_watchdog_secs = settings.minimal_watchdog_secs;
+    options.minimal_cleanup_only_own = settings.minimal_cleanup_only_own;
+    options.forward_rumble = settings.forward_rumble;
+    options.stop_settle = settings
+        .neutralize_on_stop
+        .then(|| std::time::Duration::from_millis(settings.stop_settle_ms.into()));
+    options.reset_chord =
+        (settings.reset_chord_enabled && settings.reset_chord_buttons != 0).then(|| ResetChord {
+            buttons: settings.reset_chord_buttons,
+            hold: std::time::Duration::from_millis(settings.reset_chord_hold_ms.into()),
+        });
+    if settings.forward_only_when_focused {
+        options.focus_exe_names = if settings.focus_exe_names.is_empty() {
+            config
+                .game_rules
+                .iter()
+                .filter(|r| r.enabled)
+                .flat_map(|r| r.exe_names.iter().cloned())
+                .collect()
+        } else {
+            settings.focus_exe_names.clone()
+        };
+    }
+    options
+}
+
+/// Non-fatal Force-mode preflight: emit `steam-input-warning` if Steam is running
+/// with Steam Input likely on. Runs off-thread so listing processes doesn't hold
+/// up the start.
+fn warn_steam_input(app: &AppHandle) {
+    let app = app.clone();
+    let spawned = std::thread::Builder::new()
+        .name("padswitch-steam-check".into())
+        .spawn(move || {
+            if let Some(warning) = crate::conflicts::detect_steam_input() {
+                log::warn!("{} {}", warning.message, warning.remediation);
+                let _ = app.emit("steam-input-warning", &warning);
+            }
+        });
+    if let Err(e) = spawned {
+        log::warn!("Failed to spawn Steam Input check: {}", e);
+    }
+}
+
+/// Pair an assignment with its device. Passthrough assignments target the slot the
+/// device already occupies, falling back to the configured slot if it has none.
+/// A slot in `reserved` (see `passthrough_reservations`) only takes the device
+/// keeping it.
+fn resolve_one(
+    assignment: &SlotAssignment,
+    device: &PhysicalDevice,
+    reserved: &[(u8, String)],
+) -> std::result::Result<ResolvedAssignment, UnresolvedReason> {
+    let target_slot = match (assignment.passthrough, device.xinput_slot) {
+        (true, Some(slot)) => slot as u8,
+        _ => assignment.slot,
+    };
+    if reserved
+        .iter()
+        .any(|(slot, id)| *slot == target_slot && *id != device.id)
+    {
+        return Err(UnresolvedReason::SlotReserved);
+    }
+    Ok(ResolvedAssignment {
+        device_id: device.id.clone(),
+        instance_path: device.instance_path.clone(),
+        xinput_slot: device.xinput_slot,
+        target_slot,
+        mapping: assignment.mapping.clone(),
+        passthrough: assignment.passthrough,
+        routing_mode: assignment.routing_mode.clone(),
+    })
+}
+
+/// The most recent failure on a background thread, kept for UIs that weren't
+/// listening when its event was emitted.
+#[derive(Debug, Clone, Serialize)]
+pub struct BackgroundError {
+    /// Which background task failed ("forwarding", "watcher", "auto-forward").
+    pub source: String,
+    pub message: String,
+    pub at: chrono::DateTime<chrono::Utc>,
+}
+
 pub struct AppState {
     inner: Mutex<Inner>,
+    /// Signalled when a transition finished outside the lock settles `forwarding_phase`.
+    phase_settled: Condvar,
     manager: Arc<dyn PlatformServices>,
     /// Process watcher has its own lock to avoid contention with inner.
     watcher: Mutex<ProcessWatcher>,
+    /// Local control endpoint, independent of inner for the same reason.
+    control_server: Mutex<ControlServer>,
+    /// Bumped on every device refresh request; see `device_refresh::schedule`.
+    refresh_generation: AtomicU64,
+    /// Bumped to start or cancel an identify-all run; see `identify::start_all`.
+    identify_generation: AtomicU64,
+    /// Bumped to start or stop the all-slots preview; see `slot_preview::start`.
+    preview_generation: AtomicU64,
+    /// Last background failure; its own lock so the input loop can record one
+    /// while a stop holds `inner` and joins it.
+    last_background_error: Mutex<Option<BackgroundError>>,
+    /// HidHide was switched on by hand (`set_hidhide_active`) and should be switched
+    /// off again on exit.
+    manual_hiding: AtomicBool,
+    /// Cancels a forwarding start in progress without waiting for `inner`.
+    start_cancel: Arc<StartCancel>,
+    /// Latency measurement shared with the loop thread, usable without `inner`.
+    latency_probe: Arc<LatencyProbe>,
+    /// Launched with `--safe-mode`: no auto-start behaviors regardless of settings.
+    safe_mode: bool,
+    /// Set once `shutdown` has run.
+    shut_down: AtomicBool,
 }
 
 impl AppState {
-    pub fn new(manager: Arc<dyn PlatformServices>) -> Self {
-        let config = AppConfig::load().unwrap_or_default();
+    pub fn new(manager: Arc<dyn PlatformServices>, config: AppConfig, safe_mode: bool) -> Self {
+        let input_loop = InputLoop::new();
+        let start_cancel = input_loop.start_cancel();
+        let latency_probe = input_loop.latency_probe();
         Self {
             inner: Mutex::new(Inner {
                 devices: vec![],
                 assignments: vec![],
                 driver_status: DriverStatus::default(),
-                forwarding_active: false,
+                forwarding_phase: ForwardingPhase::Idle,
                 config,
-                input_loop: InputLoop::new(),
+                input_loop,
+                session: None,
             }),
+            phase_settled: Condvar::new(),
             manager,
             watcher: Mutex::new(ProcessWatcher::new()),
+            control_server: Mutex::new(ControlServer::new()),
+            refresh_generation: AtomicU64::new(0),
+            identify_generation: AtomicU64::new(0),
+            preview_generation: AtomicU64::new(0),
+            last_background_error: Mutex::new(None),
+            manual_hiding: AtomicBool::new(false),
+            start_cancel,
+            latency_probe,
+            safe_mode,
+            shut_down: AtomicBool::new(false),
         }
     }
 
+    /// Lock `Inner`, first waiting for any start/stop transition still running
+    /// outside the lock (see `stop_forwarding_with`) to settle.
     pub fn lock_inner(&self) -> MutexGuard<'_, Inner> {
-        self.inner.lock().unwrap()
+        self.phase_settled
+            .wait_while(self.inner.lock().unwrap(), |inner| {
+                inner.forwarding_phase.is_transitioning()
+            })
+            .unwrap()
+    }
+
+    /// Stop forwarding, then run `cleanup` with the lock released — e.g. unhiding
+    /// devices or re-applying persistent hides. `take` runs under the lock before
+    /// stopping, so it can see whether forwarding was active. The phase stays
+    /// `Stopping` until `cleanup` returns, so a concurrent start waits for it
+    /// instead of interleaving its hides with the cleanup's unhides.
+    pub fn stop_forwarding_with<T, R>(
+        &self,
+        take: impl FnOnce(&Inner) -> T,
+        cleanup: impl FnOnce(T) -> R,
+    ) -> R {
+        let data = {
+            let mut inner = self.lock_inner();
+            let data = take(&inner);
+            inner.stop_forwarding();
+            inner.forwarding_phase = ForwardingPhase::Stopping;
+            data
+        };
+        let _settle = SettleOnDrop(self);
+        cleanup(data)
     }
 
     pub fn manager(&self) -> &Arc<dyn PlatformServices> {
@@ -169,4 +872,451 @@ impl AppState {
     pub fn lock_watcher(&self) -> MutexGuard<'_, ProcessWatcher> {
         self.watcher.lock().unwrap()
     }
+
+    pub fn lock_control_server(&self) -> MutexGuard<'_, ControlServer> {
+        self.control_server.lock().unwrap()
+    }
+
+    pub fn identify_generation(&self) -> &AtomicU64 {
+        &self.identify_generation
+    }
+
+    pub fn preview_generation(&self) -> &AtomicU64 {
+        &self.preview_generation
+    }
+
+    pub fn safe_mode(&self) -> bool {
+        self.safe_mode
+    }
+
+    /// Crash recovery for devices: enumerate afresh (the cache may be empty or
+    /// stale), re-enable and unhide every device except `keep_hidden`, then switch
+    /// hiding off globally. In safe mode devices are only unhidden. Doesn't lock
+    /// `inner`, so it can run as a `stop_forwarding_with` cleanup.
+    pub fn recover_devices(&self, keep_hidden: &[String]) {
+        match self.manager.enumerate_devices() {
+            Ok(devices) => {
+                for dev in &devices {
+                    if !self.safe_mode {
+                        let _ = self.manager.enable_device(&dev.instance_path);
+                    }
+                    if !keep_hidden
+                        .iter()
+                        .any(|p| crate::platform::same_instance_path(p, &dev.instance_path))
+                    {
+                        let _ = self.manager.unhide_device(&dev.instance_path);
+                    }
+                }
+            }
+            Err(e) => log::warn!("Recovery: device enumeration failed: {}", e),
+        }
+        let _ = self.manager.deactivate_hiding();
+    }
+
+    /// Abort a forwarding start in progress; it fails with "Forwarding start
+    /// cancelled" once the loop has undone its steps. `false` if nothing is starting.
+    pub fn cancel_start(&self) -> bool {
+        self.start_cancel.cancel()
+    }
+
+    pub fn latency_probe(&self) -> &Arc<LatencyProbe> {
+        &self.latency_probe
+    }
+
+    /// Switch the hiding driver on or off outside a session, remembering a manual
+    /// activation so `release_manual_hiding` can undo it.
+    pub fn set_hiding_active(&self, active: bool) -> crate::error::Result<()> {
+        if active {
+            self.manager.activate_hiding()?;
+        } else {
+            self.manager.deactivate_hiding()?;
+        }
+        self.manual_hiding.store(active, Ordering::SeqCst);
+        Ok(())
+    }
+
+    /// Exit cleanup: switch off hiding left on by `set_hiding_active`, unless a
+    /// session or persistently-hidden devices still rely on it.
+    pub fn release_manual_hiding(&self) {
+        if !self.manual_hiding.swap(false, Ordering::SeqCst) {
+            return;
+        }
+        let inner = self.lock_inner();
+        if inner.forwarding_active() || !inner.config.persistent_hidden.is_empty() {
+            return;
+        }
+        drop(inner);
+        log::info!("Deactivating HidHide left on manually");
+        if let Err(e) = self.manager.deactivate_hiding() {
+            log::warn!("Failed to deactivate HidHide on exit: {}", e);
+        }
+    }
+
+    /// Exit sequence, in order: stop the watcher so it can't start a session, stop
+    /// forwarding (its cleanup unhides and re-enables devices), switch off manual
+    /// hiding, flush the config. Only the first call does anything.
+    pub fn shutdown(&self) {
+        if self.shut_down.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        log::info!("Shutting down");
+        let _caller = crate::audit::caller("shutdown");
+        self.lock_watcher().stop();
+        // Entries added mid-session were unhidden by the loop's cleanup
+        self.stop_forwarding_with(
+            |inner| {
+                inner
+                    .forwarding_active()
+                    .then(|| inner.config.persistent_hidden.clone())
+            },
+            |persistent_hidden| {
+                if let Some(persistent_hidden) = persistent_hidden {
+                    crate::platform::apply_persistent_hides(&*self.manager, &persistent_hidden);
+                }
+            },
+        );
+        self.release_manual_hiding();
+        if let Err(e) = self.lock_inner().config.save() {
+            log::warn!("Failed to flush config on exit: {}", e);
+        }
+    }
+
+    /// Remember a background failure for `get_last_background_error`.
+    pub fn record_background_error(&self, source: &str, message: &str) {
+        *self.last_background_error.lock().unwrap() = Some(BackgroundError {
+            source: source.into(),
+            message: message.into(),
+            at: chrono::Utc::now(),
+        });
+    }
+
+    pub fn last_background_error(&self) -> Option<BackgroundError> {
+        self.last_background_error.lock().unwrap().clone()
+    }
+
+    /// Forget the last background failure (after forwarding starts successfully).
+    pub fn clear_background_error(&self) {
+        *self.last_background_error.lock().unwrap() = None;
+    }
+
+    pub fn refresh_generation(&self) -> &AtomicU64 {
+        &self.refresh_generation
+    }
+}
+
+/// Ends a transition begun by `AppState::stop_forwarding_with`, even if its cleanup
+/// panics — otherwise every later `lock_inner` would wait forever.
+struct SettleOnDrop<'a>(&'a AppState);
+
+impl Drop for SettleOnDrop<'_> {
+    fn drop(&mut self) {
+        let mut inner = self.0.inner.lock().unwrap_or_else(PoisonError::into_inner);
+        inner.forwarding_phase = ForwardingPhase::Idle;
+        self.0.phase_settled.notify_all();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::GameRule;
+    use crate::platform::mock::{MockCall, MockPlatform};
+    use std::time::Duration;
+
+    const DEVICES: [&str; 3] = ["HID\\PAD_A", "HID\\PAD_B", "HID\\PAD_C"];
+
+    fn new_state() -> (Arc<AppState>, Arc<MockPlatform>) {
+        let mock = Arc::new(MockPlatform::new(vec![]));
+        let state = AppState::new(mock.clone(), AppConfig::default(), false);
+        (Arc::new(state), mock)
+    }
+
+    /// `Inner::start_forwarding`'s transitions with a session that only hides the
+    /// devices (the real one needs an `AppHandle` and an input loop), under the lock.
+    fn start(state: &AppState) {
+        state
+            .lock_inner()
+            .transition_start(|_| {
+                for path in DEVICES {
+                    state.manager().hide_device(path)?;
+                }
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    /// Mirrors the stop commands: unhiding runs after the lock is released.
+    fn stop(state: &AppState) {
+        state.stop_forwarding_with(
+            |inner| inner.forwarding_active(),
+            |was_active| {
+                if !was_active {
+                    return;
+                }
+                for path in DEVICES {
+                    state.manager().unhide_device(path).unwrap();
+                    std::thread::sleep(Duration::from_millis(1));
+                }
+            },
+        );
+    }
+
+    #[test]
+    fn concurrent_start_and_restart_never_interleave() {
+        let (state, mock) = new_state();
+
+        let workers: Vec<_> = (0..4)
+            .map(|worker| {
+                let state = state.clone();
+                std::thread::spawn(move || {
+                    for _ in 0..25 {
+                        if worker % 2 == 0 {
+                            start(&state);
+                        } else {
+                            stop(&state);
+                            start(&state);
+                        }
+                    }
+                })
+            })
+            .collect();
+        for worker in workers {
+            worker.join().unwrap();
+        }
+
+        assert!(state.lock_inner().forwarding_active());
+        stop(&state);
+
+        // Every start hid all devices and every stop unhid all of them, with no
+        // other call landing in between
+        let calls = mock.calls();
+        for (i, batch) in calls.chunks(DEVICES.len()).enumerate() {
+            let hiding = i % 2 == 0;
+            for (call, path) in batch.iter().zip(DEVICES) {
+                let expected = if hiding {
+                    MockCall::Hide(path.into())
+                } else {
+                    MockCall::Unhide(path.into())
+                };
+                assert_eq!(call, &expected, "batch {} interleaved: {:?}", i, batch);
+            }
+        }
+        assert!(mock.hidden().is_empty());
+        assert_eq!(state.lock_inner().forwarding_phase, ForwardingPhase::Idle);
+    }
+
+    #[test]
+    fn failed_start_returns_to_idle() {
+        let (state, _mock) = new_state();
+        let mut inner = state.lock_inner();
+        let result = inner.transition_start(|inner| {
+            assert_eq!(inner.forwarding_phase, ForwardingPhase::Starting);
+            Err(crate::error::PadSwitchError::Forwarding(
+                "no devices".into(),
+            ))
+        });
+        assert!(result.is_err());
+        assert_eq!(inner.forwarding_phase, ForwardingPhase::Idle);
+
+        assert!(inner.transition_start(|_| Ok(())).unwrap());
+        assert!(!inner
+            .transition_start(|_| panic!("already active"))
+            .unwrap());
+        assert_eq!(inner.forwarding_phase, ForwardingPhase::Active);
+        inner.forwarding_phase = ForwardingPhase::Idle;
+    }
+
+    #[test]
+    fn panicking_cleanup_still_settles_the_phase() {
+        let (state, _mock) = new_state();
+        start(&state);
+
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            state.stop_forwarding_with(|_| (), |()| panic!("cleanup failed"));
+        }));
+        assert!(result.is_err());
+
+        // Would block forever if the phase were left at `Stopping`
+        let inner = state.lock_inner();
+        assert_eq!(inner.forwarding_phase, ForwardingPhase::Idle);
+    }
+
+    #[test]
+    fn profile_status_reports_missing_devices() {
+        let (state, mock) = new_state();
+        let mut inner = state.lock_inner();
+        inner.devices = vec![PhysicalDevice::from_xinput_slot(0)];
+        let assignment = |device_id: &str| SlotAssignment {
+            device_id: device_id.into(),
+            slot: 0,
+            enabled: true,
+            mapping: Default::default(),
+            passthrough: false,
+            routing_mode: None,
+        };
+        let mut profile = Profile {
+            id: "p".into(),
+            name: "P".into(),
+            assignments: vec![assignment("missing")],
+            routing_mode: RoutingMode::Force,
+            hard_hide: false,
+            virtual_subtype: Default::default(),
+            virtual_layout_id: None,
+        };
+        let status = inner.profile_status(&profile, &*mock);
+        assert!(!status.can_run);
+        assert_eq!(status.blockers.len(), 1);
+
+        profile.assignments = vec![assignment("xinput-0")];
+        assert!(inner.profile_status(&profile, &*mock).can_run);
+    }
+
+    #[test]
+    fn directinput_force_assignments_warn_on_windows() {
+        let (state, _) = new_state();
+        let mut inner = state.lock_inner();
+        inner.devices = vec![PhysicalDevice {
+            id: "dinput".into(),
+            name: "DirectInput Pad".into(),
+            instance_path: "HID\\VID_054C&PID_0CE6\\PAD".into(),
+            device_type: DeviceType::DirectInput,
+            hidden: false,
+            connected: true,
+            vendor_id: 0x054C,
+            product_id: 0x0CE6,
+            xinput_slot: None,
+            xinput_overflow: false,
+        }];
+        let assignments = vec![SlotAssignment {
+            device_id: "dinput".into(),
+            slot: 1,
+            enabled: true,
+            mapping: Default::default(),
+            passthrough: false,
+            routing_mode: None,
+        }];
+
+        let warnings = inner.assignment_warnings(&assignments, &RoutingMode::Force);
+        assert_eq!(warnings.len(), usize::from(cfg!(target_os = "windows")));
+        assert!(inner
+            .assignment_warnings(&assignments, &RoutingMode::Minimal)
+            .is_empty());
+    }
+
+    #[test]
+    fn passthrough_slots_reject_other_devices() {
+        let (state, _) = new_state();
+        let mut inner = state.lock_inner();
+        inner.devices = vec![
+            PhysicalDevice::from_xinput_slot(0),
+            PhysicalDevice::from_xinput_slot(1),
+        ];
+        let assignment = |device_id: &str, slot: u8, passthrough: bool| SlotAssignment {
+            device_id: device_id.into(),
+            slot,
+            enabled: true,
+            mapping: Default::default(),
+            passthrough,
+            routing_mode: None,
+        };
+        inner.assignments = vec![
+            assignment("xinput-1", 3, true),
+            assignment("xinput-0", 1, false),
+        ];
+
+        let explained = inner.explain_assignments();
+        assert_eq!(explained.resolved.len(), 1);
+        assert_eq!(explained.resolved[0].target_slot, 1);
+        assert!(matches!(
+            explained.unresolved[..],
+            [UnresolvedAssignment {
+                reason: UnresolvedReason::SlotReserved,
+                ..
+            }]
+        ));
+        assert_eq!(inner.resolve_assignments().len(), 1);
+
+        inner.assignments[1].slot = 2;
+        assert_eq!(inner.resolve_assignments().len(), 2);
+    }
+
+    #[test]
+    fn restart_check_only_flags_session_fields() {
+        let (state, _) = new_state();
+        let mut inner = state.lock_inner();
+        let mut settings = inner.config.settings.clone();
+        settings.auto_switch = !settings.auto_switch;
+        settings.forward_rumble = !settings.forward_rumble;
+        assert!(!inner.restart_check(Some(&settings), None).required);
+
+        inner.forwarding_phase = ForwardingPhase::Active;
+        inner.session = Some(inner.session_snapshot());
+        let check = inner.restart_check(Some(&settings), None);
+        assert!(check.required && !check.app_restart);
+        assert_eq!(check.reasons.len(), 1);
+
+        settings.forward_rumble = inner.config.settings.forward_rumble;
+        assert!(!inner.restart_check(Some(&settings), None).required);
+        inner.forwarding_phase = ForwardingPhase::Idle;
+    }
+
+    /// Inner with a session running on the current config.
+    fn forwarding_inner(state: &AppState) -> MutexGuard<'_, Inner> {
+        let mut inner = state.lock_inner();
+        inner.forwarding_phase = ForwardingPhase::Active;
+        inner.session = Some(inner.session_snapshot());
+        inner
+    }
+
+    #[test]
+    fn restart_check_flags_game_rules_feeding_the_focus_gate() {
+        let (state, _) = new_state();
+        state.lock_inner().config.settings.forward_only_when_focused = true;
+        let mut inner = forwarding_inner(&state);
+        inner.config.game_rules.push(GameRule {
+            id: "r".into(),
+            exe_names: vec!["game.exe".into()],
+            profile_id: "p".into(),
+            enabled: true,
+            activation_delay_ms: 0,
+        });
+
+        let check = inner.restart_check(None, None);
+        assert!(check.required);
+        assert_eq!(check.reasons, ["Focus-only forwarding changed"]);
+        inner.forwarding_phase = ForwardingPhase::Idle;
+    }
+
+    #[test]
+    fn restart_check_flags_persistent_hidden_changes() {
+        let (state, _) = new_state();
+        let mut inner = forwarding_inner(&state);
+        inner.config.persistent_hidden.push("HID\\PAD_A".into());
+
+        let check = inner.restart_check(None, None);
+        assert_eq!(check.reasons, ["Persistently hidden devices changed"]);
+        inner.forwarding_phase = ForwardingPhase::Idle;
+    }
+
+    #[test]
+    fn restart_check_compares_assignments_with_the_running_session() {
+        let (state, _) = new_state();
+        let mut inner = forwarding_inner(&state);
+        inner.assignments.push(SlotAssignment {
+            device_id: "xinput-0".into(),
+            slot: 0,
+            enabled: true,
+            mapping: Default::default(),
+            passthrough: false,
+            routing_mode: None,
+        });
+        assert_eq!(
+            inner.restart_check(None, None).reasons,
+            ["Assignments or their mappings changed"]
+        );
+
+        inner.assignments.clear();
+        assert!(!inner.restart_check(None, None).required);
+        inner.forwarding_phase = ForwardingPhase::Idle;
+    }
 }
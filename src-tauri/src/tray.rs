@@ -1,3 +1,5 @@
+use crate::config::{Profile, RoutingMode, TrayProfileDetail};
+use crate::platform::RestoreOptions;
 use crate::state::AppState;
 use tauri::{
     menu::{Menu, MenuBuilder, MenuItem, PredefinedMenuItem, Submenu},
@@ -29,6 +31,8 @@ pub fn setup_tray(app: &AppHandle) -> tauri::Result<()> {
                         reset_from_tray(app);
                     }
                     "quit" => {
+                        // Clean up before the event loop winds down
+                        crate::shutdown(app);
                         app.exit(0);
                     }
                     _ => {}
@@ -58,15 +62,14 @@ fn build_tray_menu(app: &AppHandle) -> tauri::Result<Menu<tauri::Wry>> {
         let inner = state.lock_inner();
         let profiles = &inner.config.profiles;
         let active_id = inner.config.settings.active_profile_id.as_deref();
+        let detail = inner.config.settings.tray_profile_detail;
+        let forwarding = inner.is_forwarding();
 
         if !profiles.is_empty() {
             let mut submenu_items: Vec<MenuItem<tauri::Wry>> = Vec::new();
             for profile in profiles {
-                let label = if active_id == Some(&profile.id) {
-                    format!("* {}", profile.name)
-                } else {
-                    profile.name.clone()
-                };
+                let active = active_id == Some(&profile.id);
+                let label = profile_label(profile, active, forwarding, detail);
                 let item_id = format!("{}{}", PROFILE_PREFIX, profile.id);
                 let item = MenuItem::with_id(app, item_id, label, true, None::<&str>)?;
                 submenu_items.push(item);
@@ -92,33 +95,48 @@ fn build_tray_menu(app: &AppHandle) -> tauri::Result<Menu<tauri::Wry>> {
         .build()
 }
 
+/// Submenu label for `profile`. Only the text changes with `detail`; the item id
+/// stays `profile:<id>`.
+fn profile_label(
+    profile: &Profile,
+    active: bool,
+    forwarding: bool,
+    detail: TrayProfileDetail,
+) -> String {
+    let marker = match (active, forwarding, detail) {
+        (false, _, _) => "",
+        (true, true, TrayProfileDetail::Full) => "▶ ",
+        (true, _, _) => "* ",
+    };
+    let mode = match (detail, &profile.routing_mode) {
+        (TrayProfileDetail::Marker, _) => "",
+        (_, RoutingMode::Minimal) => " (Minimal)",
+        (_, RoutingMode::Force) => " (Force)",
+    };
+    format!("{}{}{}", marker, profile.name, mode)
+}
+
 fn reset_from_tray(app: &AppHandle) {
     let state: Option<tauri::State<'_, AppState>> = app.try_state();
     let Some(state) = state else { return };
+    let _caller = crate::audit::caller("reset");
 
-    // Stop watcher
-    state.lock_watcher().stop();
+    // Stop watcher (or suppress the running game's rule, if configured)
+    crate::process_watcher::pause_for_reset(&state);
 
-    // Stop forwarding
+    // Stop forwarding, then re-enable and unhide all known devices
     let manager = state.manager().clone();
-    {
-        let mut inner = state.lock_inner();
-        if inner.forwarding_active {
-            inner.input_loop.stop();
-            inner.forwarding_active = false;
-        }
-    }
-
-    // Re-enable and unhide all known devices
-    let device_paths: Vec<String> = {
-        let inner = state.lock_inner();
-        inner.devices.iter().map(|d| d.instance_path.clone()).collect()
-    };
-    for path in &device_paths {
-        let _ = manager.enable_device(path);
-        let _ = manager.unhide_device(path);
-    }
-    let _ = manager.deactivate_hiding();
+    state.stop_forwarding_with(
+        |inner| {
+            let paths: Vec<String> = inner
+                .devices
+                .iter()
+                .map(|d| d.instance_path.clone())
+                .collect();
+            (paths, RestoreOptions::from_settings(&inner.config.settings))
+        },
+        |(paths, options)| crate::platform::restore_devices(&*manager, &paths, &options),
+    );
 
     // Clear active profile
     {
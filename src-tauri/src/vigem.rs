@@ -12,8 +12,23 @@
 
 #[cfg(target_os = "windows")]
 pub mod imp {
+    use crate::config::VirtualSubtype;
     use crate::device::GamepadState;
 
+    /// Xbox Series X|S controller (USB) vendor/product IDs.
+    const XBOX_SERIES_TARGET_ID: vigem_client::TargetId = vigem_client::TargetId {
+        vendor_id: 0x045E,
+        product_id: 0x0B12,
+    };
+
+    /// Target identifiers presented to games for a virtual controller subtype.
+    pub fn target_id(subtype: VirtualSubtype) -> vigem_client::TargetId {
+        match subtype {
+            VirtualSubtype::Xbox360 => vigem_client::TargetId::XBOX360_WIRED,
+            VirtualSubtype::XboxSeries => XBOX_SERIES_TARGET_ID,
+        }
+    }
+
     /// Check if ViGEmBus is installed by attempting to connect.
     pub fn is_installed() -> bool {
         vigem_client::Client::connect().is_ok()
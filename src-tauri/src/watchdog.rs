@@ -0,0 +1,149 @@
+use crate::error::{PadSwitchError, Result};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+/// Command-line flag that runs the process as a Minimal-mode watchdog instead of
+/// the app: `--minimal-watchdog <session> <timeout_secs>`.
+pub const WATCHDOG_FLAG: &str = "--minimal-watchdog";
+
+/// Shortest accepted timeout, so a busy reorder can't trip the watchdog.
+const MIN_TIMEOUT_SECS: u64 = 3;
+
+/// How often the watchdog checks the heartbeat file.
+const POLL: Duration = Duration::from_secs(1);
+
+/// Contents of the heartbeat file: which session owns it and what to re-enable.
+#[derive(Serialize, Deserialize)]
+struct HeartbeatFile {
+    session: String,
+    instance_paths: Vec<String>,
+}
+
+fn heartbeat_path() -> Option<PathBuf> {
+    crate::config::data_dir().map(|d| d.join("minimal.heartbeat"))
+}
+
+/// Keep-alive for Minimal mode. While it lives, the loop calls `beat` regularly;
+/// if the process dies without dropping it, the watchdog process spawned by
+/// `start` sees the heartbeat go stale and re-enables the devices.
+#[cfg(target_os = "windows")]
+pub struct Heartbeat {
+    path: PathBuf,
+    contents: String,
+}
+
+#[cfg(target_os = "windows")]
+impl Heartbeat {
+    /// Write the heartbeat file and spawn a watchdog that reverts `instance_paths`
+    /// once the file is older than `timeout_secs`.
+    pub fn start(instance_paths: &[&str], timeout_secs: u32) -> Result<Self> {
+        let path = heartbeat_path()
+            .ok_or_else(|| PadSwitchError::Config("Cannot find config directory".into()))?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let session = uuid::Uuid::new_v4().to_string();
+        let contents = serde_json::to_string(&HeartbeatFile {
+            session: session.clone(),
+            instance_paths: instance_paths.iter().map(|p| p.to_string()).collect(),
+        })?;
+        std::fs::write(&path, &contents)?;
+
+        let exe = std::env::current_exe()?;
+        let timeout = (timeout_secs as u64).max(MIN_TIMEOUT_SECS);
+        if let Err(e) = std::process::Command::new(exe)
+            .arg(WATCHDOG_FLAG)
+            .arg(&session)
+            .arg(timeout.to_string())
+            .spawn()
+        {
+            let _ = std::fs::remove_file(&path);
+            return Err(e.into());
+        }
+
+        log::info!("Minimal-mode watchdog started (timeout {}s)", timeout);
+        Ok(Self { path, contents })
+    }
+
+    /// Refresh the heartbeat so the watchdog stays idle.
+    pub fn beat(&self) {
+        if let Err(e) = std::fs::write(&self.path, &self.contents) {
+            log::warn!("Failed to write Minimal-mode heartbeat: {}", e);
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+impl Drop for Heartbeat {
+    /// Clean shutdown: removing the file tells the watchdog to exit.
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// Watchdog process entry point (see `WATCHDOG_FLAG`). Exits once the heartbeat
+/// file is removed or taken over by another session; if it goes stale instead,
+/// re-enables the recorded devices first.
+pub fn run(args: &[String]) {
+    let (Some(session), Some(timeout)) = (args.first(), args.get(1)) else {
+        log::error!("Usage: {} <session> <timeout_secs>", WATCHDOG_FLAG);
+        return;
+    };
+    let timeout = Duration::from_secs(
+        timeout
+            .parse::<u64>()
+            .unwrap_or(MIN_TIMEOUT_SECS)
+            .max(MIN_TIMEOUT_SECS),
+    );
+    let Some(path) = heartbeat_path() else { return };
+
+    loop {
+        std::thread::sleep(POLL);
+
+        let Ok(data) = std::fs::read_to_string(&path) else {
+            return; // Stopped cleanly
+        };
+        let Ok(heartbeat) = serde_json::from_str::<HeartbeatFile>(&data) else {
+            continue; // Caught mid-write; check again next tick
+        };
+        if heartbeat.session != *session {
+            return; // A newer session owns the file
+        }
+
+        let age = std::fs::metadata(&path)
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+            .unwrap_or_default();
+        if age < timeout {
+            continue;
+        }
+
+        log::warn!(
+            "Minimal-mode heartbeat stale for {}s — re-enabling {} devices",
+            age.as_secs(),
+            heartbeat.instance_paths.len()
+        );
+        for instance_path in &heartbeat.instance_paths {
+            if let Err(e) = enable_device(instance_path) {
+                log::warn!("Watchdog enable failed for {}: {}", instance_path, e);
+            }
+        }
+        let _ = std::fs::remove_file(&path);
+        return;
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn enable_device(instance_path: &str) -> Result<()> {
+    crate::setupdi::imp::enable_device(instance_path)
+}
+
+#[cfg(not(target_os = "windows"))]
+fn enable_device(_instance_path: &str) -> Result<()> {
+    Err(PadSwitchError::PlatformNotSupported(
+        "Minimal mode only disables devices on Windows".into(),
+    ))
+}
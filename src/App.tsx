@@ -16,6 +16,7 @@ function App() {
     devices,
     driverStatus,
     elevated,
+    safeMode,
     identifying,
     profiles,
     activeProfileId,
@@ -83,6 +84,13 @@ function App() {
         </div>
       )}
 
+      {safeMode && (
+        <div className="safe-mode-banner">
+          Safe mode — auto-switch, auto-forwarding and the control server were not started.
+          Restart without --safe-mode to restore them.
+        </div>
+      )}
+
       <DriverStatus status={driverStatus} routingMode={routingMode} elevated={elevated} />
 
       {identifying && (
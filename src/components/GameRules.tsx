@@ -5,7 +5,7 @@ interface GameRulesProps {
   rules: GameRule[];
   profiles: Profile[];
   watcherRunning: boolean;
-  onAddRule: (exeName: string, profileId: string) => void;
+  onAddRule: (exeNames: string[], profileId: string) => void;
   onDeleteRule: (ruleId: string) => void;
   onToggleRule: (ruleId: string, enabled: boolean) => void;
   onToggleWatcher: (running: boolean) => void;
@@ -24,9 +24,13 @@ export default function GameRules({
   const [profileId, setProfileId] = useState("");
 
   function handleAdd() {
-    const trimmed = exeName.trim();
-    if (!trimmed || !profileId) return;
-    onAddRule(trimmed, profileId);
+    // Comma-separated so a launcher and its game can share one rule
+    const names = exeName
+      .split(",")
+      .map((n) => n.trim())
+      .filter(Boolean);
+    if (names.length === 0 || !profileId) return;
+    onAddRule(names, profileId);
     setExeName("");
   }
 
@@ -62,7 +66,7 @@ export default function GameRules({
               className={`rule-card ${!rule.enabled ? "rule-disabled" : ""}`}
             >
               <div className="rule-info">
-                <div className="rule-exe">{rule.exe_name}</div>
+                <div className="rule-exe">{rule.exe_names.join(", ")}</div>
                 <div className="rule-profile">{profileName(rule.profile_id)}</div>
               </div>
               <label className="card-toggle">
@@ -100,8 +104,8 @@ export default function GameRules({
             type="text"
             value={exeName}
             onChange={(e) => setExeName(e.target.value)}
-            placeholder="e.g. RocketLeague.exe"
-            maxLength={128}
+            placeholder="e.g. Launcher.exe, Game-Win64-Shipping.exe"
+            maxLength={256}
           />
           <select
             value={profileId}
@@ -10,6 +10,7 @@ import type {
 } from "../types/controller";
 import {
   getConnectedDevices,
+  refreshDevices,
   checkDriverStatus,
   toggleDevice,
   applyAssignments,
@@ -17,6 +18,7 @@ import {
   stopForwarding,
   isForwarding,
   isElevated,
+  isSafeMode,
   detectXInputSlot,
   confirmDeviceSlot,
   getProfiles,
@@ -37,6 +39,7 @@ import {
 import {
   onDeviceChange,
   onForwardingStatus,
+  onForwardingError,
   onProfileActivated,
 } from "../lib/events";
 
@@ -79,6 +82,7 @@ export function usePadSwitch() {
   const [gameRules, setGameRules] = useState<GameRule[]>([]);
   const [watcherRunning, setWatcherRunning] = useState(false);
   const [elevated, setElevated] = useState(true); // assume true until checked
+  const [safeMode, setSafeMode] = useState(false);
   const [identifying, setIdentifying] = useState<string | null>(null); // device ID being identified
   const [forwarding, setForwarding] = useState(false);
   const [loading, setLoading] = useState(true);
@@ -86,13 +90,14 @@ export function usePadSwitch() {
 
   const refresh = useCallback(async () => {
     try {
-      const [devs, drivers, fwd, loadedProfiles, settings, elev, rules, watching] = await Promise.all([
-        getConnectedDevices(),
+      const [devs, drivers, fwd, loadedProfiles, settings, elev, safe, rules, watching] = await Promise.all([
+        refreshDevices(),
         checkDriverStatus(),
         isForwarding(),
         getProfiles(),
         getSettings(),
         isElevated(),
+        isSafeMode(),
         getGameRules(),
         isWatcherRunning(),
       ]);
@@ -111,6 +116,7 @@ export function usePadSwitch() {
       setActiveProfileId(settings.active_profile_id);
       setRoutingMode(activeProfile?.routing_mode ?? "Minimal");
       setElevated(elev);
+      setSafeMode(safe);
       setGameRules(rules);
       setWatcherRunning(watching);
       setError(null);
@@ -136,6 +142,12 @@ export function usePadSwitch() {
         setError(payload.error);
       }
     });
+    // Fatal errors also arrive as forwarding-status { active: false, error }
+    const unlistenForwardingError = onForwardingError((payload) => {
+      if (!payload.fatal) {
+        setError(payload.error);
+      }
+    });
     const unlistenProfile = onProfileActivated((payload) => {
       setActiveProfileId(payload.profile_id);
       setRoutingMode(payload.routing_mode);
@@ -148,6 +160,7 @@ export function usePadSwitch() {
     return () => {
       unlistenDevice.then((fn) => fn());
       unlistenForwarding.then((fn) => fn());
+      unlistenForwardingError.then((fn) => fn());
       unlistenProfile.then((fn) => fn());
     };
   }, []);
@@ -199,7 +212,14 @@ export function usePadSwitch() {
     async (name: string, mode: RoutingMode) => {
       try {
         const assignments = currentAssignments(devices);
-        const profile = await saveProfile(name, assignments, mode);
+        const { profile, warnings } = await saveProfile({
+          name,
+          assignments,
+          routing_mode: mode,
+        });
+        if (warnings.length > 0) {
+          setError(warnings.join("\n"));
+        }
         const nextProfiles = [...profiles, profile];
         setProfiles(nextProfiles);
         setActiveProfileId(profile.id);
@@ -244,9 +264,9 @@ export function usePadSwitch() {
   );
 
   const handleAddGameRule = useCallback(
-    async (exeName: string, profileId: string) => {
+    async (exeNames: string[], profileId: string) => {
       try {
-        const rule = await addGameRule(exeName, profileId);
+        const rule = await addGameRule(exeNames, profileId);
         setGameRules((prev) => [...prev, rule]);
       } catch (err) {
         setError(err instanceof Error ? err.message : String(err));
@@ -336,6 +356,7 @@ export function usePadSwitch() {
     devices,
     driverStatus,
     elevated,
+    safeMode,
     identifying,
     profiles,
     activeProfileId,
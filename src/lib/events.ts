@@ -1,21 +1,93 @@
 import { listen, type UnlistenFn } from "@tauri-apps/api/event";
-import type { PhysicalDevice, SlotAssignment, RoutingMode } from "../types/controller";
+import type {
+  AppConfig,
+  ConflictWarning,
+  GamepadState,
+  PhysicalDevice,
+  SlotAssignment,
+  RoutingMode,
+} from "../types/controller";
 
 export interface DeviceChangePayload {
   devices: PhysicalDevice[];
 }
 
+/** What a re-enumeration changed, matched by device id. */
+export interface DeviceDiff {
+  added: PhysicalDevice[];
+  removed: PhysicalDevice[];
+  changed: PhysicalDevice[];
+}
+
+export interface DevicesUpdatedPayload extends DeviceChangePayload {
+  diff: DeviceDiff;
+}
+
 export interface ForwardingStatusPayload {
   active: boolean;
   error?: string;
 }
 
+export interface ForwardingErrorPayload {
+  error: string;
+  /** True if the loop stopped; a `forwarding-status { active: false }` follows. */
+  fatal: boolean;
+}
+
+/** Linux Force mode: a source device dropped out; the others keep forwarding. */
+export interface ForwardingDeviceLostPayload {
+  instance_path: string;
+  reason: string;
+}
+
+/** Linux Force mode: a lost source device was reopened. */
+export interface ForwardingDeviceRestoredPayload {
+  instance_path: string;
+}
+
+/** A Force-mode startup step is retrying (e.g. connecting to ViGEmBus). */
+export interface ForwardingProgressPayload {
+  message: string;
+}
+
+/** One entry per XInput slot 0-3; `null` when the slot is empty. */
+export interface SlotsPreviewPayload {
+  slots: (GamepadState | null)[];
+}
+
+/** The all-slots preview ended on its own (e.g. Force mode started). */
+export interface SlotsPreviewStoppedPayload {
+  reason: string;
+}
+
+export interface ConfigRestoredPayload {
+  config: AppConfig;
+}
+
 export interface ProfileActivatedPayload {
   profile_id: string | null;
   assignments: SlotAssignment[];
   routing_mode: RoutingMode;
 }
 
+export interface GameRuleFiredPayload {
+  rule_id: string;
+  exe_name: string;
+  profile_id: string | null;
+  /** "kept"/"stopped": the game exited and its profile stayed active (forwarding stopped). */
+  action: "activated" | "reverted" | "switched" | "kept" | "stopped";
+}
+
+export interface IdentifyStepPayload {
+  slot: number;
+  step: number;
+  total: number;
+}
+
+export interface IdentifyDonePayload {
+  cancelled: boolean;
+}
+
 export function onDeviceChange(
   callback: (payload: DeviceChangePayload) => void
 ): Promise<UnlistenFn> {
@@ -24,6 +96,14 @@ export function onDeviceChange(
   });
 }
 
+export function onDevicesUpdated(
+  callback: (payload: DevicesUpdatedPayload) => void
+): Promise<UnlistenFn> {
+  return listen<DevicesUpdatedPayload>("devices-updated", (event) => {
+    callback(event.payload);
+  });
+}
+
 export function onForwardingStatus(
   callback: (payload: ForwardingStatusPayload) => void
 ): Promise<UnlistenFn> {
@@ -32,6 +112,46 @@ export function onForwardingStatus(
   });
 }
 
+export function onForwardingError(
+  callback: (payload: ForwardingErrorPayload) => void
+): Promise<UnlistenFn> {
+  return listen<ForwardingErrorPayload>("forwarding-error", (event) => {
+    callback(event.payload);
+  });
+}
+
+export function onForwardingDeviceLost(
+  callback: (payload: ForwardingDeviceLostPayload) => void
+): Promise<UnlistenFn> {
+  return listen<ForwardingDeviceLostPayload>("forwarding-device-lost", (event) => {
+    callback(event.payload);
+  });
+}
+
+export function onForwardingDeviceRestored(
+  callback: (payload: ForwardingDeviceRestoredPayload) => void
+): Promise<UnlistenFn> {
+  return listen<ForwardingDeviceRestoredPayload>("forwarding-device-restored", (event) => {
+    callback(event.payload);
+  });
+}
+
+export function onForwardingProgress(
+  callback: (payload: ForwardingProgressPayload) => void
+): Promise<UnlistenFn> {
+  return listen<ForwardingProgressPayload>("forwarding-progress", (event) => {
+    callback(event.payload);
+  });
+}
+
+export function onConfigRestored(
+  callback: (payload: ConfigRestoredPayload) => void
+): Promise<UnlistenFn> {
+  return listen<ConfigRestoredPayload>("config-restored", (event) => {
+    callback(event.payload);
+  });
+}
+
 export function onProfileActivated(
   callback: (payload: ProfileActivatedPayload) => void
 ): Promise<UnlistenFn> {
@@ -39,3 +159,73 @@ export function onProfileActivated(
     callback(event.payload);
   });
 }
+
+export function onGameRuleFired(
+  callback: (payload: GameRuleFiredPayload) => void
+): Promise<UnlistenFn> {
+  return listen<GameRuleFiredPayload>("game-rule-fired", (event) => {
+    callback(event.payload);
+  });
+}
+
+export function onIdentifyStep(
+  callback: (payload: IdentifyStepPayload) => void
+): Promise<UnlistenFn> {
+  return listen<IdentifyStepPayload>("identify-step", (event) => {
+    callback(event.payload);
+  });
+}
+
+export function onIdentifyDone(
+  callback: (payload: IdentifyDonePayload) => void
+): Promise<UnlistenFn> {
+  return listen<IdentifyDonePayload>("identify-done", (event) => {
+    callback(event.payload);
+  });
+}
+
+export function onSlotsPreview(
+  callback: (payload: SlotsPreviewPayload) => void
+): Promise<UnlistenFn> {
+  return listen<SlotsPreviewPayload>("slots-preview", (event) => {
+    callback(event.payload);
+  });
+}
+
+export function onSlotsPreviewStopped(
+  callback: (payload: SlotsPreviewStoppedPayload) => void
+): Promise<UnlistenFn> {
+  return listen<SlotsPreviewStoppedPayload>("slots-preview-stopped", (event) => {
+    callback(event.payload);
+  });
+}
+
+/** Force mode started while Steam Input is likely grabbing controllers (non-fatal). */
+export function onSteamInputWarning(
+  callback: (payload: ConflictWarning) => void
+): Promise<UnlistenFn> {
+  return listen<ConflictWarning>("steam-input-warning", (event) => {
+    callback(event.payload);
+  });
+}
+
+/** Saved profiles that need admin (Minimal mode or hard hide) while not elevated. */
+export interface NeedsElevationWarningPayload {
+  profiles: { id: string; name: string }[];
+}
+
+/** Emitted at launch; offer `relaunchElevated` before the user starts forwarding. */
+export function onNeedsElevationWarning(
+  callback: (payload: NeedsElevationWarningPayload) => void
+): Promise<UnlistenFn> {
+  return listen<NeedsElevationWarningPayload>("needs-elevation-warning", (event) => {
+    callback(event.payload);
+  });
+}
+
+/** Emitted at launch after a crash when automatic recovery is turned off. */
+export function onDirtyShutdownDetected(callback: () => void): Promise<UnlistenFn> {
+  return listen("dirty-shutdown-detected", () => {
+    callback();
+  });
+}
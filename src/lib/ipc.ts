@@ -1,40 +1,110 @@
 import { invoke } from "@tauri-apps/api/core";
 import type {
+  AppConfig,
   PhysicalDevice,
+  DeviceCapabilities,
+  HideCandidate,
   DriverStatus,
   SlotAssignment,
   Profile,
+  ProfileInput,
   GameRule,
+  MoveDirection,
   Settings,
   RoutingMode,
+  RemapPreset,
+  AxisOverride,
+  SavedProfile,
+  VirtualLayout,
+  VirtualSubtype,
+  AssignmentResolution,
+  HiddenDevicePreview,
+  AppInfo,
+  BackgroundError,
+  VirtualControllerInfo,
+  LatencyReport,
+  ConflictWarning,
+  EffectiveRoutingMode,
+  RestartCheck,
+  DeviceSlotTable,
+  Diagnostics,
+  ProfileWithStatus,
 } from "../types/controller";
 
 // Device discovery
 export const getConnectedDevices = () =>
   invoke<PhysicalDevice[]>("get_connected_devices");
 
+export const refreshDevices = () => invoke<PhysicalDevice[]>("refresh_devices");
+
 export const checkDriverStatus = () =>
   invoke<DriverStatus>("check_driver_status");
 
+export const detectConflicts = () => invoke<ConflictWarning[]>("detect_conflicts");
+
+// Raw HID/evdev frames as hex, for troubleshooting unknown controllers
+export const readRawReport = (instancePath: string, count: number) =>
+  invoke<string[]>("read_raw_report", { instancePath, count });
+
+export const getDeviceCapabilities = (deviceId: string) =>
+  invoke<DeviceCapabilities>("get_device_capabilities", { deviceId });
+
+export const deviceSupportsRumble = (deviceId: string) =>
+  invoke<boolean>("device_supports_rumble", { deviceId });
+
+// Paths HidHide could blacklist for a device and whether each is (Windows-only)
+export const getHideCandidates = (deviceId: string) =>
+  invoke<HideCandidate[]>("get_hide_candidates", { deviceId });
+
+// HidHide global switch, for troubleshooting (Windows only)
+export const getHidHideActive = () => invoke<boolean>("get_hidhide_active");
+
+export const setHidHideActive = (active: boolean) =>
+  invoke<void>("set_hidhide_active", { active });
+
 // Device toggling
 export const toggleDevice = (deviceId: string, hidden: boolean) =>
   invoke<void>("toggle_device", { deviceId, hidden });
 
 // Forwarding
+/** Resolves to warnings about assignments forwarding would skip. */
 export const applyAssignments = (assignments: SlotAssignment[]) =>
-  invoke<void>("apply_assignments", { assignments });
+  invoke<string[]>("apply_assignments", { assignments });
+
+export const rebindSlot = (targetSlot: number, newDeviceId: string) =>
+  invoke<SlotAssignment[]>("rebind_slot", { targetSlot, newDeviceId });
+
+export const getVirtualControllers = () =>
+  invoke<VirtualControllerInfo[]>("get_virtual_controllers");
+
+export const measureLatency = (targetSlot: number) =>
+  invoke<LatencyReport>("measure_latency", { targetSlot });
+
+export const getResolvedAssignments = () =>
+  invoke<AssignmentResolution>("get_resolved_assignments");
+
+export const previewHiddenDevices = (profileId: string) =>
+  invoke<HiddenDevicePreview[]>("preview_hidden_devices", { profileId });
 
 export const startForwarding = () => invoke<void>("start_forwarding");
 
 export const stopForwarding = () => invoke<void>("stop_forwarding");
 
+export const cancelStartForwarding = () => invoke<void>("cancel_start_forwarding");
+
 export const isForwarding = () => invoke<boolean>("is_forwarding");
 
 // Profiles
 export const getProfiles = () => invoke<Profile[]>("get_profiles");
 
-export const saveProfile = (name: string, assignments: SlotAssignment[], routingMode: RoutingMode) =>
-  invoke<Profile>("save_profile", { name, assignments, routingMode });
+export const getProfilesWithStatus = () =>
+  invoke<ProfileWithStatus[]>("get_profiles_with_status");
+
+export const profilesUsingDevice = (deviceId: string) =>
+  invoke<Profile[]>("profiles_using_device", { deviceId });
+
+export const saveProfile = (profile: ProfileInput) =>
+  invoke<SavedProfile>("save_profile", { profile });
 
 export const deleteProfile = (profileId: string) =>
   invoke<void>("delete_profile", { profileId });
@@ -42,9 +112,73 @@ export const deleteProfile = (profileId: string) =>
 export const activateProfile = (profileId: string) =>
   invoke<SlotAssignment[]>("activate_profile", { profileId });
 
+export const setProfileRoutingMode = (profileId: string, routingMode: RoutingMode) =>
+  invoke<Profile>("set_profile_routing_mode", { profileId, routingMode });
+
+export const applyRemapPreset = (
+  profileId: string,
+  assignmentIndex: number,
+  preset: RemapPreset,
+) => invoke<Profile>("apply_remap_preset", { profileId, assignmentIndex, preset });
+
+export const setAxisOverrides = (deviceId: string, overrides: AxisOverride[]) =>
+  invoke<void>("set_axis_overrides", { deviceId, overrides });
+
+// Virtual layouts (shared Force mode target sets)
+export const getVirtualLayouts = () => invoke<VirtualLayout[]>("get_virtual_layouts");
+
+/** Creates a layout when `layoutId` is omitted, otherwise replaces it. */
+export const saveVirtualLayout = (
+  name: string,
+  targets: VirtualSubtype[],
+  layoutId?: string
+) => invoke<VirtualLayout>("save_virtual_layout", { layoutId, name, targets });
+
+export const deleteVirtualLayout = (layoutId: string) =>
+  invoke<void>("delete_virtual_layout", { layoutId });
+
+export const setProfileVirtualLayout = (profileId: string, layoutId: string | null) =>
+  invoke<Profile>("set_profile_virtual_layout", { profileId, layoutId });
+
 // Environment
 export const isElevated = () => invoke<boolean>("is_elevated");
 
+export const isSafeMode = () => invoke<boolean>("is_safe_mode");
+
+// Last input loop / watcher failure, in case its event fired before the UI listened
+export const getLastBackgroundError = () =>
+  invoke<BackgroundError | null>("get_last_background_error");
+
+export const getAppInfo = () => invoke<AppInfo>("get_app_info");
+
+export const getBackendDescription = () => invoke<string>("get_backend_description");
+
+// Support artifacts
+export const dumpDeviceSlotTable = () =>
+  invoke<DeviceSlotTable>("dump_device_slot_table");
+
+export const collectDiagnostics = () => invoke<Diagnostics>("collect_diagnostics");
+
+/** Copies a trimmed diagnostics summary; resolves to its length in characters. */
+export const copyDiagnosticsToClipboard = () =>
+  invoke<number>("copy_diagnostics_to_clipboard");
+
+export const readAuditLog = (lines?: number) =>
+  invoke<string[]>("read_audit_log", { lines });
+
+export const getAuditLogPath = () => invoke<string | null>("get_audit_log_path");
+
+export const getEffectiveRoutingMode = () =>
+  invoke<EffectiveRoutingMode>("get_effective_routing_mode");
+
+export const changesRequireRestart = (settings?: Settings, profile?: Profile) =>
+  invoke<RestartCheck>("changes_require_restart", { settings, profile });
+
+export const planRequiresElevation = (routingMode: RoutingMode) =>
+  invoke<boolean>("plan_requires_elevation", { routingMode });
+
+export const relaunchElevated = () => invoke<void>("relaunch_elevated");
+
 // Device identification — polls XInput for button press, returns slot 0-3 or null
 export const detectXInputSlot = () => invoke<number | null>("detect_xinput_slot");
 
@@ -52,11 +186,37 @@ export const detectXInputSlot = () => invoke<number | null>("detect_xinput_slot"
 export const confirmDeviceSlot = (deviceId: string, xinputSlot: number) =>
   invoke<void>("confirm_device_slot", { deviceId, xinputSlot });
 
+// Rumble a slot / all slots in sequence (see onIdentifyStep)
+export const pulseSlot = (slot: number) => invoke<void>("pulse_slot", { slot });
+
+export const identifyAll = () => invoke<void>("identify_all");
+
+// Vibrate a virtual controller as a game would (Force mode; strength 0-1)
+export const testVirtualRumble = (targetSlot: number, strength: number, durationMs: number) =>
+  invoke<void>("test_virtual_rumble", { targetSlot, strength, durationMs });
+
+export const cancelIdentify = () => invoke<void>("cancel_identify");
+
+// Live state of all four XInput slots (see onSlotsPreview); refused during Force mode
+export const startAllSlotsPreview = () => invoke<void>("start_all_slots_preview");
+
+export const stopAllSlotsPreview = () => invoke<void>("stop_all_slots_preview");
+
+// Input recording/playback for testing mappings
+export const recordInput = (path: string, slot: number, durationMs: number) =>
+  invoke<number>("record_input", { path, slot, durationMs });
+
+export const replayInput = (path: string, targetSlot: number) =>
+  invoke<void>("replay_input", { path, targetSlot });
+
 // Game rules
 export const getGameRules = () => invoke<GameRule[]>("get_game_rules");
 
-export const addGameRule = (exeName: string, profileId: string) =>
-  invoke<GameRule>("add_game_rule", { exeName, profileId });
+export const addGameRule = (exeNames: string | string[], profileId: string) =>
+  invoke<GameRule>("add_game_rule", {
+    exeNames: Array.isArray(exeNames) ? exeNames : [exeNames],
+    profileId,
+  });
 
 export const deleteGameRule = (ruleId: string) =>
   invoke<void>("delete_game_rule", { ruleId });
@@ -64,18 +224,63 @@ export const deleteGameRule = (ruleId: string) =>
 export const toggleGameRule = (ruleId: string, enabled: boolean) =>
   invoke<void>("toggle_game_rule", { ruleId, enabled });
 
+export const setGameRuleDelay = (ruleId: string, activationDelayMs: number) =>
+  invoke<void>("set_game_rule_delay", { ruleId, activationDelayMs });
+
+export const duplicateGameRule = (ruleId: string) =>
+  invoke<GameRule>("duplicate_game_rule", { ruleId });
+
+export const moveGameRule = (ruleId: string, direction: MoveDirection) =>
+  invoke<GameRule[]>("move_game_rule", { ruleId, direction });
+
 // Process watcher
 export const startProcessWatcher = () => invoke<void>("start_process_watcher");
 
 export const stopProcessWatcher = () => invoke<void>("stop_process_watcher");
 
+export const setAutoSwitch = (enabled: boolean) => invoke<void>("set_auto_switch", { enabled });
+
 export const isWatcherRunning = () => invoke<boolean>("is_watcher_running");
 
 // Reset
 export const resetAll = () => invoke<void>("reset_all");
 
+/** Dirty-shutdown recovery on demand: enumerates afresh, unlike `resetAll`. */
+export const recoverNow = () => invoke<void>("recover_now");
+
+export const resetDevice = (deviceId: string) =>
+  invoke<void>("reset_device", { deviceId });
+
+// Manual device mode (only devices marked as controllers are enumerated)
+export const getInputDevices = () => invoke<PhysicalDevice[]>("get_input_devices");
+
+export const getMarkedControllers = () => invoke<string[]>("get_marked_controllers");
+
+export const markAsController = (deviceId: string, marked: boolean) =>
+  invoke<string[]>("mark_as_controller", { deviceId, marked });
+
+// Persistent hide (kept hidden across sessions)
+export const getPersistentHidden = () => invoke<string[]>("get_persistent_hidden");
+
+export const addPersistentHide = (deviceId: string) =>
+  invoke<string[]>("add_persistent_hide", { deviceId });
+
+export const removePersistentHide = (instancePath: string) =>
+  invoke<string[]>("remove_persistent_hide", { instancePath });
+
 // Settings
 export const getSettings = () => invoke<Settings>("get_settings");
 
 export const updateSettings = (settings: Settings) =>
   invoke<void>("update_settings", { settings });
+
+// Config file tooling
+export const getConfigSchema = () =>
+  invoke<Record<string, unknown>>("get_config_schema");
+
+export const validateConfigFile = (path: string) =>
+  invoke<string[]>("validate_config_file", { path });
+
+export const backupConfig = (path: string) => invoke<void>("backup_config", { path });
+
+export const restoreConfig = (path: string) => invoke<AppConfig>("restore_config", { path });
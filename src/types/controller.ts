@@ -10,12 +10,84 @@ export interface PhysicalDevice {
   vendor_id: number;
   product_id: number;
   xinput_slot: number | null;
+  /** XInput device left without a slot because all four are taken (not DirectInput). */
+  xinput_overflow: boolean;
+}
+
+export interface DeviceCapabilities {
+  buttons: string[];
+  axes: string[];
+}
+
+export type Trigger = "Left" | "Right";
+
+export interface TriggerToButton {
+  trigger: Trigger;
+  button: number;
+  threshold: number;
+}
+
+export interface ButtonToTrigger {
+  button: number;
+  trigger: Trigger;
+}
+
+export interface ButtonRemap {
+  from: number;
+  to: number;
+}
+
+export type RemapPreset = "Xbox" | "Nintendo" | "PlayStationCircleConfirm";
+
+export type GamepadAxis =
+  | "LeftStickX"
+  | "LeftStickY"
+  | "RightStickX"
+  | "RightStickY"
+  | "LeftTrigger"
+  | "RightTrigger";
+
+/** Linux: read a raw evdev axis (`ABS_*` code) as a gamepad axis. */
+export interface AxisOverride {
+  source: number;
+  target: GamepadAxis;
+}
+
+/** Input groups an assignment forwards; masked groups are sent as neutral. */
+export interface InputMask {
+  buttons: boolean;
+  dpad: boolean;
+  left_stick: boolean;
+  right_stick: boolean;
+  triggers: boolean;
+}
+
+export interface InputMapping {
+  trigger_to_button: TriggerToButton[];
+  button_to_trigger: ButtonToTrigger[];
+  button_remap: ButtonRemap[];
+  left_trigger_deadzone: number;
+  right_trigger_deadzone: number;
+  invert_left_trigger: boolean;
+  invert_right_trigger: boolean;
+  trigger_output_min: number;
+  trigger_output_max: number;
+  left_stick_deadzone: number;
+  right_stick_deadzone: number;
+  left_anti_deadzone: number;
+  right_anti_deadzone: number;
+  input_mask: InputMask;
 }
 
 export interface SlotAssignment {
   device_id: string;
   slot: number;
   enabled: boolean;
+  mapping?: InputMapping;
+  /** Keep the device's current XInput slot as the target (`slot` is the fallback). */
+  passthrough?: boolean;
+  /** Route this device with a different mode than the profile's. */
+  routing_mode?: RoutingMode | null;
 }
 
 export interface DriverStatus {
@@ -23,28 +95,264 @@ export interface DriverStatus {
   vigembus_installed: boolean;
   hidhide_version: string | null;
   vigembus_version: string | null;
+  xinput_version: string | null;
 }
 
 export type RoutingMode = "Minimal" | "Force";
 
+export type XInputVersion = "Auto" | "XInput1_4" | "XInput1_3" | "XInput9_1_0";
+
+export interface EffectiveRoutingMode {
+  mode: RoutingMode;
+  reason: string;
+  profile_id: string | null;
+  requires_elevation: boolean;
+}
+
+/** Whether pending settings/profile edits need forwarding (or the app) restarted. */
+export interface RestartCheck {
+  required: boolean;
+  app_restart: boolean;
+  reasons: string[];
+}
+
+export type VirtualSubtype = "Xbox360" | "XboxSeries";
+
 export interface Profile {
   id: string;
   name: string;
   assignments: SlotAssignment[];
   routing_mode: RoutingMode;
+  hard_hide: boolean;
+  virtual_subtype: VirtualSubtype;
+  /** Shared layout the Force mode targets come from, overriding `virtual_subtype`. */
+  virtual_layout_id: string | null;
+}
+
+/** A new profile for `save_profile`; the backend assigns the id. */
+export interface ProfileInput {
+  name: string;
+  assignments: SlotAssignment[];
+  routing_mode?: RoutingMode;
+  hard_hide?: boolean;
+  virtual_subtype?: VirtualSubtype;
+  virtual_layout_id?: string | null;
+}
+
+/** Reusable Force mode targets, in creation order (at most 4). */
+export interface VirtualLayout {
+  id: string;
+  name: string;
+  targets: VirtualSubtype[];
+}
+
+/** A newly saved profile and warnings about assignments that won't forward. */
+export interface SavedProfile {
+  profile: Profile;
+  warnings: string[];
+}
+
+/** Whether a saved profile could start right now. */
+export interface ProfileRunStatus {
+  can_run: boolean;
+  blockers: string[];
+}
+
+export interface ProfileWithStatus {
+  profile: Profile;
+  status: ProfileRunStatus;
 }
 
 export interface GameRule {
   id: string;
-  exe_name: string;
+  /** Matches if any of these executables is running. */
+  exe_names: string[];
   profile_id: string;
   enabled: boolean;
+  /** Wait this long after a match (re-checking the process) before switching. */
+  activation_delay_ms: number;
 }
 
+/** Rules match in list order; moving up gives a rule precedence. */
+export type MoveDirection = "Up" | "Down";
+
 export interface Settings {
   auto_start: boolean;
   start_minimized: boolean;
   auto_forward_on_launch: boolean;
   auto_switch: boolean;
   active_profile_id: string | null;
+  control_server_enabled: boolean;
+  control_server_port: number;
+  control_server_token: string | null;
+  overlay_whitelist_enabled: boolean;
+  overlay_whitelist_apps: string[];
+  on_game_exit: GameExitBehavior;
+  watcher_switch_cooldown_ms: number;
+  xinput_version: XInputVersion;
+  forward_guide_button: boolean;
+  minimal_watchdog_secs: number;
+  hide_interface_paths: boolean;
+  minimal_cleanup_only_own: boolean;
+  forward_only_when_focused: boolean;
+  focus_exe_names: string[];
+  conflict_processes: string[];
+  forward_rumble: boolean;
+  reset_suppresses_running_games: boolean;
+  reset_power_cycle: boolean;
+  reset_priority: string[];
+  neutralize_on_stop: boolean;
+  stop_settle_ms: number;
+  reset_chord_enabled: boolean;
+  /** XInput button bits that must all be held. */
+  reset_chord_buttons: number;
+  reset_chord_hold_ms: number;
+  /** Off: a crash is only reported (`dirty-shutdown-detected`), not auto-recovered. */
+  auto_recover_on_dirty_shutdown: boolean;
+  tray_profile_detail: TrayProfileDetail;
+  /** Only `AppConfig.marked_controllers` count as controllers. */
+  manual_device_mode: boolean;
+  /** Record device state changes to `audit.log` in the data dir. */
+  audit_log_enabled: boolean;
 }
+
+export interface AppConfig {
+  settings: Settings;
+  profiles: Profile[];
+  game_rules: GameRule[];
+  persistent_hidden: string[];
+  /** Axis overrides by device id. */
+  axis_overrides: Record<string, AxisOverride[]>;
+  /** Device ids marked as controllers (manual device mode). */
+  marked_controllers: string[];
+  virtual_layouts: VirtualLayout[];
+}
+
+export interface ConflictWarning {
+  source: string;
+  message: string;
+  remediation: string;
+}
+
+export type GameExitBehavior = "RevertToPrevious" | "StopForwarding" | "KeepProfile";
+
+/** Tray submenu detail: `*` marker only, plus routing mode, or both plus `▶` while forwarding. */
+export type TrayProfileDetail = "Marker" | "RoutingMode" | "Full";
+
+export interface ResolvedAssignment {
+  device_id: string;
+  instance_path: string;
+  xinput_slot: number | null;
+  target_slot: number;
+  mapping: InputMapping;
+  passthrough: boolean;
+  routing_mode: RoutingMode | null;
+}
+
+export type HideCandidateKind = "Instance" | "Child" | "Interface" | "Parent";
+
+export interface HideCandidate {
+  path: string;
+  kind: HideCandidateKind;
+  blacklisted: boolean;
+}
+
+export interface GamepadState {
+  buttons: number;
+  left_trigger: number;
+  right_trigger: number;
+  thumb_lx: number;
+  thumb_ly: number;
+  thumb_rx: number;
+  thumb_ry: number;
+}
+
+export interface HiddenDevicePreview {
+  device_id: string;
+  name: string;
+  instance_path: string;
+}
+
+export type UnresolvedReason =
+  | "DeviceNotFound"
+  | "Disabled"
+  | "NoXInputSlot"
+  | "XInputSlotsFull"
+  | "Disconnected"
+  | "SlotReserved";
+
+export interface UnresolvedAssignment {
+  device_id: string;
+  target_slot: number;
+  reason: UnresolvedReason;
+}
+
+export interface AssignmentResolution {
+  resolved: ResolvedAssignment[];
+  unresolved: UnresolvedAssignment[];
+  /** Devices feeding more than one target — allowed, but worth a warning. */
+  shared_devices: string[];
+}
+
+export interface BackgroundError {
+  /** "forwarding", "watcher" or "auto-forward". */
+  source: string;
+  message: string;
+  /** RFC 3339 timestamp. */
+  at: string;
+}
+
+export interface AppInfo {
+  app_version: string;
+  os_name: string;
+  os_version: string;
+  arch: string;
+  elevated: boolean;
+}
+
+export interface DeviceSlotRow {
+  name: string;
+  vendor_id: number;
+  product_id: number;
+  instance_path: string;
+  device_type: DeviceType;
+  is_xinput: boolean;
+  xinput_slot: number | null;
+  xinput_overflow: boolean;
+}
+
+/** Device-to-slot table for support threads; `text` is a preformatted table. */
+export interface DeviceSlotTable {
+  rows: DeviceSlotRow[];
+  text: string;
+}
+
+export interface Diagnostics {
+  app: AppInfo;
+  backend: string;
+  driver_status: DriverStatus;
+  routing: EffectiveRoutingMode;
+  forwarding_active: boolean;
+  devices: DeviceSlotTable;
+  last_background_error: BackgroundError | null;
+}
+
+/** Software pipeline latency of one virtual controller (not total input lag). */
+export interface LatencyReport {
+  target_slot: number;
+  samples: number;
+  min_us: number;
+  avg_us: number;
+  max_us: number;
+}
+
+export interface VirtualControllerInfo {
+  index: number;
+  target_slot: number;
+  xinput_slot: number | null;
+  device_ids: string[];
+  /** How rumble reaches the physical pads; "Unavailable" if ViGEm notifications failed. */
+  rumble: RumblePath;
+}
+
+export type RumblePath = "Notification" | "Unavailable" | "Off" | "NoMotors";